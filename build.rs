@@ -6,11 +6,7 @@ use dotenvy::dotenv;
 use sqlx::migrate::MigrateDatabase;
 use sqlx::{Sqlite, SqlitePool};
 
-async fn add_admin<'a>(
-    uname: String,
-    pass: String,
-    db_pool: SqlitePool,
-) -> Result<(), BcryptError> {
+async fn add_admin(uname: String, pass: String, db_pool: SqlitePool) -> Result<(), BcryptError> {
     // why no argon2 , veruthe : )
     let passwd = hash(pass, DEFAULT_COST)?;
     sqlx::query!(
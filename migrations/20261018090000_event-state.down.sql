@@ -0,0 +1,2 @@
+DROP INDEX idx_events_state;
+ALTER TABLE events DROP COLUMN state;
@@ -0,0 +1,12 @@
+-- Persist event lifecycle state (new/active/end) so the current event
+-- can be restored on server restart : )
+ALTER TABLE events ADD COLUMN state TEXT NOT NULL DEFAULT 'new';
+
+-- only one event could be added at a time before state tracking, so the
+-- latest event is the one still served and every older one had ended
+UPDATE events SET state = CASE
+    WHEN id = (SELECT id FROM events ORDER BY created_at DESC LIMIT 1) THEN 'active'
+    ELSE 'end'
+END;
+
+CREATE INDEX idx_events_state ON events (state);
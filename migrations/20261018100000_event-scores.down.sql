@@ -0,0 +1,5 @@
+DROP TRIGGER event_teams_score_insert;
+DROP TRIGGER event_users_score_insert;
+DROP TRIGGER event_teams_score_delete;
+DROP TRIGGER event_users_score_delete;
+DROP TABLE event_scores;
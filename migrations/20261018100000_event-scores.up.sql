@@ -0,0 +1,40 @@
+-- Event scoped scores : )
+-- participant_id is a team id for team events and a user id for user events
+-- teams.score and users.score are kept for fts but no longer updated
+CREATE TABLE event_scores (
+    event_id UUID,
+    participant_id UUID,
+    score INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (event_id, participant_id),
+    FOREIGN KEY (event_id) REFERENCES events (id) ON DELETE CASCADE
+);
+
+-- index for fast vaderboard reads
+CREATE INDEX idx_event_scores_score ON event_scores (event_id, score);
+
+-- move existing scores to their events
+INSERT INTO event_scores (event_id, participant_id, score)
+SELECT et.event_id, et.team_id, IFNULL(t.score, 0) FROM event_teams et
+JOIN teams t ON t.id = et.team_id;
+
+INSERT INTO event_scores (event_id, participant_id, score)
+SELECT eu.event_id, eu.user_id, IFNULL(u.score, 0) FROM event_users eu
+JOIN users u ON u.id = eu.user_id;
+
+-- Keep event_scores in sync with event participants
+
+CREATE TRIGGER event_teams_score_insert AFTER INSERT ON event_teams BEGIN
+  INSERT INTO event_scores (event_id, participant_id) VALUES (new.event_id, new.team_id);
+END;
+
+CREATE TRIGGER event_users_score_insert AFTER INSERT ON event_users BEGIN
+  INSERT INTO event_scores (event_id, participant_id) VALUES (new.event_id, new.user_id);
+END;
+
+CREATE TRIGGER event_teams_score_delete AFTER DELETE ON event_teams BEGIN
+  DELETE FROM event_scores WHERE event_id = old.event_id AND participant_id = old.team_id;
+END;
+
+CREATE TRIGGER event_users_score_delete AFTER DELETE ON event_users BEGIN
+  DELETE FROM event_scores WHERE event_id = old.event_id AND participant_id = old.user_id;
+END;
@@ -0,0 +1,2 @@
+DROP TRIGGER score_ledger_immutable;
+DROP TABLE score_ledger;
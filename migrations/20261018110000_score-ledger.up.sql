@@ -0,0 +1,22 @@
+-- Append only ledger of every score change : )
+CREATE TABLE score_ledger (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_id UUID NOT NULL,
+    participant_id UUID NOT NULL,
+    delta INTEGER NOT NULL,
+    total INTEGER NOT NULL,
+    admin TEXT,
+    reason TEXT,
+    created_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (event_id) REFERENCES events (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_score_ledger_event ON score_ledger (event_id, created_at);
+CREATE INDEX idx_score_ledger_participant ON score_ledger (event_id, participant_id, created_at);
+
+-- recorded score changes cannot be modified
+CREATE TRIGGER score_ledger_immutable BEFORE UPDATE OF 
+event_id, participant_id, delta, total, admin, reason, created_at ON score_ledger
+BEGIN
+  SELECT RAISE(ABORT,'score_ledger entries cannot be modified');
+END;
@@ -0,0 +1,10 @@
+DROP TRIGGER score_ledger_revert_once;
+DROP TRIGGER score_ledger_immutable;
+CREATE TRIGGER score_ledger_immutable BEFORE UPDATE OF 
+event_id, participant_id, delta, total, admin, reason, created_at ON score_ledger
+BEGIN
+  SELECT RAISE(ABORT,'score_ledger entries cannot be modified');
+END;
+ALTER TABLE score_ledger DROP COLUMN source;
+ALTER TABLE score_ledger DROP COLUMN revert_of;
+ALTER TABLE score_ledger DROP COLUMN reverted_by;
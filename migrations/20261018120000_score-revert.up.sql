@@ -0,0 +1,21 @@
+-- Track reverted score changes : )
+-- reverted_by : compensating entry that reverted this entry
+-- revert_of : entry reverted by this compensating entry
+ALTER TABLE score_ledger ADD COLUMN reverted_by INTEGER REFERENCES score_ledger (id);
+ALTER TABLE score_ledger ADD COLUMN revert_of INTEGER REFERENCES score_ledger (id);
+-- what made the score change, only manual ones can be reverted
+ALTER TABLE score_ledger ADD COLUMN source TEXT NOT NULL DEFAULT 'manual';
+
+-- score changes can be reverted only once
+CREATE TRIGGER score_ledger_revert_once BEFORE UPDATE OF reverted_by, revert_of ON score_ledger
+WHEN old.reverted_by IS NOT NULL OR old.revert_of IS NOT NULL
+BEGIN
+  SELECT RAISE(ABORT,'score_ledger entry already reverted');
+END;
+
+DROP TRIGGER score_ledger_immutable;
+CREATE TRIGGER score_ledger_immutable BEFORE UPDATE OF 
+event_id, participant_id, delta, total, admin, reason, created_at, source ON score_ledger
+BEGIN
+  SELECT RAISE(ABORT,'score_ledger entries cannot be modified');
+END;
@@ -0,0 +1,2 @@
+ALTER TABLE events DROP COLUMN max_score;
+ALTER TABLE events DROP COLUMN min_score;
@@ -0,0 +1,3 @@
+-- Optional per-event score bounds, NULL means unbounded : )
+ALTER TABLE events ADD COLUMN min_score INTEGER;
+ALTER TABLE events ADD COLUMN max_score INTEGER;
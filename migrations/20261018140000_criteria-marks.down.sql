@@ -0,0 +1,3 @@
+DROP TABLE criteria_marks;
+ALTER TABLE events DROP COLUMN show_breakdown;
+ALTER TABLE events DROP COLUMN criteria;
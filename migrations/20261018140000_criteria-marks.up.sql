@@ -0,0 +1,15 @@
+-- Weighted judging criteria of an event stored as json : )
+-- [{"name":"innovation","weight":30,"max_marks":10}, ...]
+ALTER TABLE events ADD COLUMN criteria TEXT;
+ALTER TABLE events ADD COLUMN show_breakdown INTEGER NOT NULL DEFAULT 0;
+
+-- Per criterion marks of each participant
+-- event_scores.score holds the weighted total of these marks
+CREATE TABLE criteria_marks (
+    event_id UUID,
+    participant_id UUID,
+    criterion TEXT NOT NULL,
+    marks INTEGER NOT NULL,
+    PRIMARY KEY (event_id, participant_id, criterion),
+    FOREIGN KEY (event_id, participant_id) REFERENCES event_scores (event_id, participant_id) ON DELETE CASCADE
+);
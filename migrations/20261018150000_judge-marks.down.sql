@@ -0,0 +1,16 @@
+CREATE TABLE criteria_marks (
+    event_id UUID,
+    participant_id UUID,
+    criterion TEXT NOT NULL,
+    marks INTEGER NOT NULL,
+    PRIMARY KEY (event_id, participant_id, criterion),
+    FOREIGN KEY (event_id, participant_id) REFERENCES event_scores (event_id, participant_id) ON DELETE CASCADE
+);
+
+INSERT INTO criteria_marks (event_id, participant_id, criterion, marks)
+SELECT event_id, participant_id, criterion, ROUND(AVG(marks)) FROM judge_marks
+GROUP BY event_id, participant_id, criterion;
+
+DROP INDEX idx_judge_marks_judge;
+DROP TABLE judge_marks;
+ALTER TABLE events DROP COLUMN aggregation;
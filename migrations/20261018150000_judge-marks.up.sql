@@ -0,0 +1,20 @@
+-- Marks are kept per judge, event score is the aggregate of judge totals : )
+ALTER TABLE events ADD COLUMN aggregation TEXT NOT NULL DEFAULT 'mean';
+
+CREATE TABLE judge_marks (
+    event_id UUID,
+    participant_id UUID,
+    judge TEXT NOT NULL,
+    criterion TEXT NOT NULL,
+    marks INTEGER NOT NULL,
+    PRIMARY KEY (event_id, participant_id, judge, criterion),
+    FOREIGN KEY (event_id, participant_id) REFERENCES event_scores (event_id, participant_id) ON DELETE CASCADE
+);
+
+-- marks entered before judges were tracked belong to an unnamed judge
+INSERT INTO judge_marks (event_id, participant_id, judge, criterion, marks)
+SELECT event_id, participant_id, '', criterion, marks FROM criteria_marks;
+
+DROP TABLE criteria_marks;
+
+CREATE INDEX idx_judge_marks_judge ON judge_marks (event_id, judge);
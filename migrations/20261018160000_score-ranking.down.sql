@@ -0,0 +1,2 @@
+ALTER TABLE event_scores DROP COLUMN updated_at;
+ALTER TABLE events DROP COLUMN ranking;
@@ -0,0 +1,10 @@
+-- Ranking mode of vaderboard : standard (1224), dense (1223) or ordinal
+ALTER TABLE events ADD COLUMN ranking TEXT NOT NULL DEFAULT 'standard';
+
+-- time of last score change, used to break ties (earliest to reach score first)
+ALTER TABLE event_scores ADD COLUMN updated_at DATETIME;
+
+UPDATE event_scores SET updated_at = (
+    SELECT MAX(sl.created_at) FROM score_ledger sl
+    WHERE sl.event_id = event_scores.event_id AND sl.participant_id = event_scores.participant_id
+);
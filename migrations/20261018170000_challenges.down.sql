@@ -0,0 +1,3 @@
+DROP INDEX idx_solves_participant;
+DROP TABLE solves;
+DROP TABLE challenges;
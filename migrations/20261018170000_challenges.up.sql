@@ -0,0 +1,29 @@
+-- CTF challenges of an event : )
+-- value decays from points to min_points over decay solves, first solver gets first_blood bonus
+CREATE TABLE challenges (
+    id UUID PRIMARY KEY,
+    event_id UUID NOT NULL,
+    name TEXT NOT NULL,
+    category TEXT NOT NULL,
+    points INTEGER NOT NULL,
+    min_points INTEGER,
+    decay INTEGER,
+    first_blood INTEGER NOT NULL DEFAULT 0,
+    -- bcrypt hash of the flag, NULL if solves are only recorded by admins
+    flag TEXT,
+    created_at DATETIME NOT NULL,
+    UNIQUE (event_id, name),
+    FOREIGN KEY (event_id) REFERENCES events (id) ON DELETE CASCADE
+);
+
+CREATE TABLE solves (
+    challenge_id UUID,
+    event_id UUID NOT NULL,
+    participant_id UUID,
+    solved_at DATETIME NOT NULL,
+    PRIMARY KEY (challenge_id, participant_id),
+    FOREIGN KEY (challenge_id) REFERENCES challenges (id) ON DELETE CASCADE,
+    FOREIGN KEY (event_id, participant_id) REFERENCES event_scores (event_id, participant_id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_solves_participant ON solves (event_id, participant_id);
@@ -0,0 +1,2 @@
+DROP TABLE matches;
+ALTER TABLE events DROP COLUMN bracket;
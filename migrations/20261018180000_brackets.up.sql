@@ -0,0 +1,29 @@
+-- Tournament bracket format of an event, NULL for plain leaderboard events : )
+-- single_elimination, double_elimination or round_robin
+ALTER TABLE events ADD COLUMN bracket TEXT;
+
+-- Matches generated from event participants on event start
+-- a slot is pending when participant is NULL and bye is 0
+CREATE TABLE matches (
+    event_id UUID,
+    id INTEGER NOT NULL,
+    stage TEXT NOT NULL,
+    round INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    participant_a UUID,
+    participant_b UUID,
+    bye_a INTEGER NOT NULL DEFAULT 0,
+    bye_b INTEGER NOT NULL DEFAULT 0,
+    score_a INTEGER,
+    score_b INTEGER,
+    winner UUID,
+    done INTEGER NOT NULL DEFAULT 0,
+    -- match id and slot (0 = a, 1 = b) taken by winner / loser
+    next_match INTEGER,
+    next_slot INTEGER,
+    loser_match INTEGER,
+    loser_slot INTEGER,
+    completed_at DATETIME,
+    PRIMARY KEY (event_id, id),
+    FOREIGN KEY (event_id) REFERENCES events (id) ON DELETE CASCADE
+);
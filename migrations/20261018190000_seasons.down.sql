@@ -0,0 +1,4 @@
+DROP INDEX idx_season_events_event;
+DROP TABLE season_events;
+DROP TABLE seasons;
+DROP TABLE event_placements;
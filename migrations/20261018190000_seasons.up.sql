@@ -0,0 +1,28 @@
+-- Final placement of each participant, recorded when event ends : )
+CREATE TABLE event_placements (
+    event_id UUID,
+    participant_id UUID,
+    placement INTEGER NOT NULL,
+    PRIMARY KEY (event_id, participant_id),
+    FOREIGN KEY (event_id) REFERENCES events (id) ON DELETE CASCADE
+);
+
+-- Seasons group events into an overall championship
+-- points is a json array of points by placement, [10,7,5] : 1st = 10, 2nd = 7, 3rd = 5
+CREATE TABLE seasons (
+    id UUID PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    points TEXT NOT NULL,
+    created_at DATETIME NOT NULL
+);
+
+CREATE TABLE season_events (
+    season_id UUID,
+    event_id UUID,
+    multiplier REAL NOT NULL DEFAULT 1,
+    PRIMARY KEY (season_id, event_id),
+    FOREIGN KEY (season_id) REFERENCES seasons (id) ON DELETE CASCADE,
+    FOREIGN KEY (event_id) REFERENCES events (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_season_events_event ON season_events (event_id);
@@ -0,0 +1,3 @@
+DROP TABLE revealed_participants;
+ALTER TABLE events DROP COLUMN revealed;
+ALTER TABLE events DROP COLUMN freeze_at;
@@ -0,0 +1,12 @@
+-- Vaderboard shows scores as of freeze_at until revealed : )
+ALTER TABLE events ADD COLUMN freeze_at DATETIME;
+ALTER TABLE events ADD COLUMN revealed INTEGER NOT NULL DEFAULT 0;
+
+-- Participants revealed one by one from the bottom of the frozen board
+CREATE TABLE revealed_participants (
+    event_id UUID,
+    participant_id UUID,
+    revealed_at DATETIME NOT NULL,
+    PRIMARY KEY (event_id, participant_id),
+    FOREIGN KEY (event_id) REFERENCES events (id) ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+ALTER TABLE events DROP COLUMN clock_remaining;
+ALTER TABLE events DROP COLUMN clock_ends_at;
+ALTER TABLE events DROP COLUMN end_at;
+ALTER TABLE events DROP COLUMN duration;
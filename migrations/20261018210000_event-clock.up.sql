@@ -0,0 +1,7 @@
+-- Countdown set up when event starts, duration in seconds : )
+ALTER TABLE events ADD COLUMN duration INTEGER;
+ALTER TABLE events ADD COLUMN end_at DATETIME;
+
+-- Current clock of active event, ends_at while running and remaining ms while paused
+ALTER TABLE events ADD COLUMN clock_ends_at DATETIME;
+ALTER TABLE events ADD COLUMN clock_remaining INTEGER;
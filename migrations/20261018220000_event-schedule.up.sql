@@ -0,0 +1,2 @@
+-- Event is started by the server at start_at : )
+ALTER TABLE events ADD COLUMN start_at DATETIME;
@@ -0,0 +1 @@
+ALTER TABLE event_scores DROP COLUMN key_hash;
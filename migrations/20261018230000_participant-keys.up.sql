@@ -0,0 +1,3 @@
+-- bcrypt hash of the key a participant logs in with to submit flags : )
+-- keys are issued by admins, NULL until one is issued
+ALTER TABLE event_scores ADD COLUMN key_hash TEXT;
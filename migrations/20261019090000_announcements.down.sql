@@ -0,0 +1,2 @@
+DROP INDEX idx_announcements_event;
+DROP TABLE announcements;
@@ -0,0 +1,13 @@
+-- Announcements of an event, kept across restarts until they expire or are removed : )
+-- severity is one of info / warning / critical
+CREATE TABLE announcements (
+    id UUID PRIMARY KEY,
+    event_id UUID NOT NULL,
+    text TEXT NOT NULL,
+    severity TEXT NOT NULL DEFAULT 'info',
+    created_at DATETIME NOT NULL,
+    expires_at DATETIME,
+    FOREIGN KEY (event_id) REFERENCES events (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_announcements_event ON announcements (event_id);
@@ -1,23 +1,33 @@
 use actix::Addr;
 use actix_session::Session;
-use actix_web::{post, web, Either, HttpResponse, Responder};
-use log::{error, info};
+use actix_web::{post, web, Either, HttpRequest, HttpResponse, Responder};
+use log::{debug, error, info};
 use sqlx::SqlitePool;
+use uuid::Uuid;
 
+use crate::models::bracket_models::{BracketGet, BracketSrv};
 use crate::models::command_models::{
-    CommandResponse, ContestantInfo, EventReq, MemberInfo, ScoreUpdate, TeamWithMembers,
+    AnnouncementReq, ChallengeReq, ClockReq, CommandResponse, ContestantInfo, CriteriaMarks,
+    EventReq, FlagSubmit, MatchResult, MemberInfo, ParticipantKey, ParticipantLogin, RevealReq,
+    ScoreLog, ScoreRevert, ScoreUpdate, SolveReq, TeamWithMembers,
 };
 use crate::models::error_models::VaderError;
+use crate::models::flag_models::FlagThrottle;
 use crate::models::query_models::{
-    CurFtsServer, CurFtsStop, EventInfo, EventType, IdQuery, TransferType, VboardGet, VboardSrv,
+    Announcement, EventInfo, EventType, IdQuery, TransferType, VbAnnounce, VbClock, VbRetract,
+    VboardGet, VboardSrv,
 };
-use crate::models::v_models::{AdminInfo, AppState, Event, Team, User, VaderEvent};
+use crate::models::schedule_models::{EventScheduler, EventSrvs, ScheduleEvent};
+use crate::models::season_models::{Season, SeasonEventReq, SeasonGet, SeasonReq, SeasonSrv};
+use crate::models::v_models::{AdminInfo, AppState, Challenge, Event, Team, User, VaderEvent};
 use crate::models::wrapper_models::{EventStateWrapper, EventWrapper};
+use crate::services::flag_services::participant_session_key;
 
 #[post("/event/add")]
 pub async fn add_event<'a>(
     event_data: web::Json<EventReq<'a>>,
     app_state: web::Data<AppState>,
+    scheduler: web::Data<Addr<EventScheduler>>,
     db_pool: web::Data<SqlitePool>,
 ) -> Either<impl Responder, impl Responder>
 where
@@ -26,46 +36,45 @@ where
     let event_data = event_data.into_inner();
     match event_data.event_type {
         EventType::TeamEvent { team_size: _ } => {
-            Either::Left(add_team_event(event_data, app_state, db_pool).await)
+            Either::Left(add_team_event(event_data, app_state, scheduler, db_pool).await)
+        }
+        EventType::UserEvent => {
+            Either::Right(add_user_event(event_data, app_state, scheduler, db_pool).await)
         }
-        EventType::UserEvent => Either::Right(add_user_event(event_data, app_state, db_pool).await),
     }
 }
 
 pub async fn add_team_event<'a>(
     event_info: EventReq<'a>,
     app_state: web::Data<AppState>,
+    scheduler: web::Data<Addr<EventScheduler>>,
     db_pool: web::Data<SqlitePool>,
 ) -> impl Responder
 where
     'a: 'static,
 {
-    let mut event_state = app_state.current_event.lock().await;
-    if event_state.is_some() {
-        error!("Request delined.Another Event already added.");
-        return HttpResponse::BadRequest()
-            .body("Another event already Added . Wait till the current Event ends");
-    }
     match Into::<Result<Event<Team>, VaderError>>::into(event_info) {
         Ok(event) => {
             let event_id = event.id;
             match event.add_event(&db_pool).await {
                 Ok(_) => {
                     info!("Successfully added team Event [id : {}]", event_id);
-                    *event_state = Some(EventWrapper::TeamEvent(EventStateWrapper::New(event)));
+                    let event = EventWrapper::TeamEvent(EventStateWrapper::New(event));
+                    scheduler.do_send(event.schedule(None));
+                    app_state.events.lock().await.insert(event_id, event);
                     HttpResponse::Ok().json(web::Json(CommandResponse::new(
                         "Successfully added team event",
                         event_id,
                     )))
                 }
                 Err(err) => {
-                    error!("Error adding Team event : {}", err.to_string());
+                    error!("Error adding Team event : {}", err);
                     HttpResponse::InternalServerError().body(err.to_string())
                 }
             }
         }
         Err(e) => {
-            error!("Error adding Team event : {}", e.to_string());
+            error!("Error adding Team event : {}", e);
             HttpResponse::InternalServerError().body(e.to_string())
         }
     }
@@ -73,131 +82,166 @@ where
 pub async fn add_user_event<'a>(
     event_info: EventReq<'a>,
     app_state: web::Data<AppState>,
+    scheduler: web::Data<Addr<EventScheduler>>,
     db_pool: web::Data<SqlitePool>,
 ) -> impl Responder
 where
     'a: 'static,
 {
-    let mut event_state = app_state.current_event.lock().await;
-    if event_state.is_some() {
-        error!("Request delined.Another Event already added.");
-        return HttpResponse::BadRequest()
-            .body("Another event already Added . Wait till the current Event ends");
-    }
     match Into::<Result<Event<User>, VaderError>>::into(event_info) {
         Ok(event) => {
             let event_id = event.id;
             match event.add_event(&db_pool).await {
                 Ok(_) => {
                     info!("Successfully added user Event [id : {}]", event_id);
-                    *event_state = Some(EventWrapper::UserEvent(EventStateWrapper::New(event)));
+                    let event = EventWrapper::UserEvent(EventStateWrapper::New(event));
+                    scheduler.do_send(event.schedule(None));
+                    app_state.events.lock().await.insert(event_id, event);
                     HttpResponse::Ok().json(web::Json(CommandResponse::new(
                         "Successfully added user Event",
                         event_id,
                     )))
                 }
                 Err(err) => {
-                    error!("Error adding User event : {}", err.to_string());
+                    error!("Error adding User event : {}", err);
                     HttpResponse::InternalServerError().body(err.to_string())
                 }
             }
         }
         Err(err) => {
-            error!("Error adding User event : {}", err.to_string());
+            error!("Error adding User event : {}", err);
             HttpResponse::InternalServerError().body(err.to_string())
         }
     }
 }
 
-#[post("/event/start")]
+#[post("/event/{event_id}/start")]
 pub async fn start_event(
-    app_state: web::Data<AppState>,
-    vb_srv: web::Data<Addr<VboardSrv>>,
-    db_pool: web::Data<SqlitePool>,
+    event_id: web::Path<Uuid>,
+    srvs: web::Data<EventSrvs>,
+    scheduler: web::Data<Addr<EventScheduler>>,
 ) -> impl Responder {
-    let mut event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        error!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to start event")
-    } else {
-        //reset score before starting event
-        let reset_res = event_state.as_ref().unwrap().reset_score(&db_pool).await;
-        if let Err(e) = reset_res {
-            match e {
-                VaderError::EventActive(_) => {}
-                _ => {
-                    error!("Error reseting score to start event");
-                    return HttpResponse::BadRequest().body(format!(
-                        "Error resetting score to start event.\n{}",
-                        e.to_string()
-                    ));
-                }
-            }
-        };
-        let res = event_state.as_mut().unwrap().start_event();
+    let event_id = event_id.into_inner();
+    let mut events = srvs.app_state.events.lock().await;
+    if let Some(event) = events.get_mut(&event_id) {
+        let res = event.start_event(&srvs.db_pool).await;
         match res {
-            Ok(_) => {
-                vb_srv.do_send(VboardGet(TransferType::Broadcast));
-                let body = format!(
-                    "Event id : [{}] started successfully",
-                    event_state.as_ref().unwrap().get_id()
-                );
+            Ok(clock) => {
+                srvs.started(event_id, clock);
+                // replaces a pending scheduled start
+                scheduler.do_send(event.schedule(clock));
+                let body = format!("Event id : [{}] started successfully", event_id);
                 info!("{}", body);
                 HttpResponse::Ok().body(body)
             }
             Err(e) => HttpResponse::BadRequest().body(e.to_string()),
         }
+    } else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to start event")
     }
 }
-#[post("/event/stop")]
+#[post("/event/{event_id}/stop")]
 pub async fn end_event(
-    app_state: web::Data<AppState>,
-    srv_addr: web::Data<Addr<CurFtsServer<'static>>>,
+    event_id: web::Path<Uuid>,
+    srvs: web::Data<EventSrvs>,
+    scheduler: web::Data<Addr<EventScheduler>>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let mut events = srvs.app_state.events.lock().await;
+    let Some(event) = events.get_mut(&event_id) else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        return HttpResponse::BadRequest()
+            .body("No event added with given id.Add event to stop event");
+    };
+    // ended event stays in the registry to serve its final standings
+    if let Err(e) = event.end_event(&srvs.db_pool).await {
+        return HttpResponse::BadRequest().body(e.to_string());
+    }
+    drop(events);
+    let body = format!("Event id : [{}] stopped successfully", event_id);
+    info!("{}", body);
+    scheduler.do_send(ScheduleEvent(event_id, None));
+    srvs.stopped(event_id).await;
+    HttpResponse::Ok().body(body)
+}
+
+#[post("/event/{event_id}/pause")]
+pub async fn pause_event(
+    event_id: web::Path<Uuid>,
+    srvs: web::Data<EventSrvs>,
+    scheduler: web::Data<Addr<EventScheduler>>,
 ) -> impl Responder {
-    let mut event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        error!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to start event")
+    let event_id = event_id.into_inner();
+    let mut events = srvs.app_state.events.lock().await;
+    if let Some(event) = events.get_mut(&event_id) {
+        let res = event.pause_event(&srvs.db_pool).await;
+        match res {
+            Ok(clock) => {
+                srvs.paused(event_id, clock);
+                // scheduled stop waits for the clock to run again
+                scheduler.do_send(event.schedule(clock));
+                let body = format!("Event id : [{}] paused successfully", event_id);
+                info!("{}", body);
+                HttpResponse::Ok().body(body)
+            }
+            Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+        }
     } else {
-        let res = event_state.as_mut().unwrap().end_event();
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to pause event")
+    }
+}
+#[post("/event/{event_id}/resume")]
+pub async fn resume_event(
+    event_id: web::Path<Uuid>,
+    srvs: web::Data<EventSrvs>,
+    scheduler: web::Data<Addr<EventScheduler>>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let mut events = srvs.app_state.events.lock().await;
+    if let Some(event) = events.get_mut(&event_id) {
+        let res = event.resume_event(&srvs.db_pool).await;
         match res {
-            Ok(_) => {
-                let body = format!(
-                    "Event id : [{}] stopped successfully",
-                    event_state.as_ref().unwrap().get_id()
-                );
+            Ok(clock) => {
+                srvs.resumed(event_id, clock);
+                scheduler.do_send(event.schedule(clock));
+                let body = format!("Event id : [{}] resumed successfully", event_id);
                 info!("{}", body);
-                *event_state = None;
-                srv_addr.do_send(CurFtsStop);
                 HttpResponse::Ok().body(body)
             }
             Err(e) => HttpResponse::BadRequest().body(e.to_string()),
         }
+    } else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to resume event")
     }
 }
 
-#[post("/score/update")]
+#[post("/event/{event_id}/score/update")]
 pub async fn update_score(
+    session: Session,
+    event_id: web::Path<Uuid>,
     score_req: web::Json<ScoreUpdate>,
     app_state: web::Data<AppState>,
     vb_srv: web::Data<Addr<VboardSrv>>,
     db_pool: web::Data<SqlitePool>,
 ) -> impl Responder {
-    let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        error!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to start event")
-    } else {
+    let event_id = event_id.into_inner();
+    let events = app_state.events.lock().await;
+    if let Some(event) = events.get(&event_id) {
         let sr = score_req.into_inner();
-        let score_res = event_state
-            .as_ref()
-            .unwrap()
-            .update_score_by_id(&sr.id, sr.score, &db_pool)
+        let log = ScoreLog {
+            admin: session.get::<String>("username").ok().flatten(),
+            reason: sr.reason,
+        };
+        let score_res = event
+            .update_score_by_id(&sr.id, sr.score, sr.mode, &log, &db_pool)
             .await;
         match score_res {
             Ok(_) => {
                 info!("Score updated successfully.");
-                vb_srv.do_send(VboardGet(TransferType::Broadcast));
+                vb_srv.do_send(VboardGet(event_id, TransferType::Broadcast));
                 HttpResponse::Ok().body("Score Updated")
             }
             Err(err) => {
@@ -205,22 +249,453 @@ pub async fn update_score(
                 HttpResponse::BadRequest().body(format!("Error updating Score : \n{}", err))
             }
         }
+    } else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to update score")
     }
 }
-#[post("/score/reset")]
-pub async fn reset_score(
+#[post("/event/{event_id}/score/update/batch")]
+pub async fn update_score_batch(
+    session: Session,
+    event_id: web::Path<Uuid>,
+    score_req: web::Json<Vec<ScoreUpdate>>,
     app_state: web::Data<AppState>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
     db_pool: web::Data<SqlitePool>,
 ) -> impl Responder {
-    let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        error!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to start event")
+    let event_id = event_id.into_inner();
+    let updates = score_req.into_inner();
+    if updates.is_empty() {
+        error!(
+            "Request delined.Empty score batch [event id : {}]",
+            event_id
+        );
+        return HttpResponse::BadRequest().body("No score updates in batch");
+    }
+    let events = app_state.events.lock().await;
+    if let Some(event) = events.get(&event_id) {
+        let log = ScoreLog {
+            admin: session.get::<String>("username").ok().flatten(),
+            reason: None,
+        };
+        let score_res = event.update_scores_by_id(&updates, &log, &db_pool).await;
+        match score_res {
+            Ok(_) => {
+                info!("Score batch of {} updated successfully.", updates.len());
+                vb_srv.do_send(VboardGet(event_id, TransferType::Broadcast));
+                HttpResponse::Ok().body("Scores Updated")
+            }
+            Err(err) => {
+                error!("Error updating score batch :\n[error] : {}", err);
+                HttpResponse::BadRequest().body(format!("Error updating Scores : \n{}", err))
+            }
+        }
     } else {
-        let res = event_state.as_ref().unwrap().reset_score(&db_pool).await;
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to update score")
+    }
+}
+#[post("/event/{event_id}/score/marks")]
+pub async fn update_marks(
+    session: Session,
+    event_id: web::Path<Uuid>,
+    marks_req: web::Json<CriteriaMarks>,
+    app_state: web::Data<AppState>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let events = app_state.events.lock().await;
+    if let Some(event) = events.get(&event_id) {
+        let mut marks = marks_req.into_inner();
+        let log = ScoreLog {
+            admin: session.get::<String>("username").ok().flatten(),
+            reason: marks.reason.take(),
+        };
+        let marks_res = event.update_marks_by_id(&marks, &log, &db_pool).await;
+        match marks_res {
+            Ok(_) => {
+                info!("Criteria marks updated successfully.");
+                vb_srv.do_send(VboardGet(event_id, TransferType::Broadcast));
+                HttpResponse::Ok().body("Marks Updated")
+            }
+            Err(err) => {
+                error!("Error updating marks :\n[error] : {}", err);
+                HttpResponse::BadRequest().body(format!("Error updating Marks : \n{}", err))
+            }
+        }
+    } else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to update marks")
+    }
+}
+#[post("/event/{event_id}/challenge/add")]
+pub async fn add_challenge(
+    event_id: web::Path<Uuid>,
+    challenge_req: web::Json<ChallengeReq<'static>>,
+    app_state: web::Data<AppState>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let challenge = match Challenge::from_req(challenge_req.into_inner()).await {
+        Ok(challenge) => challenge,
+        Err(err) => {
+            error!("Request delined.Invalid challenge :\n[error] : {}", err);
+            return HttpResponse::BadRequest().body(format!("Error adding Challenge : \n{}", err));
+        }
+    };
+    let events = app_state.events.lock().await;
+    if let Some(event) = events.get(&event_id) {
+        match event.add_challenge(&challenge, &db_pool).await {
+            Ok(_) => {
+                info!("Challenge added [id : {}]", challenge.id);
+                HttpResponse::Ok().json(web::Json(CommandResponse::new(
+                    "Challenge added successfully",
+                    challenge.id,
+                )))
+            }
+            Err(err) => {
+                error!("Error adding challenge :\n[error] : {}", err);
+                HttpResponse::BadRequest().body(format!("Error adding Challenge : \n{}", err))
+            }
+        }
+    } else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to add challenge")
+    }
+}
+#[post("/event/{event_id}/challenge/{challenge_id}/solve")]
+pub async fn record_solve(
+    session: Session,
+    path: web::Path<(Uuid, Uuid)>,
+    solve_req: web::Json<SolveReq>,
+    app_state: web::Data<AppState>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let (event_id, challenge_id) = path.into_inner();
+    let log = ScoreLog {
+        admin: session.get::<String>("username").ok().flatten(),
+        reason: None,
+    };
+    solve(
+        event_id,
+        challenge_id,
+        solve_req.id,
+        log,
+        app_state,
+        vb_srv,
+        db_pool,
+    )
+    .await
+}
+#[post("/event/{event_id}/challenge/{challenge_id}/flag")]
+pub async fn submit_flag(
+    (session, req): (Session, HttpRequest),
+    path: web::Path<(Uuid, Uuid)>,
+    flag_req: web::Json<FlagSubmit>,
+    app_state: web::Data<AppState>,
+    throttle: web::Data<FlagThrottle>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let (event_id, challenge_id) = path.into_inner();
+    let p_id = match session.get::<Uuid>(&participant_session_key(&event_id)) {
+        Ok(Some(p_id)) => p_id,
+        _ => {
+            debug!("Request delined.No participant session [id : {}]", event_id);
+            return HttpResponse::Unauthorized()
+                .body("Login as a participant of the event to submit flags");
+        }
+    };
+    let keys = FlagThrottle::keys(Some(p_id), &req);
+    if let Err(e) = throttle.check(&keys) {
+        debug!("Request delined.{}", e);
+        return HttpResponse::TooManyRequests().body(e.to_string());
+    }
+    let flag_req = flag_req.into_inner();
+    // flag is checked before taking the event lock, bcrypt is slow : )
+    match Challenge::check_flag(&event_id, &challenge_id, flag_req.flag, &db_pool).await {
+        Ok(true) => {
+            let log = ScoreLog {
+                admin: None,
+                reason: None,
+            };
+            solve(
+                event_id,
+                challenge_id,
+                p_id,
+                log,
+                app_state,
+                vb_srv,
+                db_pool,
+            )
+            .await
+        }
+        Ok(false) => {
+            info!(
+                "Wrong flag submitted [challenge id : {}] [participant id : {}]",
+                challenge_id, p_id
+            );
+            throttle.failed(&keys);
+            HttpResponse::BadRequest().body("Wrong flag")
+        }
+        Err(err) => {
+            error!("Error checking flag :\n[error] : {}", err);
+            HttpResponse::BadRequest().body(format!("Error checking Flag : \n{}", err))
+        }
+    }
+}
+// Participant session used to submit flags of the event
+#[post("/event/{event_id}/login")]
+pub async fn participant_login(
+    session: Session,
+    req: HttpRequest,
+    event_id: web::Path<Uuid>,
+    login_req: web::Json<ParticipantLogin>,
+    throttle: web::Data<FlagThrottle>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let login_req = login_req.into_inner();
+    let p_id = login_req.id;
+    let keys = FlagThrottle::keys(Some(p_id), &req);
+    if let Err(e) = throttle.check(&keys) {
+        debug!("Request delined.{}", e);
+        return HttpResponse::TooManyRequests().body(e.to_string());
+    }
+    match login_req.verify_key(&event_id, &db_pool).await {
+        Ok(true) => {
+            if session
+                .insert(participant_session_key(&event_id), p_id)
+                .is_ok()
+            {
+                info!(
+                    "Participant [id : {}] logged in to event [id : {}]",
+                    p_id, event_id
+                );
+                HttpResponse::Ok().body("Login Successful")
+            } else {
+                error!("Unable to get Participant Session");
+                HttpResponse::InternalServerError().finish()
+            }
+        }
+        Ok(false) => {
+            throttle.failed(&keys);
+            debug!("Invalid participant key [id : {}]", p_id);
+            HttpResponse::Unauthorized().body("Invalid participant id/key")
+        }
+        Err(e) => {
+            error!("Participant auth error : {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+#[post("/event/{event_id}/participant/{participant_id}/key")]
+pub async fn issue_participant_key(
+    path: web::Path<(Uuid, Uuid)>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let (event_id, p_id) = path.into_inner();
+    match ParticipantLogin::issue_key(&event_id, &p_id, &db_pool).await {
+        Ok(key) => {
+            info!("Key issued to participant [id : {}]", p_id);
+            HttpResponse::Ok().json(web::Json(ParticipantKey { id: p_id, key }))
+        }
+        Err(err) => {
+            error!("Error issuing participant key :\n[error] : {}", err);
+            HttpResponse::BadRequest().body(format!("Error issuing Participant key : \n{}", err))
+        }
+    }
+}
+async fn solve(
+    event_id: Uuid,
+    challenge_id: Uuid,
+    p_id: Uuid,
+    log: ScoreLog,
+    app_state: web::Data<AppState>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    db_pool: web::Data<SqlitePool>,
+) -> HttpResponse {
+    let events = app_state.events.lock().await;
+    if let Some(event) = events.get(&event_id) {
+        match event
+            .record_solve(&challenge_id, &p_id, &log, &db_pool)
+            .await
+        {
+            Ok(_) => {
+                info!(
+                    "Challenge [id : {}] solved by [id : {}]",
+                    challenge_id, p_id
+                );
+                vb_srv.do_send(VboardGet(event_id, TransferType::Broadcast));
+                HttpResponse::Ok().body("Challenge Solved")
+            }
+            Err(err) => {
+                error!("Error recording solve :\n[error] : {}", err);
+                HttpResponse::BadRequest().body(format!("Error recording Solve : \n{}", err))
+            }
+        }
+    } else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to record solve")
+    }
+}
+#[post("/event/{event_id}/bracket/match/{match_id}/result")]
+pub async fn record_match(
+    path: web::Path<(Uuid, u32)>,
+    result: web::Json<MatchResult>,
+    app_state: web::Data<AppState>,
+    br_srv: web::Data<Addr<BracketSrv>>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let (event_id, match_id) = path.into_inner();
+    let events = app_state.events.lock().await;
+    if let Some(event) = events.get(&event_id) {
+        match event.record_match(match_id, &result, &db_pool).await {
+            Ok(_) => {
+                info!(
+                    "Match [id : {}] result recorded for event [id : {}]",
+                    match_id, event_id
+                );
+                br_srv.do_send(BracketGet(event_id, TransferType::Broadcast));
+                HttpResponse::Ok().body("Match result recorded")
+            }
+            Err(err) => {
+                error!("Error recording match result :\n[error] : {}", err);
+                HttpResponse::BadRequest().body(format!("Error recording Match result : \n{}", err))
+            }
+        }
+    } else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to record match")
+    }
+}
+#[post("/event/{event_id}/reveal")]
+pub async fn reveal_vboard(
+    event_id: web::Path<Uuid>,
+    reveal_req: web::Json<RevealReq>,
+    srvs: web::Data<EventSrvs>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    if let Err(e) = srvs.app_state.load_event(event_id, &srvs.db_pool).await {
+        return HttpResponse::InternalServerError().body(e.to_string());
+    }
+    let events = srvs.app_state.events.lock().await;
+    let Some(event) = events.get(&event_id) else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        return HttpResponse::BadRequest()
+            .body("No event added with given id.Add event to reveal vaderboard");
+    };
+    let ended = event.is_ended();
+    let revealed = match event.reveal(reveal_req.mode, &srvs.db_pool).await {
+        Ok(revealed) => revealed,
+        Err(err) => {
+            error!("Error revealing vaderboard :\n[error] : {}", err);
+            return HttpResponse::BadRequest()
+                .body(format!("Error revealing Vaderboard : \n{}", err));
+        }
+    };
+    drop(events);
+    srvs.vb_srv
+        .do_send(VboardGet(event_id, TransferType::Broadcast));
+    // revealed scores change placements of an ended event
+    if ended {
+        srvs.placed(event_id).await;
+    }
+    match revealed {
+        Some(p_id) => {
+            info!("Participant [id : {}] revealed", p_id);
+            HttpResponse::Ok().json(web::Json(CommandResponse::new(
+                "Participant revealed",
+                p_id,
+            )))
+        }
+        None => {
+            info!("Vaderboard of event [id : {}] revealed", event_id);
+            HttpResponse::Ok().body("Vaderboard revealed")
+        }
+    }
+}
+#[post("/event/{event_id}/clock")]
+pub async fn update_clock(
+    event_id: web::Path<Uuid>,
+    clock_req: web::Json<ClockReq>,
+    app_state: web::Data<AppState>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    scheduler: web::Data<Addr<EventScheduler>>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let events = app_state.events.lock().await;
+    if let Some(event) = events.get(&event_id) {
+        match event.update_clock(&clock_req, &db_pool).await {
+            Ok(clock) => {
+                vb_srv.do_send(VbClock(event_id, Some(clock)));
+                // scheduled stop follows the clock
+                scheduler.do_send(event.schedule(Some(clock)));
+                info!("Clock of event [id : {}] updated", event_id);
+                HttpResponse::Ok().json(web::Json(clock))
+            }
+            Err(err) => {
+                error!("Error updating event clock :\n[error] : {}", err);
+                HttpResponse::BadRequest().body(format!("Error updating event clock : \n{}", err))
+            }
+        }
+    } else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to update clock")
+    }
+}
+#[post("/event/{event_id}/score/revert")]
+pub async fn revert_score(
+    session: Session,
+    event_id: web::Path<Uuid>,
+    revert_req: web::Json<ScoreRevert>,
+    app_state: web::Data<AppState>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let events = app_state.events.lock().await;
+    if let Some(event) = events.get(&event_id) {
+        let rr = revert_req.into_inner();
+        let log = ScoreLog {
+            admin: session.get::<String>("username").ok().flatten(),
+            reason: rr.reason,
+        };
+        let revert_res = event.revert_score(&rr.target, &log, &db_pool).await;
+        match revert_res {
+            Ok(reverted) => {
+                info!("Score updates reverted successfully : {:?}", reverted);
+                vb_srv.do_send(VboardGet(event_id, TransferType::Broadcast));
+                HttpResponse::Ok().json(reverted)
+            }
+            Err(err) => {
+                error!("Error reverting score :\n[error] : {}", err);
+                HttpResponse::BadRequest().body(format!("Error reverting Score : \n{}", err))
+            }
+        }
+    } else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to revert score")
+    }
+}
+#[post("/event/{event_id}/score/reset")]
+pub async fn reset_score(
+    event_id: web::Path<Uuid>,
+    app_state: web::Data<AppState>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let events = app_state.events.lock().await;
+    if let Some(event) = events.get(&event_id) {
+        let res = event.reset_score(&db_pool).await;
         match res {
             Ok(_) => {
                 info!("Score Reset successful");
+                vb_srv.do_send(VboardGet(event_id, TransferType::Broadcast));
                 HttpResponse::Ok().body("Score reset successful")
             }
             Err(err) => {
@@ -228,22 +703,24 @@ pub async fn reset_score(
                 HttpResponse::BadRequest().body(format!("Error resetting Score : \n{}", err))
             }
         }
+    } else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to reset score")
     }
 }
-#[post("/event/team/add")]
+#[post("/event/{event_id}/team/add")]
 pub async fn add_team(
+    event_id: web::Path<Uuid>,
     c_info: web::Json<ContestantInfo<'_>>,
     app_state: web::Data<AppState>,
     db_pool: web::Data<SqlitePool>,
 ) -> impl Responder {
-    let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        error!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to start event")
-    } else {
+    let event_id = event_id.into_inner();
+    let events = app_state.events.lock().await;
+    if let Some(event) = events.get(&event_id) {
         let team = Into::<Team>::into(c_info.into_inner());
         let team_id = team.id;
-        let res = event_state.as_ref().unwrap().add_team(team, &db_pool).await;
+        let res = event.add_team(team, &db_pool).await;
         match res {
             Ok(_) => {
                 info!("Team  added successfully : {}", team_id);
@@ -257,29 +734,27 @@ pub async fn add_team(
                 HttpResponse::BadRequest().body(err.to_string())
             }
         }
+    } else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to add team")
     }
 }
 
-#[post("/event/team/add/with_members")]
+#[post("/event/{event_id}/team/add/with_members")]
 pub async fn add_team_with_members(
+    event_id: web::Path<Uuid>,
     tm_info: web::Json<TeamWithMembers<'_>>,
     app_state: web::Data<AppState>,
     db_pool: web::Data<SqlitePool>,
 ) -> impl Responder {
-    let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        error!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to start event")
-    } else {
+    let event_id = event_id.into_inner();
+    let events = app_state.events.lock().await;
+    if let Some(event) = events.get(&event_id) {
         let tm = tm_info.into_inner();
         let team = Into::<Team>::into(tm.team_info);
         let members: Vec<User> = tm.members.into_iter().map(Into::<User>::into).collect();
         let team_id = team.id;
-        let res = event_state
-            .as_ref()
-            .unwrap()
-            .add_team_with_members(&team, &members, &db_pool)
-            .await;
+        let res = event.add_team_with_members(&team, &members, &db_pool).await;
         match res {
             Ok(_) => {
                 info!("Team  added successfully : {}", team_id);
@@ -293,27 +768,25 @@ pub async fn add_team_with_members(
                 HttpResponse::BadRequest().body(err.to_string())
             }
         }
+    } else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to add team")
     }
 }
 
-#[post("/event/user/add")]
+#[post("/event/{event_id}/user/add")]
 pub async fn add_user(
+    event_id: web::Path<Uuid>,
     c_info: web::Json<ContestantInfo<'_>>,
     app_state: web::Data<AppState>,
     db_pool: web::Data<SqlitePool>,
 ) -> impl Responder {
-    let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        error!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to start event")
-    } else {
+    let event_id = event_id.into_inner();
+    let events = app_state.events.lock().await;
+    if let Some(event) = events.get(&event_id) {
         let user = Into::<User>::into(c_info.into_inner());
         let user_id = user.id;
-        let res = event_state
-            .as_ref()
-            .unwrap()
-            .add_user(&user, &db_pool)
-            .await;
+        let res = event.add_user(&user, &db_pool).await;
         match res {
             Ok(_) => {
                 info!("User  added successfully : {}", user_id);
@@ -327,26 +800,53 @@ pub async fn add_user(
                 HttpResponse::BadRequest().body(err.to_string())
             }
         }
+    } else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to add user")
     }
 }
 
-#[post("/event/team/add/members")]
+#[post("/event/{event_id}/participant/add")]
+pub async fn add_participant(
+    event_id: web::Path<Uuid>,
+    id_info: web::Json<IdQuery>,
+    app_state: web::Data<AppState>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let p_id = id_info.into_inner().id;
+    let events = app_state.events.lock().await;
+    if let Some(event) = events.get(&event_id) {
+        match event.add_participant_by_id(p_id, &db_pool).await {
+            Ok(_) => {
+                info!("Participant added successfully : {}", p_id);
+                HttpResponse::Ok().json(web::Json(CommandResponse::new(
+                    "Participant added successfully",
+                    p_id,
+                )))
+            }
+            Err(err) => {
+                error!("Error adding Participant :\n[error] : {}", err);
+                HttpResponse::BadRequest().body(err.to_string())
+            }
+        }
+    } else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to add participant")
+    }
+}
+#[post("/event/{event_id}/team/add/members")]
 pub async fn add_team_members(
+    event_id: web::Path<Uuid>,
     m_info: web::Json<MemberInfo>,
     app_state: web::Data<AppState>,
     db_pool: web::Data<SqlitePool>,
 ) -> impl Responder {
-    let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        error!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to start event")
-    } else {
+    let event_id = event_id.into_inner();
+    let events = app_state.events.lock().await;
+    if let Some(event) = events.get(&event_id) {
         let mi = m_info.into_inner();
-        let res = event_state
-            .as_ref()
-            .unwrap()
-            .add_team_members(&mi, &db_pool)
-            .await;
+        let res = event.add_team_members(&mi, &db_pool).await;
         match res {
             Ok(_) => {
                 info!("Team Members added successfully : {:?}", mi.members);
@@ -357,6 +857,10 @@ pub async fn add_team_members(
                 HttpResponse::BadRequest().body(format!("Error adding Team Members : {}", err))
             }
         }
+    } else {
+        error!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest()
+            .body("No event added with given id.Add event to add team members")
     }
 }
 
@@ -367,17 +871,19 @@ pub async fn delete_event(
     id_info: web::Json<IdQuery>,
 ) -> impl Responder {
     let id = id_info.into_inner().id;
-    let event_state = app_state.current_event.lock().await;
-    if let Some(e) = event_state.as_ref() {
-        let event_id = e.get_id();
-        if event_id.eq(&id) {
-            return HttpResponse::BadRequest().body(
-                VaderError::EventActive(
-                    "Unable to remove Event i.e currently Added/Active.Stop the event to remove",
-                )
-                .to_string(),
-            );
-        }
+    if app_state
+        .events
+        .lock()
+        .await
+        .get(&id)
+        .is_some_and(|event| !event.is_ended())
+    {
+        return HttpResponse::BadRequest().body(
+            VaderError::EventActive(
+                "Unable to remove Event i.e currently Added/Active.Stop the event to remove",
+            )
+            .to_string(),
+        );
     }
     let info_res: Result<EventInfo, VaderError> = EventInfo::get_event_info(&id, &db_pool).await;
     let res = match info_res {
@@ -395,6 +901,7 @@ pub async fn delete_event(
     };
     match res {
         Ok(_) => {
+            app_state.events.lock().await.remove(&id);
             info!("Successfully deleted event : {}", id);
             HttpResponse::Ok().json(web::Json(CommandResponse::new(
                 "Successfully deleted event",
@@ -402,16 +909,92 @@ pub async fn delete_event(
             )))
         }
         Err(e) => {
-            let err = format!("Error Deleting event : {}.\n{}", id, e.to_string());
+            let err = format!("Error Deleting event : {}.\n{}", id, e);
             error!("{}", err);
             HttpResponse::BadRequest().body(err)
         }
     }
 }
 
+#[post("/season/add")]
+pub async fn add_season<'a>(
+    season_req: web::Json<SeasonReq<'a>>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder
+where
+    'a: 'static,
+{
+    match Season::from_req(season_req.into_inner()) {
+        Ok(season) => match season.add_season(&db_pool).await {
+            Ok(_) => {
+                info!("Successfully added Season [id : {}]", season.id);
+                HttpResponse::Ok().json(web::Json(CommandResponse::new(
+                    "Successfully added Season",
+                    season.id,
+                )))
+            }
+            Err(err) => {
+                error!("Error adding Season : {}", err);
+                HttpResponse::InternalServerError().body(err.to_string())
+            }
+        },
+        Err(err) => {
+            error!("Error adding Season : {}", err);
+            HttpResponse::BadRequest().body(err.to_string())
+        }
+    }
+}
+#[post("/season/{season_id}/event/add")]
+pub async fn add_season_event(
+    season_id: web::Path<Uuid>,
+    event_req: web::Json<SeasonEventReq>,
+    sn_srv: web::Data<Addr<SeasonSrv>>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let season_id = season_id.into_inner();
+    match Season::add_event(season_id, &event_req, &db_pool).await {
+        Ok(_) => {
+            info!(
+                "Event [id : {}] added to Season [id : {}]",
+                event_req.event_id, season_id
+            );
+            sn_srv.do_send(SeasonGet(season_id, TransferType::Broadcast));
+            HttpResponse::Ok().body("Event added to Season")
+        }
+        Err(err) => {
+            error!("Error adding event to season :\n[error] : {}", err);
+            HttpResponse::BadRequest().body(format!("Error adding Event to Season : \n{}", err))
+        }
+    }
+}
+#[post("/season/{season_id}/event/{event_id}/remove")]
+pub async fn remove_season_event(
+    path: web::Path<(Uuid, Uuid)>,
+    sn_srv: web::Data<Addr<SeasonSrv>>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let (season_id, event_id) = path.into_inner();
+    match Season::remove_event(season_id, event_id, &db_pool).await {
+        Ok(_) => {
+            info!(
+                "Event [id : {}] removed from Season [id : {}]",
+                event_id, season_id
+            );
+            sn_srv.do_send(SeasonGet(season_id, TransferType::Broadcast));
+            HttpResponse::Ok().body("Event removed from Season")
+        }
+        Err(err) => {
+            error!("Error removing event from season :\n[error] : {}", err);
+            HttpResponse::BadRequest().body(format!("Error removing Event from Season : \n{}", err))
+        }
+    }
+}
+
 #[post("/team/delete")]
 pub async fn delete_team(
     db_pool: web::Data<SqlitePool>,
+    app_state: web::Data<AppState>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
     id_info: web::Json<IdQuery>,
 ) -> impl Responder {
     let id = id_info.into_inner().id;
@@ -419,6 +1002,7 @@ pub async fn delete_team(
     match res {
         Ok(_) => {
             info!("Successfully deleted team : {}", id);
+            refresh_vboards(&app_state, &vb_srv).await;
             HttpResponse::Ok().json(web::Json(CommandResponse::new(
                 "Successfully deleted team ",
                 id,
@@ -426,7 +1010,7 @@ pub async fn delete_team(
         }
 
         Err(e) => {
-            let err = format!("Error Deleting team : {}.\n{}", id, e.to_string());
+            let err = format!("Error Deleting team : {}.\n{}", id, e);
             error!("{}", err);
             HttpResponse::BadRequest().body(err)
         }
@@ -436,6 +1020,8 @@ pub async fn delete_team(
 #[post("/user/delete")]
 pub async fn delete_user(
     db_pool: web::Data<SqlitePool>,
+    app_state: web::Data<AppState>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
     id_info: web::Json<IdQuery>,
 ) -> impl Responder {
     let id = id_info.into_inner().id;
@@ -443,19 +1029,89 @@ pub async fn delete_user(
     match res {
         Ok(_) => {
             info!("Successfully deleted user : {}", id);
+            refresh_vboards(&app_state, &vb_srv).await;
             HttpResponse::Ok().json(web::Json(CommandResponse::new(
                 "Successfully deleted user",
                 id,
             )))
         }
         Err(e) => {
-            let err = format!("Error Deleting user : {}.\n{}", id, e.to_string());
+            let err = format!("Error Deleting user : {}.\n{}", id, e);
             error!("{}", err);
             HttpResponse::BadRequest().body(err)
         }
     }
 }
 
+#[post("/event/{event_id}/announcement/add")]
+pub async fn add_announcement(
+    event_id: web::Path<Uuid>,
+    announcement_req: web::Json<AnnouncementReq>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let announcement = match announcement_req.into_inner().announcement(event_id) {
+        Ok(announcement) => announcement,
+        Err(err) => {
+            error!("Error adding Announcement :\n[error] : {}", err);
+            return HttpResponse::BadRequest().body(err.to_string());
+        }
+    };
+    match announcement.add(&db_pool).await {
+        Ok(()) => {
+            let id = announcement.id;
+            vb_srv.do_send(VbAnnounce(announcement));
+            info!("Announcement added successfully : {}", id);
+            HttpResponse::Ok().json(web::Json(CommandResponse::new(
+                "Announcement added successfully",
+                id,
+            )))
+        }
+        Err(err) => {
+            error!("Error adding Announcement :\n[error] : {}", err);
+            HttpResponse::BadRequest().body(err.to_string())
+        }
+    }
+}
+
+#[post("/event/{event_id}/announcement/{announcement_id}/remove")]
+pub async fn remove_announcement(
+    path: web::Path<(Uuid, Uuid)>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let (event_id, id) = path.into_inner();
+    match Announcement::remove(event_id, id, &db_pool).await {
+        Ok(true) => {
+            vb_srv.do_send(VbRetract(event_id, id));
+            info!("Announcement removed successfully : {}", id);
+            HttpResponse::Ok().json(web::Json(CommandResponse::new(
+                "Announcement removed successfully",
+                id,
+            )))
+        }
+        Ok(false) => {
+            error!("Request delined.No announcement found [id : {}]", id);
+            HttpResponse::BadRequest().body(
+                VaderError::AnnouncementError("No active announcement with given id").to_string(),
+            )
+        }
+        Err(err) => {
+            error!("Error removing Announcement :\n[error] : {}", err);
+            HttpResponse::InternalServerError().body(err.to_string())
+        }
+    }
+}
+
+// Participants are not tied to one event, so every current vaderboard is refreshed
+async fn refresh_vboards(app_state: &AppState, vb_srv: &Addr<VboardSrv>) {
+    let events = app_state.events.lock().await;
+    events
+        .keys()
+        .for_each(|event_id| vb_srv.do_send(VboardGet(*event_id, TransferType::Broadcast)));
+}
+
 #[post("/login")]
 pub async fn login(
     session: Session,
@@ -463,9 +1119,11 @@ pub async fn login(
     db_pool: web::Data<SqlitePool>,
 ) -> impl Responder {
     let login = login_info.into_inner();
+    let username = login.username.clone();
     match login.verify_passwd(&db_pool).await {
         Ok(true) => {
-            if session.insert("admin", true).is_ok() {
+            if session.insert("admin", true).is_ok() && session.insert("username", username).is_ok()
+            {
                 log::debug!("Login Successful : )");
                 HttpResponse::Ok().body("Login Successful")
             } else {
@@ -478,7 +1136,7 @@ pub async fn login(
             HttpResponse::Unauthorized().body("Invalid UserName/Password")
         }
         Err(e) => {
-            log::debug!("Admin Auth error : {}", e.to_string());
+            log::debug!("Admin Auth error : {}", e);
             HttpResponse::InternalServerError().finish()
         }
     }
@@ -1,138 +1,259 @@
-
-
-use actix::Addr;
+use actix::{Actor, Addr};
+use actix_web::http::header::{CacheControl, CacheDirective, ETag, EntityTag, Header, IfNoneMatch};
 use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
 use actix_web_actors::ws;
+use futures::channel::mpsc;
+use futures::StreamExt;
 use log::debug;
 use sqlx::SqlitePool;
+use uuid::Uuid;
 
+use crate::models::bracket_models::{BracketClient, BracketSrv, BracketState};
 use crate::models::error_models::VaderError;
 use crate::models::query_models::{
-    CurFtsBuilder, CurFtsServer, EventInfo, EventQuery, FtsQuery, IdQuery, TeamInfo, VboardClient,
-    VboardSrv,
+    ChallengeInfo, CurFtsBuilder, CurFtsServer, EventInfo, EventQuery, FtsQuery, IdQuery,
+    JudgeMarks, LedgerEntry, TeamInfo, VbAnnouncements, VbPoll, VboardClient, VboardQuery,
+    VboardSrv, VboardSse, VboardWindow,
 };
+use crate::models::replay_models::{ReplayAt, ReplayOpts, Timeline, VboardReplay};
+use crate::models::season_models::{Season, SeasonClient, SeasonEntry, SeasonSrv};
 use crate::models::v_models::{AppState, Team, User};
+use crate::models::wrapper_models::EventWrapper;
+use crate::models::ws_models::{WsConn, WsLimits};
 
 #[get("/event/info")]
-pub async fn get_current_event(app_state: web::Data<AppState>) -> impl Responder {
-    let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        debug!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to Fetch details")
-    } else {
-        let res: EventQuery = event_state.as_ref().unwrap().get_event();
+pub async fn get_current_events(app_state: web::Data<AppState>) -> impl Responder {
+    let events = app_state.events.lock().await;
+    let res: Vec<EventQuery> = events
+        .values()
+        .filter(|e| !e.is_ended())
+        .map(|e| e.get_event())
+        .collect();
+    HttpResponse::Ok().json(web::Json(res))
+}
+#[get("/event/{event_id}/info")]
+pub async fn get_current_event(
+    event_id: web::Path<Uuid>,
+    app_state: web::Data<AppState>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let events = app_state.events.lock().await;
+    if let Some(event) = events.get(&event_id) {
+        let res: EventQuery = event.get_event();
         HttpResponse::Ok().json(web::Json(res))
+    } else {
+        debug!("Request delined.No event found [id : {}]", event_id);
+        HttpResponse::BadRequest().body("No event added with given id.Add event to Fetch details")
     }
 }
-#[get("event/info/team/{count}")]
+#[get("event/{event_id}/info/team/{count}")]
 pub async fn get_event_teams(
     app_state: web::Data<AppState>,
     srv_addr: web::Data<Addr<CurFtsServer<'static>>>,
     db_pool: web::Data<SqlitePool>,
-    count: web::Path<u32>,
+    path: web::Path<(Uuid, u32)>,
     req: HttpRequest,
     stream: web::Payload,
+    ws_limits: web::Data<WsLimits>,
 ) -> impl Responder {
-    let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        debug!("Request delined.No event added");
-        Ok(HttpResponse::BadRequest().body("No event added.Add event to Fetch details"))
-    } else {
-        let event = event_state.as_ref().unwrap();
-        match event {
-            crate::models::wrapper_models::EventWrapper::TeamEvent(_) => {
-                let event_id = event.get_id();
-                let cur_fts = CurFtsBuilder::<Team>::new(
-                    event_id,
-                    srv_addr.into_inner(),
-                    count.into_inner(),
-                    db_pool.into_inner(),
-                )
-                .team_fts()
-                .build();
-                ws::start(cur_fts, &req, stream)
-            }
-            crate::models::wrapper_models::EventWrapper::UserEvent(_) => {
-                Ok(HttpResponse::BadRequest().body(
-                    VaderError::EventTypeMismatch("Cannot get Team Info in user event").to_string(),
-                ))
-            }
-        }
-    }
-}
-#[get("event/info/team/rem_members/{count}")]
+    let conn = match WsConn::acquire(&ws_limits, &req) {
+        Ok(conn) => conn,
+        Err(e) => {
+            debug!("Request delined.{}", e);
+            return Ok(HttpResponse::TooManyRequests().body(e.to_string()));
+        }
+    };
+    let (event_id, count) = path.into_inner();
+    let events = app_state.events.lock().await;
+    match events.get(&event_id) {
+        Some(EventWrapper::TeamEvent(_)) => {
+            let cur_fts = CurFtsBuilder::<Team>::new(
+                event_id,
+                srv_addr.into_inner(),
+                count,
+                db_pool.into_inner(),
+                conn,
+            )
+            .team_fts()
+            .build();
+            ws::start(cur_fts, &req, stream)
+        }
+        Some(EventWrapper::UserEvent(_)) => Ok(HttpResponse::BadRequest()
+            .body(VaderError::EventTypeMismatch("Cannot get Team Info in user event").to_string())),
+        None => {
+            debug!("Request delined.No event found [id : {}]", event_id);
+            Ok(HttpResponse::BadRequest()
+                .body("No event added with given id.Add event to Fetch details"))
+        }
+    }
+}
+#[get("event/{event_id}/info/team/rem_members/{count}")]
 pub async fn get_event_rem_members(
     app_state: web::Data<AppState>,
     srv_addr: web::Data<Addr<CurFtsServer<'static>>>,
     db_pool: web::Data<SqlitePool>,
-    count: web::Path<u32>,
+    path: web::Path<(Uuid, u32)>,
     req: HttpRequest,
     stream: web::Payload,
+    ws_limits: web::Data<WsLimits>,
 ) -> impl Responder {
-    let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        debug!("Request delined.No event added");
-        Ok(HttpResponse::BadRequest().body("No event added.Add event to Fetch details"))
-    } else {
-        let event = event_state.as_ref().unwrap();
-        match event {
-            crate::models::wrapper_models::EventWrapper::TeamEvent(_) => {
-                let event_id = event.get_id();
-                let cur_fts = CurFtsBuilder::<Team>::new(
-                    event_id,
-                    srv_addr.into_inner(),
-                    count.into_inner(),
-                    db_pool.into_inner(),
-                )
-                .rem_user_fts()
-                .build();
-                ws::start(cur_fts, &req, stream)
-            }
-            crate::models::wrapper_models::EventWrapper::UserEvent(_) => {
-                Ok(HttpResponse::BadRequest().body(
-                    VaderError::EventTypeMismatch("Cannot get Team Info in user event").to_string(),
-                ))
-            }
-        }
-    }
-}
-
-#[get("event/info/user/{count}")]
+    let conn = match WsConn::acquire(&ws_limits, &req) {
+        Ok(conn) => conn,
+        Err(e) => {
+            debug!("Request delined.{}", e);
+            return Ok(HttpResponse::TooManyRequests().body(e.to_string()));
+        }
+    };
+    let (event_id, count) = path.into_inner();
+    let events = app_state.events.lock().await;
+    match events.get(&event_id) {
+        Some(EventWrapper::TeamEvent(_)) => {
+            let cur_fts = CurFtsBuilder::<Team>::new(
+                event_id,
+                srv_addr.into_inner(),
+                count,
+                db_pool.into_inner(),
+                conn,
+            )
+            .rem_user_fts()
+            .build();
+            ws::start(cur_fts, &req, stream)
+        }
+        Some(EventWrapper::UserEvent(_)) => Ok(HttpResponse::BadRequest()
+            .body(VaderError::EventTypeMismatch("Cannot get Team Info in user event").to_string())),
+        None => {
+            debug!("Request delined.No event found [id : {}]", event_id);
+            Ok(HttpResponse::BadRequest()
+                .body("No event added with given id.Add event to Fetch details"))
+        }
+    }
+}
+
+#[get("event/{event_id}/info/user/{count}")]
 pub async fn get_event_users(
     app_state: web::Data<AppState>,
     srv_addr: web::Data<Addr<CurFtsServer<'static>>>,
     db_pool: web::Data<SqlitePool>,
-    count: web::Path<u32>,
+    path: web::Path<(Uuid, u32)>,
     req: HttpRequest,
     stream: web::Payload,
+    ws_limits: web::Data<WsLimits>,
 ) -> impl Responder {
-    let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        debug!("Request delined.No event added");
-        Ok(HttpResponse::BadRequest().body("No event added.Add event to Fetch details"))
-    } else {
-        let event = event_state.as_ref().unwrap();
-        match event {
-            crate::models::wrapper_models::EventWrapper::TeamEvent(_) => {
-                Ok(HttpResponse::BadRequest().body(
-                    VaderError::EventTypeMismatch("Cannot get User Info in team event").to_string(),
-                ))
-            }
-            crate::models::wrapper_models::EventWrapper::UserEvent(_) => {
-                let event_id = event.get_id();
-                let cur_fts = CurFtsBuilder::<User>::new(
-                    event_id,
-                    srv_addr.into_inner(),
-                    count.into_inner(),
-                    db_pool.into_inner(),
-                )
-                .build();
-                ws::start(cur_fts, &req, stream)
-            }
+    let conn = match WsConn::acquire(&ws_limits, &req) {
+        Ok(conn) => conn,
+        Err(e) => {
+            debug!("Request delined.{}", e);
+            return Ok(HttpResponse::TooManyRequests().body(e.to_string()));
+        }
+    };
+    let (event_id, count) = path.into_inner();
+    let events = app_state.events.lock().await;
+    match events.get(&event_id) {
+        Some(EventWrapper::TeamEvent(_)) => Ok(HttpResponse::BadRequest()
+            .body(VaderError::EventTypeMismatch("Cannot get User Info in team event").to_string())),
+        Some(EventWrapper::UserEvent(_)) => {
+            let cur_fts = CurFtsBuilder::<User>::new(
+                event_id,
+                srv_addr.into_inner(),
+                count,
+                db_pool.into_inner(),
+                conn,
+            )
+            .build();
+            ws::start(cur_fts, &req, stream)
+        }
+        None => {
+            debug!("Request delined.No event found [id : {}]", event_id);
+            Ok(HttpResponse::BadRequest()
+                .body("No event added with given id.Add event to Fetch details"))
         }
     }
 }
 
+#[get("/event/{event_id}/ledger")]
+pub async fn get_event_ledger(
+    event_id: web::Path<Uuid>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let res: Result<Vec<LedgerEntry>, VaderError> =
+        LedgerEntry::get_event_ledger(&event_id, &db_pool).await;
+    match res {
+        Ok(entries) => HttpResponse::Ok().json(web::Json(entries)),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+#[get("/event/{event_id}/ledger/{participant_id}")]
+pub async fn get_participant_ledger(
+    path: web::Path<(Uuid, Uuid)>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let (event_id, participant_id) = path.into_inner();
+    let res: Result<Vec<LedgerEntry>, VaderError> =
+        LedgerEntry::get_participant_ledger(&event_id, &participant_id, &db_pool).await;
+    match res {
+        Ok(entries) => HttpResponse::Ok().json(web::Json(entries)),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+#[get("/event/{event_id}/challenges")]
+pub async fn get_event_challenges(
+    event_id: web::Path<Uuid>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let res: Result<Vec<ChallengeInfo>, VaderError> =
+        ChallengeInfo::get_event_challenges(&event_id, &db_pool).await;
+    match res {
+        Ok(challenges) => HttpResponse::Ok().json(web::Json(challenges)),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+#[get("/event/{event_id}/bracket")]
+pub async fn get_event_bracket(
+    event_id: web::Path<Uuid>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let res: Result<BracketState, VaderError> =
+        BracketState::get_bracket_state(event_id, &db_pool).await;
+    match res {
+        Ok(state) => HttpResponse::Ok().json(web::Json(state)),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+#[get("/event/{event_id}/marks")]
+pub async fn get_event_marks(
+    event_id: web::Path<Uuid>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let res: Result<Vec<JudgeMarks>, VaderError> =
+        JudgeMarks::get_event_marks(&event_id, &db_pool).await;
+    match res {
+        Ok(marks) => HttpResponse::Ok().json(web::Json(marks)),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+#[get("/event/{event_id}/marks/{participant_id}")]
+pub async fn get_participant_marks(
+    path: web::Path<(Uuid, Uuid)>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let (event_id, participant_id) = path.into_inner();
+    let res: Result<Vec<JudgeMarks>, VaderError> =
+        JudgeMarks::get_participant_marks(&event_id, &participant_id, &db_pool).await;
+    match res {
+        Ok(marks) => HttpResponse::Ok().json(web::Json(marks)),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
 #[get("/event/info/id")]
 pub async fn get_event_info(
     id_info: web::Json<IdQuery>,
@@ -155,6 +276,41 @@ pub async fn get_all_event(db_pool: web::Data<SqlitePool>) -> impl Responder {
     }
 }
 
+#[get("/season/info/all")]
+pub async fn get_all_seasons(db_pool: web::Data<SqlitePool>) -> impl Responder {
+    let res: Result<Vec<Season>, VaderError> = Season::get_all_seasons(&db_pool).await;
+    match res {
+        Ok(seasons) => HttpResponse::Ok().json(web::Json(seasons)),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+#[get("/season/{season_id}/info")]
+pub async fn get_season(
+    season_id: web::Path<Uuid>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let res: Result<Season, VaderError> =
+        Season::get_season(season_id.into_inner(), &db_pool).await;
+    match res {
+        Ok(season) => HttpResponse::Ok().json(web::Json(season)),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+#[get("/season/{season_id}/board")]
+pub async fn get_season_board(
+    season_id: web::Path<Uuid>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let res: Result<Vec<SeasonEntry>, VaderError> =
+        SeasonEntry::get_season_board(season_id.into_inner(), &db_pool).await;
+    match res {
+        Ok(board) => HttpResponse::Ok().json(web::Json(board)),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
 #[get("/team/info")]
 pub async fn get_team_info(
     id_info: web::Json<IdQuery>,
@@ -203,23 +359,39 @@ pub async fn event_fts(
     db_pool: web::Data<SqlitePool>,
     count: web::Path<u32>,
     stream: web::Payload,
+    ws_limits: web::Data<WsLimits>,
 ) -> impl Responder {
+    let conn = match WsConn::acquire(&ws_limits, &req) {
+        Ok(conn) => conn,
+        Err(e) => {
+            debug!("Request delined.{}", e);
+            return Ok(HttpResponse::TooManyRequests().body(e.to_string()));
+        }
+    };
     ws::start(
-        FtsQuery::<EventInfo>::new(count.into_inner(), db_pool.into_inner()),
+        FtsQuery::<EventInfo>::new(count.into_inner(), db_pool.into_inner(), conn),
         &req,
         stream,
     )
 }
 
 #[get("/team/fts/{count}")]
-pub async fn team_fts<'a>(
+pub async fn team_fts(
     req: HttpRequest,
     db_pool: web::Data<SqlitePool>,
     count: web::Path<u32>,
     stream: web::Payload,
+    ws_limits: web::Data<WsLimits>,
 ) -> impl Responder {
+    let conn = match WsConn::acquire(&ws_limits, &req) {
+        Ok(conn) => conn,
+        Err(e) => {
+            debug!("Request delined.{}", e);
+            return Ok(HttpResponse::TooManyRequests().body(e.to_string()));
+        }
+    };
     ws::start(
-        FtsQuery::<TeamInfo>::new(count.into_inner(), db_pool.into_inner()),
+        FtsQuery::<TeamInfo>::new(count.into_inner(), db_pool.into_inner(), conn),
         &req,
         stream,
     )
@@ -230,26 +402,287 @@ pub async fn user_fts(
     db_pool: web::Data<SqlitePool>,
     count: web::Path<u32>,
     stream: web::Payload,
+    ws_limits: web::Data<WsLimits>,
 ) -> impl Responder {
+    let conn = match WsConn::acquire(&ws_limits, &req) {
+        Ok(conn) => conn,
+        Err(e) => {
+            debug!("Request delined.{}", e);
+            return Ok(HttpResponse::TooManyRequests().body(e.to_string()));
+        }
+    };
     ws::start(
-        FtsQuery::<User>::new(count.into_inner(), db_pool.into_inner()),
+        FtsQuery::<User>::new(count.into_inner(), db_pool.into_inner(), conn),
         &req,
         stream,
     )
 }
 
-#[get("/vaderboard")]
+#[get("/vaderboard/{event_id}")]
 pub async fn vaderboard(
+    event_id: web::Path<Uuid>,
     req: HttpRequest,
     app_state: web::Data<AppState>,
     srv_addr: web::Data<Addr<VboardSrv>>,
+    db_pool: web::Data<SqlitePool>,
     stream: web::Payload,
+    ws_limits: web::Data<WsLimits>,
 ) -> impl Responder {
-    let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        debug!("Request delined.No event added");
-        Ok(HttpResponse::BadRequest().body("No event added.Add event to Fetch details"))
+    let conn = match WsConn::acquire(&ws_limits, &req) {
+        Ok(conn) => conn,
+        Err(e) => {
+            debug!("Request delined.{}", e);
+            return Ok(HttpResponse::TooManyRequests().body(e.to_string()));
+        }
+    };
+    let event_id = event_id.into_inner();
+    match app_state.load_event(event_id, &db_pool).await {
+        Ok(true) => ws::start(VboardClient::new(event_id, srv_addr, conn), &req, stream),
+        Ok(false) => {
+            debug!("Request delined.No event found [id : {}]", event_id);
+            Ok(HttpResponse::BadRequest()
+                .body("No event added with given id.Add event to Fetch details"))
+        }
+        Err(e) => Ok(HttpResponse::InternalServerError().body(e.to_string())),
+    }
+}
+
+#[get("/event/{event_id}/announcement/all")]
+pub async fn get_announcements(
+    event_id: web::Path<Uuid>,
+    srv_addr: web::Data<Addr<VboardSrv>>,
+) -> impl Responder {
+    match srv_addr.send(VbAnnouncements(event_id.into_inner())).await {
+        Ok(announcements) => HttpResponse::Ok().json(web::Json(announcements)),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+#[get("/vaderboard/{event_id}/sse")]
+pub async fn vaderboard_sse(
+    event_id: web::Path<Uuid>,
+    window: web::Query<VboardQuery>,
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    srv_addr: web::Data<Addr<VboardSrv>>,
+    db_pool: web::Data<SqlitePool>,
+    ws_limits: web::Data<WsLimits>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let window = VboardWindow::from(window.into_inner());
+    if !window.is_valid() {
+        debug!("Request delined.Invalid rank window [id : {}]", event_id);
+        return HttpResponse::BadRequest().body("Invalid rank window");
+    }
+    let conn = match WsConn::acquire(&ws_limits, &req) {
+        Ok(conn) => conn,
+        Err(e) => {
+            debug!("Request delined.{}", e);
+            return HttpResponse::TooManyRequests().body(e.to_string());
+        }
+    };
+    match app_state.load_event(event_id, &db_pool).await {
+        Ok(true) => {
+            let (tx, rx) = mpsc::unbounded();
+            VboardSse::new(event_id, window, srv_addr, tx, conn).start();
+            HttpResponse::Ok()
+                .content_type("text/event-stream")
+                .insert_header(CacheControl(vec![CacheDirective::NoCache]))
+                .streaming(rx.map(Ok::<_, actix_web::Error>))
+        }
+        Ok(false) => {
+            debug!("Request delined.No event found [id : {}]", event_id);
+            HttpResponse::BadRequest()
+                .body("No event added with given id.Add event to Fetch details")
+        }
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+// Current vaderboard for pollers, 304 when the client's ETag still matches
+// rows and ETag come from the vaderboard server cache, the board is only fetched after a change
+#[get("/event/{event_id}/vaderboard")]
+pub async fn get_event_vboard(
+    event_id: web::Path<Uuid>,
+    window: web::Query<VboardQuery>,
+    req: HttpRequest,
+    srv_addr: web::Data<Addr<VboardSrv>>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let window = VboardWindow::from(window.into_inner());
+    if !window.is_valid() {
+        debug!("Request delined.Invalid rank window [id : {}]", event_id);
+        return HttpResponse::BadRequest().body("Invalid rank window");
+    }
+    let poll = match srv_addr.send(VbPoll(event_id, window)).await {
+        Ok(Ok(Some(poll))) => poll,
+        Ok(Ok(None)) => {
+            debug!("Request delined.No event found [id : {}]", event_id);
+            return HttpResponse::BadRequest()
+                .body("No event added with given id.Add event to Fetch details");
+        }
+        Ok(Err(e)) => return HttpResponse::BadRequest().body(e),
+        Err(e) => return HttpResponse::InternalServerError().body(e.to_string()),
+    };
+    let etag = EntityTag::new_strong(poll.etag);
+    let fresh = etag_matches(&req, &etag);
+    let mut res = if fresh {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    res.insert_header(ETag(etag))
+        .insert_header(CacheControl(vec![CacheDirective::NoCache]));
+    if fresh {
+        res.finish()
     } else {
-        ws::start(VboardClient::new(srv_addr), &req, stream)
+        res.json(poll.entries)
+    }
+}
+
+fn etag_matches(req: &HttpRequest, etag: &EntityTag) -> bool {
+    match IfNoneMatch::parse(req) {
+        Ok(IfNoneMatch::Any) => true,
+        Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(etag)),
+        Err(_) => false,
+    }
+}
+
+// Vaderboard as it was at the given time, rebuilt from the score ledger
+#[get("/event/{event_id}/replay")]
+pub async fn get_event_replay(
+    event_id: web::Path<Uuid>,
+    at: web::Query<ReplayAt>,
+    window: web::Query<VboardQuery>,
+    app_state: web::Data<AppState>,
+    db_pool: web::Data<SqlitePool>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let window = VboardWindow::from(window.into_inner());
+    if !window.is_valid() {
+        debug!("Request delined.Invalid rank window [id : {}]", event_id);
+        return HttpResponse::BadRequest().body("Invalid rank window");
+    }
+    match Timeline::load(event_id, &db_pool).await {
+        Ok(timeline) => {
+            let board = timeline.board_at(Some(at.into_inner().at));
+            HttpResponse::Ok().json(window.rows(&board, app_state.vb_count))
+        }
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+#[get("/replay/{event_id}")]
+pub async fn replay(
+    event_id: web::Path<Uuid>,
+    (opts, window): (web::Query<ReplayOpts>, web::Query<VboardQuery>),
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    db_pool: web::Data<SqlitePool>,
+    stream: web::Payload,
+    ws_limits: web::Data<WsLimits>,
+) -> impl Responder {
+    let event_id = event_id.into_inner();
+    let speed = opts.into_inner().speed;
+    let window = VboardWindow::from(window.into_inner());
+    if !window.is_valid() || !speed.is_finite() || speed <= 0.0 {
+        debug!("Request delined.Invalid replay options [id : {}]", event_id);
+        return Ok(HttpResponse::BadRequest().body("Invalid replay speed or rank window"));
+    }
+    let conn = match WsConn::acquire(&ws_limits, &req) {
+        Ok(conn) => conn,
+        Err(e) => {
+            debug!("Request delined.{}", e);
+            return Ok(HttpResponse::TooManyRequests().body(e.to_string()));
+        }
+    };
+    match Timeline::load(event_id, &db_pool).await {
+        Ok(timeline) => ws::start(
+            VboardReplay::new(timeline, window, speed, app_state.vb_count, conn),
+            &req,
+            stream,
+        ),
+        Err(e) => Ok(HttpResponse::BadRequest().body(e.to_string())),
+    }
+}
+
+#[get("/bracket/{event_id}")]
+pub async fn bracket(
+    event_id: web::Path<Uuid>,
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    srv_addr: web::Data<Addr<BracketSrv>>,
+    db_pool: web::Data<SqlitePool>,
+    stream: web::Payload,
+    ws_limits: web::Data<WsLimits>,
+) -> impl Responder {
+    let conn = match WsConn::acquire(&ws_limits, &req) {
+        Ok(conn) => conn,
+        Err(e) => {
+            debug!("Request delined.{}", e);
+            return Ok(HttpResponse::TooManyRequests().body(e.to_string()));
+        }
+    };
+    let event_id = event_id.into_inner();
+    match app_state.load_event(event_id, &db_pool).await {
+        Ok(true) => ws::start(BracketClient::new(event_id, srv_addr, conn), &req, stream),
+        Ok(false) => {
+            debug!("Request delined.No event found [id : {}]", event_id);
+            Ok(HttpResponse::BadRequest()
+                .body("No event added with given id.Add event to Fetch details"))
+        }
+        Err(e) => Ok(HttpResponse::InternalServerError().body(e.to_string())),
+    }
+}
+
+#[get("/seasonboard/{season_id}")]
+pub async fn seasonboard(
+    season_id: web::Path<Uuid>,
+    req: HttpRequest,
+    srv_addr: web::Data<Addr<SeasonSrv>>,
+    db_pool: web::Data<SqlitePool>,
+    stream: web::Payload,
+    ws_limits: web::Data<WsLimits>,
+) -> impl Responder {
+    let conn = match WsConn::acquire(&ws_limits, &req) {
+        Ok(conn) => conn,
+        Err(e) => {
+            debug!("Request delined.{}", e);
+            return Ok(HttpResponse::TooManyRequests().body(e.to_string()));
+        }
+    };
+    let season_id = season_id.into_inner();
+    match Season::get_season(season_id, &db_pool).await {
+        Ok(_) => ws::start(SeasonClient::new(season_id, srv_addr, conn), &req, stream),
+        Err(e) => {
+            debug!("Request delined.No season found [id : {}]", season_id);
+            Ok(HttpResponse::BadRequest().body(e.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    #[test]
+    fn etag_match() {
+        let etag = EntityTag::new_strong(String::from("18b-4"));
+        let req = |tag: &str| {
+            TestRequest::default()
+                .insert_header(("If-None-Match", tag))
+                .to_http_request()
+        };
+        assert!(etag_matches(&req("\"18b-4\""), &etag));
+        assert!(etag_matches(&req("W/\"18b-4\""), &etag));
+        assert!(etag_matches(&req("\"18b-3\", \"18b-4\""), &etag));
+        assert!(etag_matches(&req("*"), &etag));
+        assert!(!etag_matches(&req("\"18b-3\""), &etag));
+        assert!(!etag_matches(
+            &TestRequest::default().to_http_request(),
+            &etag
+        ));
     }
 }
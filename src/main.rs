@@ -1,4 +1,5 @@
 use std::env;
+use std::time::Duration;
 
 use actix::Actor;
 use actix_cors::Cors;
@@ -19,16 +20,31 @@ mod services;
 use actix_files::Files;
 
 use crate::handlers::command_handlers::{
-    add_event, add_team, add_team_members, add_team_with_members, add_user, delete_event,
-    delete_team, delete_user, end_event, login, reset_score, start_event, update_score,
+    add_announcement, add_challenge, add_event, add_participant, add_season, add_season_event,
+    add_team, add_team_members, add_team_with_members, add_user, delete_event, delete_team,
+    delete_user, end_event, issue_participant_key, login, participant_login, pause_event,
+    record_match, record_solve, remove_announcement, remove_season_event, reset_score,
+    resume_event, reveal_vboard, revert_score, start_event, submit_flag, update_clock,
+    update_marks, update_score, update_score_batch,
 };
 use crate::handlers::query_handlers::{
-    event_fts, get_all_event, get_all_team, get_all_user, get_current_event, get_event_info,
-    get_event_rem_members, get_event_teams, get_event_users, get_team_info, get_user_info,
-    team_fts, user_fts, vaderboard,
+    bracket, event_fts, get_all_event, get_all_seasons, get_all_team, get_all_user,
+    get_announcements, get_current_event, get_current_events, get_event_bracket,
+    get_event_challenges, get_event_info, get_event_ledger, get_event_marks, get_event_rem_members,
+    get_event_replay, get_event_teams, get_event_users, get_event_vboard, get_participant_ledger,
+    get_participant_marks, get_season, get_season_board, get_team_info, get_user_info, replay,
+    seasonboard, team_fts, user_fts, vaderboard, vaderboard_sse,
 };
-use crate::models::query_models::{CurFtsServer, VboardSrv};
-use crate::models::v_models::AppState;
+use crate::models::bracket_models::BracketSrv;
+use crate::models::flag_models::FlagThrottle;
+use crate::models::query_models::{
+    CurFtsServer, EventQueryState, VbClock, VbEventState, VboardSrv,
+};
+use crate::models::schedule_models::{EventScheduler, EventSrvs};
+use crate::models::season_models::SeasonSrv;
+use crate::models::v_models::{AppState, EventClock};
+use crate::models::wrapper_models::EventWrapper;
+use crate::models::ws_models::WsLimits;
 use crate::services::v_middlewares::AdminOnlyGuard;
 
 #[global_allocator]
@@ -49,16 +65,83 @@ async fn main() -> std::io::Result<()> {
             .parse::<u32>()
             .expect("Unable to parse VADERBOARD_COUNT,please replace with a positive integer")
     });
+    let vb_window = env::var("VADERBOARD_DEBOUNCE_MS").map_or(100, |window| {
+        window
+            .parse::<u64>()
+            .expect("Unable to parse VADERBOARD_DEBOUNCE_MS,please replace with a positive integer")
+    });
+    let ws_max_conns = env::var("WS_MAX_CONNECTIONS").map_or(1024, |count| {
+        count
+            .parse::<usize>()
+            .expect("Unable to parse WS_MAX_CONNECTIONS,please replace with a positive integer")
+    });
+    let ws_max_conns_per_ip = env::var("WS_MAX_CONNECTIONS_PER_IP").map_or(32, |count| {
+        count.parse::<usize>().expect(
+            "Unable to parse WS_MAX_CONNECTIONS_PER_IP,please replace with a positive integer",
+        )
+    });
+    let flag_max_attempts = env::var("FLAG_MAX_ATTEMPTS").map_or(10, |count| {
+        count
+            .parse::<u32>()
+            .expect("Unable to parse FLAG_MAX_ATTEMPTS,please replace with a positive integer")
+    });
+    let flag_window = env::var("FLAG_WINDOW_SECS").map_or(60, |secs| {
+        secs.parse::<u64>()
+            .expect("Unable to parse FLAG_WINDOW_SECS,please replace with a positive integer")
+    });
     let session_key = Key::generate();
     let host_port = format!("{}:{}", host, port);
     let db_pool = SqlitePool::connect(&db_url)
         .await
         .expect("Error connecting to Database");
-    let app_state = web::Data::new(AppState::new(vb_count));
+    //Restore Added/Active events on restart
+    let events = EventWrapper::restore_all(&db_pool)
+        .await
+        .expect("Error restoring events from Database");
+    events
+        .iter()
+        .for_each(|event| log::info!("Restored event : {}", event.get_id()));
+    let app_state = web::Data::new(AppState::new(vb_count, events));
+    let ws_limits = web::Data::new(WsLimits::new(ws_max_conns, ws_max_conns_per_ip));
+    let flag_throttle = web::Data::new(FlagThrottle::new(
+        flag_max_attempts,
+        Duration::from_secs(flag_window),
+    ));
     //VaderBoard server Actor
-    let vb_srv = VboardSrv::new(app_state.clone(), db_pool.clone()).start();
+    let vb_srv = VboardSrv::new(
+        app_state.clone(),
+        db_pool.clone(),
+        Duration::from_millis(vb_window),
+    )
+    .start();
+    //Resume clocks of active events
+    EventClock::get_all(&db_pool)
+        .await
+        .expect("Error restoring event clocks from Database")
+        .into_iter()
+        .for_each(|(event_id, clock)| vb_srv.do_send(VbClock(event_id, Some(clock))));
+    app_state
+        .events
+        .lock()
+        .await
+        .values()
+        .filter(|event| event.is_paused())
+        .for_each(|event| vb_srv.do_send(VbEventState(event.get_id(), EventQueryState::Paused)));
+    let br_srv = BracketSrv::new(db_pool.clone()).start();
+    let sn_srv = SeasonSrv::new(db_pool.clone()).start();
     //Current Event Fts Actor
     let cur_fts = CurFtsServer::new().start();
+    //Scheduled start / stop of events
+    let srvs = EventSrvs {
+        app_state: app_state.clone(),
+        vb_srv: vb_srv.clone(),
+        br_srv: br_srv.clone(),
+        sn_srv: sn_srv.clone(),
+        fts_srv: cur_fts.clone(),
+        db_pool: db_pool.clone(),
+    };
+    let scheduler = EventScheduler::new(srvs.clone()).start();
+    let srvs = web::Data::new(srvs);
     let cpus = num_cpus::get();
     log::info!("Database connection successful");
     log::info!("Server Starting on :  {}", host_port);
@@ -74,8 +157,14 @@ async fn main() -> std::io::Result<()> {
                     .build(),
             )
             .app_data(app_state.clone())
+            .app_data(ws_limits.clone())
+            .app_data(flag_throttle.clone())
             .app_data(Data::new(vb_srv.clone()))
+            .app_data(Data::new(br_srv.clone()))
+            .app_data(Data::new(sn_srv.clone()))
             .app_data(Data::new(cur_fts.clone()))
+            .app_data(Data::new(scheduler.clone()))
+            .app_data(srvs.clone())
             .app_data(Data::new(db_pool.clone()))
             .service(login)
             .service(
@@ -87,13 +176,35 @@ async fn main() -> std::io::Result<()> {
                     .service(add_team_members)
                     .service(add_team_with_members)
                     .service(start_event)
+                    .service(pause_event)
+                    .service(resume_event)
                     .service(update_score)
+                    .service(update_score_batch)
+                    .service(update_marks)
+                    .service(add_challenge)
+                    .service(record_solve)
+                    .service(issue_participant_key)
+                    .service(record_match)
+                    .service(reveal_vboard)
+                    .service(update_clock)
+                    .service(add_participant)
+                    .service(add_season)
+                    .service(add_announcement)
+                    .service(remove_announcement)
+                    .service(add_season_event)
+                    .service(remove_season_event)
+                    .service(revert_score)
                     .service(reset_score)
                     .service(delete_event)
                     .service(delete_team)
                     .service(delete_user)
-                    .service(end_event),
+                    .service(end_event)
+                    .service(get_event_ledger)
+                    .service(get_participant_ledger)
+                    .service(get_event_marks)
+                    .service(get_participant_marks),
             )
+            .service(get_current_events)
             .service(get_current_event)
             .service(get_event_teams)
             .service(get_event_rem_members)
@@ -107,7 +218,21 @@ async fn main() -> std::io::Result<()> {
             .service(event_fts)
             .service(team_fts)
             .service(user_fts)
+            .service(get_event_challenges)
+            .service(get_event_bracket)
+            .service(get_event_vboard)
+            .service(get_event_replay)
+            .service(get_all_seasons)
+            .service(get_season)
+            .service(get_season_board)
+            .service(get_announcements)
+            .service(participant_login)
+            .service(submit_flag)
             .service(vaderboard)
+            .service(vaderboard_sse)
+            .service(replay)
+            .service(bracket)
+            .service(seasonboard)
             .service(Files::new("/", "dist").index_file("index.html"))
     })
     .bind(host_port)?
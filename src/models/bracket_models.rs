@@ -0,0 +1,177 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use actix::{Actor, Addr, AsyncContext, Message};
+use actix_web::web;
+use actix_web_actors::ws;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use uuid::Uuid;
+
+use super::query_models::TransferType;
+use super::ws_models::{WsConn, WsHeartbeat};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum BracketFormat {
+    SingleElimination,
+    DoubleElimination,
+    RoundRobin,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Winners,
+    Losers,
+    GrandFinal,
+    RoundRobin,
+}
+
+// Match slot, Bye when no participant will ever take it
+#[derive(Clone, Copy, PartialEq)]
+pub enum Slot {
+    Pending,
+    Bye,
+    Filled(Uuid),
+}
+
+pub struct Match {
+    pub id: u32,
+    pub stage: Stage,
+    pub round: u32,
+    pub position: u32,
+    pub slots: [Slot; 2],
+    pub scores: [Option<i64>; 2],
+    pub winner: Option<Uuid>,
+    pub done: bool,
+    // (match id, slot) taken by winner / loser of this match
+    pub next: Option<(u32, usize)>,
+    pub loser_next: Option<(u32, usize)>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+// Matches of an event, match id is the index in matches
+pub struct Bracket {
+    pub event_id: Uuid,
+    pub format: BracketFormat,
+    pub matches: Vec<Match>,
+}
+
+#[derive(Serialize)]
+pub struct BracketState<'a> {
+    pub event_id: Uuid,
+    pub format: BracketFormat,
+    pub matches: Vec<MatchInfo<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub standings: Option<Vec<Standing<'a>>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlotInfo<'a> {
+    Pending,
+    Bye,
+    Participant { id: Uuid, name: Cow<'a, str> },
+}
+
+#[derive(Serialize)]
+pub struct MatchInfo<'a> {
+    pub id: u32,
+    pub stage: Stage,
+    pub round: u32,
+    pub position: u32,
+    pub a: SlotInfo<'a>,
+    pub b: SlotInfo<'a>,
+    pub score_a: Option<i64>,
+    pub score_b: Option<i64>,
+    pub winner: Option<Uuid>,
+    pub done: bool,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+// Round robin table row, win 3 points, draw 1 point
+#[derive(Serialize)]
+pub struct Standing<'a> {
+    pub rank: u32,
+    pub id: Uuid,
+    pub name: Cow<'a, str>,
+    pub played: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub points: u32,
+    pub score_for: i64,
+    pub score_against: i64,
+}
+
+// BracketGet(event_id,transfer_type)
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct BracketGet(pub Uuid, pub TransferType<BracketClient>);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct BracketRes<'a>(
+    pub Cow<'a, str>,
+    pub Option<(Uuid, TransferType<BracketClient>)>,
+);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct BrConnect(pub Uuid, pub Addr<BracketClient>);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct BrDisconnect(pub Uuid, pub Addr<BracketClient>);
+
+pub struct BracketClient {
+    pub event_id: Uuid,
+    pub srv_addr: Arc<Addr<BracketSrv>>,
+    pub addr: Option<Addr<Self>>,
+    pub conn: WsConn,
+}
+impl BracketClient {
+    pub fn new(event_id: Uuid, srv_addr: web::Data<Addr<BracketSrv>>, conn: WsConn) -> Self {
+        Self {
+            event_id,
+            srv_addr: srv_addr.into_inner(),
+            addr: None,
+            conn,
+        }
+    }
+}
+
+impl Actor for BracketClient {
+    type Context = ws::WebsocketContext<Self>;
+    fn started(&mut self, ctx: &mut Self::Context) {
+        let addr = ctx.address();
+        self.addr = Some(addr.clone());
+        self.start_heartbeat(ctx);
+        self.srv_addr.do_send(BrConnect(self.event_id, addr))
+    }
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        if let Some(addr) = &self.addr {
+            self.srv_addr
+                .do_send(BrDisconnect(self.event_id, addr.clone()))
+        }
+    }
+}
+
+pub struct BracketSrv {
+    // bracket clients of each event
+    pub br_addr: HashMap<Uuid, HashSet<Addr<BracketClient>>>,
+    pub db_pool: Pool<Sqlite>,
+}
+impl BracketSrv {
+    pub fn new(db_pool: Pool<Sqlite>) -> Self {
+        BracketSrv {
+            br_addr: HashMap::new(),
+            db_pool,
+        }
+    }
+}
+impl Actor for BracketSrv {
+    type Context = actix::Context<Self>;
+}
@@ -1,11 +1,13 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use super::error_models::VaderError;
-use super::query_models::EventType;
-use super::v_models::{Event, Team, User};
+use super::query_models::{Announcement, EventType, Severity};
+use super::v_models::{Event, EventConfig, Team, User};
 
 #[derive(Deserialize, Serialize)]
 pub struct EventReq<'a> {
@@ -13,13 +15,16 @@ pub struct EventReq<'a> {
     #[serde(default)]
     logo: Option<Cow<'a, str>>,
     pub event_type: EventType,
+    #[serde(flatten)]
+    pub config: EventConfig,
 }
 
 impl<'a> From<EventReq<'a>> for Result<Event<'a, Team<'a>>, VaderError<'a>> {
     fn from(req: EventReq<'a>) -> Self {
         match req.event_type {
             EventType::TeamEvent { team_size } => {
-                Ok(Event::<Team>::new(req.name, req.logo, Some(team_size)))
+                req.config.validate()?;
+                Ok(Event::<Team>::new(req.name, req.logo, Some(team_size)).with_config(req.config))
             }
             EventType::UserEvent => {
                 Err(VaderError::TeamSizeMismatch("time size was not specified"))
@@ -29,13 +34,123 @@ impl<'a> From<EventReq<'a>> for Result<Event<'a, Team<'a>>, VaderError<'a>> {
 }
 impl<'a> From<EventReq<'a>> for Result<Event<'a, User<'a>>, VaderError<'a>> {
     fn from(req: EventReq<'a>) -> Self {
-        Ok(Event::<User>::new(req.name, req.logo, None))
+        req.config.validate()?;
+        Ok(Event::<User>::new(req.name, req.logo, None).with_config(req.config))
     }
 }
 #[derive(Deserialize)]
 pub struct ScoreUpdate {
     pub id: Uuid,
     pub score: i64,
+    #[serde(default)]
+    pub mode: ScoreMode,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+// Marks of a participant for each criterion name
+// judge is always the logged in admin
+#[derive(Deserialize)]
+pub struct CriteriaMarks {
+    pub id: Uuid,
+    pub marks: HashMap<String, u32>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+// min_points and decay enable dynamic scoring, both or none should be given
+#[derive(Deserialize)]
+pub struct ChallengeReq<'a> {
+    pub name: Cow<'a, str>,
+    pub category: Cow<'a, str>,
+    pub points: i64,
+    #[serde(default)]
+    pub min_points: Option<i64>,
+    #[serde(default)]
+    pub decay: Option<u32>,
+    #[serde(default)]
+    pub first_blood: i64,
+    #[serde(default)]
+    pub flag: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SolveReq {
+    pub id: Uuid,
+}
+
+// No winner is a draw, allowed only in round robin
+#[derive(Deserialize)]
+pub struct MatchResult {
+    #[serde(default)]
+    pub winner: Option<Uuid>,
+    #[serde(default)]
+    pub score_a: Option<i64>,
+    #[serde(default)]
+    pub score_b: Option<i64>,
+}
+
+// Participant comes from the session, see ParticipantLogin
+#[derive(Deserialize)]
+pub struct FlagSubmit {
+    pub flag: String,
+}
+
+// Login of a participant to an event with the key issued by admins
+#[derive(Deserialize)]
+pub struct ParticipantLogin {
+    pub id: Uuid,
+    pub key: String,
+}
+
+#[derive(Serialize)]
+pub struct ParticipantKey {
+    pub id: Uuid,
+    pub key: String,
+}
+
+// Add score to current total or Set it to an absolute value
+#[derive(Deserialize, Clone, Copy, Default)]
+pub enum ScoreMode {
+    #[default]
+    Add,
+    Set,
+}
+
+// Reveal frozen vaderboard at once or one participant at a time from the bottom
+#[derive(Deserialize, Clone, Copy, Default)]
+pub enum RevealMode {
+    #[default]
+    All,
+    Step,
+}
+
+#[derive(Deserialize)]
+pub struct RevealReq {
+    #[serde(default)]
+    pub mode: RevealMode,
+}
+
+#[derive(Deserialize)]
+pub struct ScoreRevert {
+    #[serde(flatten)]
+    pub target: RevertTarget,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+// Revert a single score_ledger entry or the last count entries of a participant
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum RevertTarget {
+    Entry { entry_id: i64 },
+    Last { participant_id: Uuid, count: u32 },
+}
+
+// Audit info recorded with each score change in score_ledger
+pub struct ScoreLog {
+    pub admin: Option<String>,
+    pub reason: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -62,6 +177,53 @@ impl<'a> From<ContestantInfo<'a>> for Team<'a> {
     }
 }
 
+// Announcement for every vaderboard screen of an event, kept until it expires or is removed
+#[derive(Deserialize)]
+pub struct AnnouncementReq {
+    pub text: String,
+    #[serde(default)]
+    pub severity: Severity,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl AnnouncementReq {
+    pub fn announcement(self, event_id: Uuid) -> Result<Announcement, VaderError<'static>> {
+        let now = Utc::now();
+        if self.text.trim().is_empty() {
+            return Err(VaderError::AnnouncementError("Announcement text is empty"));
+        }
+        if self.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(VaderError::AnnouncementError(
+                "Announcement already expired",
+            ));
+        }
+        Ok(Announcement {
+            id: Uuid::new_v4(),
+            event_id,
+            text: self.text,
+            severity: self.severity,
+            created_at: now,
+            expires_at: self.expires_at,
+        })
+    }
+}
+
+// Admin control of an active event clock
+#[derive(Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ClockReq {
+    Pause,
+    Resume,
+    // restart countdown with a new duration in seconds or a fixed end time
+    Set {
+        #[serde(default)]
+        duration: Option<u64>,
+        #[serde(default)]
+        end_at: Option<DateTime<Utc>>,
+    },
+}
+
 #[derive(Serialize)]
 pub struct CommandResponse<'a> {
     msg: &'a str,
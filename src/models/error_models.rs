@@ -9,6 +9,7 @@ pub enum VaderError<'a> {
     EventNotActive(&'a str),
     EventEnded(&'a str),
     EventActive(&'a str),
+    EventPaused(&'a str),
     EventTypeMismatch(&'a str),
     SqlxError(sqlx::Error),
     SqlxFieldError(&'a str),
@@ -16,6 +17,19 @@ pub enum VaderError<'a> {
     TeamNotFound(&'a str),
     TeamSizeMismatch(&'a str),
     UserNotFound(&'a str),
+    ScoreRevertError(&'a str),
+    ScoreOutOfBounds(&'a str),
+    CriteriaMismatch(&'a str),
+    ChallengeError(&'a str),
+    BracketError(&'a str),
+    SeasonError(&'a str),
+    FreezeError(&'a str),
+    ConnectionLimit(&'a str),
+    AnnouncementError(&'a str),
+    ClockError(&'a str),
+    ScheduleError(&'a str),
+    ParticipantAuth(&'a str),
+    TooManyAttempts(&'a str),
     AdminHashError(BcryptError),
     BlockingOpError(BlockingError),
     SerdeJsonError(serde_json::Error),
@@ -67,6 +81,11 @@ impl<'a> Display for VaderError<'a> {
                 "Operation Cannot be performed on Active Event.\n[error] : {}",
                 e
             ),
+            VaderError::EventPaused(e) => write!(
+                f,
+                "Operation Cannot be performed while Event is Paused.\n[error] : {}",
+                e
+            ),
             VaderError::EventEnded(e) => write!(
                 f,
                 "Operation Cannot be performed on Event that Ended.\n[error] : {}",
@@ -77,6 +96,45 @@ impl<'a> Display for VaderError<'a> {
             VaderError::TeamSizeMismatch(e) => write!(f, "Team Size mismatch.\n[error] : {}", e),
 
             VaderError::UserNotFound(e) => write!(f, "User not Found.\n[error] : {}", e),
+            VaderError::ScoreRevertError(e) => {
+                write!(f, "Unable to revert score update.\n[error] : {}", e)
+            }
+            VaderError::ScoreOutOfBounds(e) => {
+                write!(f, "Score out of Event bounds.\n[error] : {}", e)
+            }
+            VaderError::CriteriaMismatch(e) => {
+                write!(f, "Criteria marks mismatch.\n[error] : {}", e)
+            }
+            VaderError::ChallengeError(e) => {
+                write!(f, "Challenge operation failed.\n[error] : {}", e)
+            }
+            VaderError::BracketError(e) => {
+                write!(f, "Bracket operation failed.\n[error] : {}", e)
+            }
+            VaderError::SeasonError(e) => {
+                write!(f, "Season operation failed.\n[error] : {}", e)
+            }
+            VaderError::FreezeError(e) => {
+                write!(f, "Vaderboard freeze operation failed.\n[error] : {}", e)
+            }
+            VaderError::ConnectionLimit(e) => {
+                write!(f, "Too many connections.\n[error] : {}", e)
+            }
+            VaderError::AnnouncementError(e) => {
+                write!(f, "Announcement operation failed.\n[error] : {}", e)
+            }
+            VaderError::ClockError(e) => {
+                write!(f, "Event clock operation failed.\n[error] : {}", e)
+            }
+            VaderError::ScheduleError(e) => {
+                write!(f, "Event schedule is invalid.\n[error] : {}", e)
+            }
+            VaderError::ParticipantAuth(e) => {
+                write!(f, "Participant authentication failed.\n[error] : {}", e)
+            }
+            VaderError::TooManyAttempts(e) => {
+                write!(f, "Too many failed attempts.\n[error] : {}", e)
+            }
             VaderError::AdminHashError(e) => {
                 write!(f, "Admin Hash Error.\n[error] : {}", e)
             }
@@ -88,11 +146,7 @@ impl<'a> Display for VaderError<'a> {
                 )
             }
             VaderError::SerdeJsonError(e) => {
-                write!(
-                    f,
-                    "Error in serializing object.\n[error] : {}",
-                    e
-                )
+                write!(f, "Error in serializing object.\n[error] : {}", e)
             }
         }
     }
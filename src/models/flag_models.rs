@@ -0,0 +1,24 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+// Wrong flags and participant keys allowed per participant and per client ip within a window
+pub struct FlagThrottle {
+    pub max_attempts: u32,
+    pub window: Duration,
+    pub failures: Mutex<HashMap<AttemptKey, FailedAttempts>>,
+}
+
+#[derive(Hash, PartialEq, Eq, Clone, Copy)]
+pub enum AttemptKey {
+    Participant(Uuid),
+    Ip(IpAddr),
+}
+
+pub struct FailedAttempts {
+    pub count: u32,
+    pub since: Instant,
+}
@@ -1,5 +1,11 @@
+pub mod bracket_models;
 pub mod command_models;
 pub mod error_models;
+pub mod flag_models;
 pub mod query_models;
+pub mod replay_models;
+pub mod schedule_models;
+pub mod season_models;
 pub mod v_models;
 pub mod wrapper_models;
+pub mod ws_models;
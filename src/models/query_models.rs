@@ -1,18 +1,23 @@
 use core::hash::Hash;
 use std::borrow::Cow;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::marker::PhantomData;
 use std::sync::Arc;
+use std::time::Duration;
 
-use actix::{Actor, Addr, AsyncContext, Message};
+use actix::{Actor, ActorContext, Addr, AsyncContext, Message, Recipient};
+use actix_web::web::Bytes;
 use actix_web::{web, Either};
 use actix_web_actors::ws;
+use chrono::{DateTime, Utc};
+use futures::channel::mpsc::UnboundedSender;
 use serde::{Deserialize, Serialize};
 use serde_repr::Serialize_repr;
 use sqlx::{Pool, Sqlite, SqlitePool};
 use uuid::Uuid;
 
-use super::v_models::{AppState, Player, Team, User};
+use super::v_models::{AppState, Challenge, EventClock, EventConfig, Player, Team, User};
+use super::ws_models::{WsConn, WsHeartbeat, WS_TIMEOUT};
 use crate::services::query_services::Queriable;
 
 // #[derive(Serialize)]
@@ -58,6 +63,7 @@ pub enum EventQueryState {
     Added,
     Start,
     Stop,
+    Paused,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -87,19 +93,76 @@ pub struct TeamInfo<'a> {
     pub logo: Option<Cow<'a, str>>,
 }
 
+// Vaderboard row, breakdown holds criteria marks when enabled for the event
+#[derive(Serialize, Clone, PartialEq)]
+pub struct VboardEntry<'a> {
+    pub rank: u32,
+    pub id: Uuid,
+    pub name: Cow<'a, str>,
+    pub score: i64,
+    pub logo: Option<Cow<'a, str>>,
+    pub solves: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breakdown: Option<BTreeMap<String, f64>>,
+    // score is as of freeze time, not yet revealed
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub frozen: bool,
+}
+
+// Copied out of a registry event so its vaderboard is fetched without holding the registry
+pub struct VboardSource {
+    pub event_id: Uuid,
+    pub table: &'static str,
+    pub config: EventConfig,
+}
+
+// Challenge with its solve count and current point value
+#[derive(Serialize)]
+pub struct ChallengeInfo<'a> {
+    #[serde(flatten)]
+    pub challenge: Challenge<'a>,
+    pub solves: u32,
+    pub value: i64,
+}
+
+// Marks given by a judge, only visible to admins
+#[derive(Serialize)]
+pub struct JudgeMarks<'a> {
+    pub participant_id: Uuid,
+    pub judge: Cow<'a, str>,
+    pub criterion: Cow<'a, str>,
+    pub marks: i64,
+}
+
+#[derive(Serialize)]
+pub struct LedgerEntry<'a> {
+    pub id: i64,
+    pub event_id: Uuid,
+    pub participant_id: Uuid,
+    pub delta: i64,
+    pub total: i64,
+    pub admin: Option<Cow<'a, str>>,
+    pub reason: Option<Cow<'a, str>>,
+    pub created_at: DateTime<Utc>,
+    pub reverted_by: Option<i64>,
+    pub revert_of: Option<i64>,
+}
+
 pub struct FtsQuery<'a, T: Queriable> {
     pub db_pool: Arc<SqlitePool>,
     pub count: u32,
+    pub conn: WsConn,
     type_marker: PhantomData<&'a T>,
 }
 impl<'a, T> FtsQuery<'a, T>
 where
     T: Queriable,
 {
-    pub fn new(count: u32, db_pool: Arc<SqlitePool>) -> Self {
+    pub fn new(count: u32, db_pool: Arc<SqlitePool>, conn: WsConn) -> Self {
         Self {
             db_pool,
             count,
+            conn,
             type_marker: PhantomData::<&'a T>,
         }
     }
@@ -132,10 +195,14 @@ impl CurFtsServer<'_> {
 }
 impl Actor for CurFtsServer<'_> {
     type Context = actix::Context<Self>;
+    fn started(&mut self, ctx: &mut Self::Context) {
+        // clients that stopped without a disconnect
+        ctx.run_interval(WS_TIMEOUT, |act, _ctx| act.remove_stale());
+    }
 }
 #[derive(Message)]
 #[rtype(result = "()")]
-pub struct CurFtsStop;
+pub struct CurFtsStop(pub Uuid);
 
 #[derive(Message)]
 #[rtype(result = "()")]
@@ -156,6 +223,7 @@ pub struct CurFtsBuilder<'a, P: Player<'a>> {
     db_pool: Arc<SqlitePool>,
     type_marker: PhantomData<&'a P>,
     count: u32,
+    conn: WsConn,
 }
 
 pub struct CurFtsTeamBuilder {
@@ -164,6 +232,7 @@ pub struct CurFtsTeamBuilder {
     db_pool: Arc<SqlitePool>,
     team_opt: TeamFtsOpt,
     count: u32,
+    conn: WsConn,
 }
 
 pub trait CurEventFtsMarker: Queriable {}
@@ -180,6 +249,7 @@ where
     pub db_pool: Arc<SqlitePool>,
     pub count: u32,
     pub team_opt: Option<TeamFtsOpt>,
+    pub conn: WsConn,
     type_marker: PhantomData<&'a T>,
 }
 
@@ -192,12 +262,14 @@ where
         srv_addr: Arc<Addr<CurFtsServer>>,
         count: u32,
         db_pool: Arc<SqlitePool>,
+        conn: WsConn,
     ) -> Self {
         CurFtsBuilder {
             event_id,
             db_pool,
             srv_addr,
             count,
+            conn,
             type_marker: PhantomData::<&'a P>,
         }
     }
@@ -215,6 +287,7 @@ where
             srv_addr: self.srv_addr,
             team_opt: None,
             count: self.count,
+            conn: self.conn,
             type_marker: PhantomData::<&'a User>,
         }
     }
@@ -226,6 +299,7 @@ impl<'a> CurFtsBuilder<'a, Team<'a>> {
             srv_addr: self.srv_addr,
             db_pool: self.db_pool,
             count: self.count,
+            conn: self.conn,
             team_opt: TeamFtsOpt::TeamInfo,
         }
     }
@@ -235,6 +309,7 @@ impl<'a> CurFtsBuilder<'a, Team<'a>> {
             srv_addr: self.srv_addr,
             db_pool: self.db_pool,
             count: self.count,
+            conn: self.conn,
             team_opt: TeamFtsOpt::RemUserInfo,
         }
     }
@@ -251,41 +326,237 @@ where
             srv_addr: self.srv_addr,
             count: self.count,
             team_opt: Some(self.team_opt),
+            conn: self.conn,
             type_marker: PhantomData::<&'a TeamInfo>,
         }
     }
 }
 
-pub enum TransferType {
+pub enum TransferType<T: Actor = VboardClient> {
     Broadcast,
-    Unicast(Addr<VboardClient>),
+    Unicast(Addr<T>),
 }
 
+// VboardGet(event_id,transfer_type)
 #[derive(Message)]
 #[rtype(result = "()")]
-pub struct VboardGet(pub TransferType);
+pub struct VboardGet(pub Uuid, pub TransferType);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct VboardRes<'a>(pub Cow<'a, str>);
+
+// websocket and sse clients of the vaderboard
+pub type VboardRecipient = Recipient<VboardRes<'static>>;
+
+// Vaderboard websocket messages, snapshot on subscribe / resync and diffs afterwards
+// seq of a diff is one more than the previous one, diffs up to snapshot seq are already applied
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VboardMsg<'a> {
+    Snapshot {
+        event_id: Uuid,
+        window: VboardWindow,
+        seq: u64,
+        entries: &'a [VboardEntry<'static>],
+    },
+    Diff {
+        seq: u64,
+        changed: Vec<VboardChange<'a>>,
+        entered: Vec<&'a VboardEntry<'static>>,
+        left: Vec<VboardLeft>,
+    },
+    Error {
+        msg: &'a str,
+    },
+    Announcement(&'a Announcement),
+    AnnouncementRemoved {
+        id: Uuid,
+    },
+    // replay position, at is None before the first change
+    Replay {
+        at: Option<DateTime<Utc>>,
+        speed: f64,
+        paused: bool,
+        done: bool,
+    },
+    // countdown of the event, remaining_ms is as of server_time
+    Clock {
+        event_id: Uuid,
+        server_time: DateTime<Utc>,
+        remaining_ms: i64,
+        paused: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        ends_at: Option<DateTime<Utc>>,
+    },
+    ClockStopped {
+        event_id: Uuid,
+    },
+    // score intake of the event is on hold until resumed
+    EventPaused {
+        event_id: Uuid,
+    },
+    EventResumed {
+        event_id: Uuid,
+    },
+    // reply to a sync request, client_time is echoed back to measure the round trip
+    ClockSync {
+        client_time: f64,
+        server_time: DateTime<Utc>,
+    },
+}
+
+// Row still in the vaderboard with its previous rank and score
+#[derive(Serialize)]
+pub struct VboardChange<'a> {
+    #[serde(flatten)]
+    pub entry: &'a VboardEntry<'static>,
+    pub old_rank: u32,
+    pub old_score: i64,
+}
+
+#[derive(Serialize)]
+pub struct VboardLeft {
+    pub id: Uuid,
+    pub old_rank: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Serialize, Clone)]
+pub struct Announcement {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub text: String,
+    pub severity: Severity,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
 
 #[derive(Message)]
 #[rtype(result = "()")]
-pub struct VboardRes<'a>(pub Cow<'a, str>, pub Option<TransferType>);
+pub struct VbAnnounce(pub Announcement);
+
+// VbRetract(event_id, announcement_id), false when no such announcement is active
+#[derive(Message)]
+#[rtype(result = "bool")]
+pub struct VbRetract(pub Uuid, pub Uuid);
 
+// VbAnnouncements(event_id), active announcements of the event
+#[derive(Message)]
+#[rtype(result = "Vec<Announcement>")]
+pub struct VbAnnouncements(pub Uuid);
+
+// VbClock(event_id, clock), None once the event has no clock
 #[derive(Message)]
 #[rtype(result = "()")]
-pub struct VbDisconnect(pub Addr<VboardClient>);
+pub struct VbClock(pub Uuid, pub Option<EventClock>);
 
+// VbEventState(event_id, state), clients are told when the event is paused / resumed
 #[derive(Message)]
 #[rtype(result = "()")]
-pub struct VbConnect(pub Addr<VboardClient>);
+pub struct VbEventState(pub Uuid, pub EventQueryState);
+
+fn default_radius() -> u32 {
+    5
+}
+
+// Rows of the vaderboard a client follows
+// {"from":11,"to":30} , {"around":participant_id,"radius":5} or {"count":20}
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(untagged)]
+pub enum VboardWindow {
+    Ranks {
+        from: u32,
+        to: u32,
+    },
+    Around {
+        around: Uuid,
+        #[serde(default = "default_radius")]
+        radius: u32,
+    },
+    // top VADERBOARD_COUNT rows when count is not given
+    Top {
+        #[serde(default)]
+        count: Option<u32>,
+    },
+}
+
+// Text frames sent by vaderboard clients
+// {"action":"subscribe","event_id":...,"count":20} or {"action":"resync"}
+#[derive(Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum VboardReq {
+    Subscribe {
+        event_id: Uuid,
+        #[serde(flatten)]
+        window: VboardWindow,
+    },
+    Resync,
+    // {"action":"sync","client_time":...} to align local time with the server clock
+    Sync {
+        client_time: f64,
+    },
+}
+
+// Subscription of a client with the rows last sent to it
+pub struct VboardSub {
+    pub event_id: Uuid,
+    pub window: VboardWindow,
+    pub seq: u64,
+    pub entries: Vec<VboardEntry<'static>>,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct VbSubscribe(pub VboardRecipient, pub Uuid, pub VboardWindow);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct VbDisconnect(pub Uuid, pub VboardRecipient);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct VbConnect(pub Uuid, pub VboardRecipient, pub VboardWindow);
+
+// VbResync(client), fresh snapshot of the window the client is subscribed to
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct VbResync(pub VboardRecipient);
+
+// VbPoll(event_id, window), rows of a plain http vaderboard request
+// None when no event is added with the id
+#[derive(Message)]
+#[rtype(result = "Result<Option<VboardPoll>, String>")]
+pub struct VbPoll(pub Uuid, pub VboardWindow);
+
+// Rows of the window, tagged with the version of the standings they were taken from
+pub struct VboardPoll {
+    pub etag: String,
+    pub entries: Vec<VboardEntry<'static>>,
+}
 
 pub struct VboardClient {
+    pub event_id: Uuid,
     pub srv_addr: Arc<Addr<VboardSrv>>,
     pub addr: Option<Addr<Self>>,
+    pub conn: WsConn,
 }
 impl VboardClient {
-    pub fn new(srv_addr: web::Data<Addr<VboardSrv>>) -> Self {
+    pub fn new(event_id: Uuid, srv_addr: web::Data<Addr<VboardSrv>>, conn: WsConn) -> Self {
         Self {
+            event_id,
             srv_addr: srv_addr.into_inner(),
             addr: None,
+            conn,
         }
     }
 }
@@ -295,24 +566,143 @@ impl Actor for VboardClient {
     fn started(&mut self, ctx: &mut Self::Context) {
         let addr = ctx.address();
         self.addr = Some(addr.clone());
-        self.srv_addr.do_send(VbConnect(addr))
+        self.start_heartbeat(ctx);
+        self.srv_addr.do_send(VbConnect(
+            self.event_id,
+            addr.recipient(),
+            VboardWindow::Top { count: None },
+        ))
     }
     fn stopped(&mut self, _ctx: &mut Self::Context) {
         if let Some(addr) = &self.addr {
-            self.srv_addr.do_send(VbDisconnect(addr.clone()))
+            self.srv_addr
+                .do_send(VbDisconnect(self.event_id, addr.clone().recipient()))
+        }
+    }
+}
+
+pub const SSE_KEEPALIVE: Duration = Duration::from_secs(15);
+// sse clients can't ask for a resync, a full snapshot is pushed every SSE_RESYNC instead
+pub const SSE_RESYNC: Duration = Duration::from_secs(60);
+pub const CLOCK_TICK: Duration = Duration::from_secs(1);
+
+// Server-Sent Events client, pushes vaderboard messages into the response stream
+pub struct VboardSse {
+    pub event_id: Uuid,
+    pub window: VboardWindow,
+    pub srv_addr: Arc<Addr<VboardSrv>>,
+    pub tx: UnboundedSender<Bytes>,
+    // holds the connection slot, sse streams count against the same limits as websockets
+    pub conn: WsConn,
+}
+impl VboardSse {
+    pub fn new(
+        event_id: Uuid,
+        window: VboardWindow,
+        srv_addr: web::Data<Addr<VboardSrv>>,
+        tx: UnboundedSender<Bytes>,
+        conn: WsConn,
+    ) -> Self {
+        Self {
+            event_id,
+            window,
+            srv_addr: srv_addr.into_inner(),
+            tx,
+            conn,
+        }
+    }
+}
+
+impl Actor for VboardSse {
+    type Context = actix::Context<Self>;
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.srv_addr.do_send(VbConnect(
+            self.event_id,
+            ctx.address().recipient(),
+            self.window,
+        ));
+        // comment lines keep proxies from closing the stream and detect gone clients
+        ctx.run_interval(SSE_KEEPALIVE, |act, ctx| {
+            if act
+                .tx
+                .unbounded_send(Bytes::from_static(b": keepalive\n\n"))
+                .is_err()
+            {
+                log::debug!("Sse client went away [ip : {}]", act.conn.permit.ip);
+                ctx.stop();
+            }
+        });
+        ctx.run_interval(SSE_RESYNC, |act, ctx| {
+            act.srv_addr.do_send(VbResync(ctx.address().recipient()))
+        });
+    }
+    fn stopped(&mut self, ctx: &mut Self::Context) {
+        self.srv_addr
+            .do_send(VbDisconnect(self.event_id, ctx.address().recipient()))
+    }
+}
+
+// Window from query params of plain http vaderboard requests
+// ?from=11&to=30 , ?around=participant_id&radius=5 or ?count=20
+#[derive(Deserialize)]
+pub struct VboardQuery {
+    pub from: Option<u32>,
+    pub to: Option<u32>,
+    pub around: Option<Uuid>,
+    pub radius: Option<u32>,
+    pub count: Option<u32>,
+}
+impl From<VboardQuery> for VboardWindow {
+    fn from(q: VboardQuery) -> Self {
+        match (q.from, q.to, q.around) {
+            (Some(from), Some(to), _) => VboardWindow::Ranks { from, to },
+            (_, _, Some(around)) => VboardWindow::Around {
+                around,
+                radius: q.radius.unwrap_or_else(default_radius),
+            },
+            _ => VboardWindow::Top { count: q.count },
         }
     }
 }
 
 pub struct VboardSrv {
-    pub vb_addr: HashSet<Addr<VboardClient>>,
+    // vaderboard clients of each event
+    pub vb_addr: HashMap<Uuid, HashSet<VboardRecipient>>,
+    pub vb_subs: HashMap<VboardRecipient, VboardSub>,
+    // standings of events with clients or pollers, dropped on score / roster changes
+    pub vb_cache: HashMap<Uuid, Vec<VboardEntry<'static>>>,
+    // bumped on every change of the standings, with vb_epoch it is the ETag of polled boards
+    pub vb_versions: HashMap<Uuid, u64>,
+    pub vb_epoch: i64,
+    // active announcements of each event, sent to clients of the event on connect
+    // loaded back from the db when the server starts
+    pub vb_announcements: HashMap<Uuid, Vec<Announcement>>,
+    // running and paused event clocks, ticked to clients of the event
+    pub vb_clocks: HashMap<Uuid, EventClock>,
+    pub vb_paused: HashSet<Uuid>,
+    // events with a scheduled vaderboard fetch and clients waiting for a snapshot
+    pub vb_pending: HashMap<Uuid, Vec<VboardRecipient>>,
+    pub vb_fetching: HashSet<Uuid>,
+    // updates within the window are coalesced into one fetch
+    pub vb_window: Duration,
     pub app_state: web::Data<AppState>,
     pub db_pool: Pool<Sqlite>,
 }
 impl VboardSrv {
-    pub fn new(app_state: web::Data<AppState>, db_pool: Pool<Sqlite>) -> Self {
+    pub fn new(app_state: web::Data<AppState>, db_pool: Pool<Sqlite>, vb_window: Duration) -> Self {
         VboardSrv {
-            vb_addr: HashSet::new(),
+            vb_addr: HashMap::new(),
+            vb_subs: HashMap::new(),
+            vb_cache: HashMap::new(),
+            vb_versions: HashMap::new(),
+            // versions restart at 0, the epoch keeps ETags from before a restart from matching
+            vb_epoch: Utc::now().timestamp_millis(),
+            vb_announcements: HashMap::new(),
+            vb_clocks: HashMap::new(),
+            vb_paused: HashSet::new(),
+            vb_pending: HashMap::new(),
+            vb_fetching: HashSet::new(),
+            vb_window,
             app_state,
             db_pool,
         }
@@ -320,4 +710,11 @@ impl VboardSrv {
 }
 impl Actor for VboardSrv {
     type Context = actix::Context<Self>;
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.load_announcements(ctx);
+        self.load_announcements(ctx);
+        // clients that stopped without a disconnect
+        ctx.run_interval(WS_TIMEOUT, |act, _ctx| act.remove_stale());
+        ctx.run_interval(CLOCK_TICK, |act, _ctx| act.tick_clocks());
+    }
 }
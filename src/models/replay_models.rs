@@ -0,0 +1,78 @@
+use actix::{Actor, SpawnHandle};
+use actix_web_actors::ws;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::query_models::{VboardEntry, VboardSub};
+use super::v_models::RankingMode;
+use super::ws_models::{WsConn, WsHeartbeat};
+
+// Score change recorded in score_ledger
+pub struct ScoreChange {
+    pub at: DateTime<Utc>,
+    pub participant_id: Uuid,
+    pub delta: i64,
+    pub total: i64,
+}
+
+// Recorded score changes and solves of an event, up to the freeze time while frozen
+pub struct Timeline {
+    pub event_id: Uuid,
+    pub ranking: RankingMode,
+    // participants with zero score
+    pub participants: Vec<VboardEntry<'static>>,
+    pub changes: Vec<ScoreChange>,
+    pub solves: Vec<(DateTime<Utc>, Uuid)>,
+    // distinct times at which the vaderboard changed
+    pub steps: Vec<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+pub struct ReplayAt {
+    pub at: DateTime<Utc>,
+}
+
+fn default_speed() -> f64 {
+    1.0
+}
+
+#[derive(Deserialize)]
+pub struct ReplayOpts {
+    #[serde(default = "default_speed")]
+    pub speed: f64,
+}
+
+// Text frames sent by replay clients
+// {"action":"speed","speed":10} , {"action":"pause"} , {"action":"resume"} or {"action":"seek","at":...}
+#[derive(Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ReplayReq {
+    Speed { speed: f64 },
+    Pause,
+    Resume,
+    Seek { at: DateTime<Utc> },
+}
+
+// Replays the timeline with the vaderboard messages of live clients
+// speed scales the real time between two changes
+pub struct VboardReplay {
+    pub timeline: Timeline,
+    pub sub: VboardSub,
+    pub vb_count: u32,
+    // steps already applied
+    pub step: usize,
+    pub speed: f64,
+    pub paused: bool,
+    pub next: Option<SpawnHandle>,
+    pub conn: WsConn,
+}
+
+impl Actor for VboardReplay {
+    type Context = ws::WebsocketContext<Self>;
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.start_heartbeat(ctx);
+        self.send_snapshot(ctx);
+        self.schedule(ctx);
+    }
+}
@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use actix::{Actor, Addr, Message, SpawnHandle};
+use actix_web::web;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Sqlite};
+use uuid::Uuid;
+
+use super::bracket_models::BracketSrv;
+use super::query_models::{CurFtsServer, VboardSrv};
+use super::season_models::SeasonSrv;
+use super::v_models::AppState;
+
+// Far away transitions are rechecked at least once a day
+pub const MAX_TIMER: Duration = Duration::from_secs(24 * 60 * 60);
+
+// Everything an event start / stop notifies, shared by admin handlers and the scheduler
+#[derive(Clone)]
+pub struct EventSrvs {
+    pub app_state: web::Data<AppState>,
+    pub vb_srv: Addr<VboardSrv>,
+    pub br_srv: Addr<BracketSrv>,
+    pub sn_srv: Addr<SeasonSrv>,
+    pub fts_srv: Addr<CurFtsServer<'static>>,
+    pub db_pool: Pool<Sqlite>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transition {
+    Start,
+    Stop,
+}
+
+// ScheduleEvent(event_id, transition and its time), None cancels the pending transition
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct ScheduleEvent(pub Uuid, pub Option<(Transition, DateTime<Utc>)>);
+
+// Performs start_at / end_at transitions, an event has at most one pending transition
+pub struct EventScheduler {
+    pub srvs: EventSrvs,
+    pub timers: HashMap<Uuid, SpawnHandle>,
+}
+impl EventScheduler {
+    pub fn new(srvs: EventSrvs) -> Self {
+        EventScheduler {
+            srvs,
+            timers: HashMap::new(),
+        }
+    }
+}
+impl Actor for EventScheduler {
+    type Context = actix::Context<Self>;
+    fn started(&mut self, ctx: &mut Self::Context) {
+        // schedules of events restored from db
+        self.restore(ctx);
+    }
+}
@@ -0,0 +1,133 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use actix::{Actor, Addr, AsyncContext, Message};
+use actix_web::web;
+use actix_web_actors::ws;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use uuid::Uuid;
+
+use super::query_models::TransferType;
+use super::ws_models::{WsConn, WsHeartbeat};
+
+// Season groups events, placements in them earn points : )
+#[derive(Serialize)]
+pub struct Season<'a> {
+    pub id: Uuid,
+    pub name: Cow<'a, str>,
+    // points by placement, index 0 is 1st place
+    pub points: Vec<u32>,
+    pub events: Vec<SeasonEvent>,
+}
+
+#[derive(Serialize)]
+pub struct SeasonEvent {
+    pub event_id: Uuid,
+    pub multiplier: f64,
+}
+
+#[derive(Deserialize)]
+pub struct SeasonReq<'a> {
+    pub name: Cow<'a, str>,
+    pub points: Vec<u32>,
+}
+
+fn default_multiplier() -> f64 {
+    1.0
+}
+
+#[derive(Deserialize)]
+pub struct SeasonEventReq {
+    pub event_id: Uuid,
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+}
+
+#[derive(Serialize)]
+pub struct SeasonResult {
+    pub event_id: Uuid,
+    pub placement: u32,
+    pub points: f64,
+}
+
+#[derive(Serialize)]
+pub struct SeasonEntry<'a> {
+    pub rank: u32,
+    pub id: Uuid,
+    pub name: Cow<'a, str>,
+    pub logo: Option<Cow<'a, str>>,
+    pub points: f64,
+    pub results: Vec<SeasonResult>,
+}
+
+// SeasonGet(season_id,transfer_type)
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SeasonGet(pub Uuid, pub TransferType<SeasonClient>);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SeasonRes<'a>(
+    pub Cow<'a, str>,
+    pub Option<(Uuid, TransferType<SeasonClient>)>,
+);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SnConnect(pub Uuid, pub Addr<SeasonClient>);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SnDisconnect(pub Uuid, pub Addr<SeasonClient>);
+
+pub struct SeasonClient {
+    pub season_id: Uuid,
+    pub srv_addr: Arc<Addr<SeasonSrv>>,
+    pub addr: Option<Addr<Self>>,
+    pub conn: WsConn,
+}
+impl SeasonClient {
+    pub fn new(season_id: Uuid, srv_addr: web::Data<Addr<SeasonSrv>>, conn: WsConn) -> Self {
+        Self {
+            season_id,
+            srv_addr: srv_addr.into_inner(),
+            addr: None,
+            conn,
+        }
+    }
+}
+
+impl Actor for SeasonClient {
+    type Context = ws::WebsocketContext<Self>;
+    fn started(&mut self, ctx: &mut Self::Context) {
+        let addr = ctx.address();
+        self.addr = Some(addr.clone());
+        self.start_heartbeat(ctx);
+        self.srv_addr.do_send(SnConnect(self.season_id, addr))
+    }
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        if let Some(addr) = &self.addr {
+            self.srv_addr
+                .do_send(SnDisconnect(self.season_id, addr.clone()))
+        }
+    }
+}
+
+pub struct SeasonSrv {
+    // season board clients of each season
+    pub sn_addr: HashMap<Uuid, HashSet<Addr<SeasonClient>>>,
+    pub db_pool: Pool<Sqlite>,
+}
+impl SeasonSrv {
+    pub fn new(db_pool: Pool<Sqlite>) -> Self {
+        SeasonSrv {
+            sn_addr: HashMap::new(),
+            db_pool,
+        }
+    }
+}
+impl Actor for SeasonSrv {
+    type Context = actix::Context<Self>;
+}
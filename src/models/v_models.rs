@@ -1,33 +1,39 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::future::Future;
 use std::marker::PhantomData;
 use std::pin::Pin;
 
 use bcrypt::verify;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+use super::bracket_models::BracketFormat;
 use super::error_models::VaderError;
 use super::wrapper_models::EventWrapper;
 
 pub type AsyncDbRes<'a, T> = Pin<Box<dyn Future<Output = Result<T, VaderError<'a>>> + Send + 'a>>;
 
+// Event registry keyed by event id : )
 pub struct AppState {
-    pub current_event: Mutex<Option<EventWrapper<'static>>>,
+    pub events: Mutex<HashMap<Uuid, EventWrapper<'static>>>,
     pub vb_count: u32,
 }
 impl AppState {
-    pub fn new(vb_count: u32) -> Self {
+    pub fn new(vb_count: u32, events: Vec<EventWrapper<'static>>) -> Self {
         AppState {
-            current_event: Mutex::new(None),
+            events: Mutex::new(events.into_iter().map(|e| (e.get_id(), e)).collect()),
             vb_count,
         }
     }
 }
 
 pub trait Player<'a>: Send + Sync {
+    // table holding participants of this kind
+    const TABLE: &'static str;
     fn add_player(&'a self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()>;
     fn get_id(&self) -> Uuid;
     fn get_logo(&self) -> String;
@@ -46,14 +52,98 @@ pub trait VaderEvent<'a> {
     fn get_logo(&self) -> String;
 }
 
-pub trait EventState: Send + Sync {}
+pub trait EventState: Send + Sync {
+    // value stored in events.state column
+    const STATE: &'static str;
+}
 
 pub struct NewEvent;
 pub struct ActiveEvent;
+// Active event holding score intake, e.g. during a dispute
+pub struct PausedEvent;
 pub struct EndEvent;
-impl EventState for NewEvent {}
-impl EventState for ActiveEvent {}
-impl EventState for EndEvent {}
+impl EventState for NewEvent {
+    const STATE: &'static str = "new";
+}
+impl EventState for ActiveEvent {
+    const STATE: &'static str = "active";
+}
+impl EventState for PausedEvent {
+    const STATE: &'static str = "paused";
+}
+impl EventState for EndEvent {
+    const STATE: &'static str = "end";
+}
+
+// Per-event scoring rules
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct EventConfig {
+    #[serde(default)]
+    pub min_score: Option<i64>,
+    #[serde(default)]
+    pub max_score: Option<i64>,
+    // Score is the weighted total of criteria marks when criteria are set
+    #[serde(default)]
+    pub criteria: Vec<Criterion>,
+    #[serde(default)]
+    pub show_breakdown: bool,
+    // How marks of several judges are combined
+    #[serde(default)]
+    pub aggregation: Aggregation,
+    #[serde(default)]
+    pub ranking: RankingMode,
+    // Matches are generated from participants when event starts
+    #[serde(default)]
+    pub bracket: Option<BracketFormat>,
+    // Vaderboard shows scores as of this time until revealed
+    #[serde(default)]
+    pub freeze_at: Option<DateTime<Utc>>,
+    // Countdown started with the event, either duration in seconds or a fixed end time
+    #[serde(default)]
+    pub duration: Option<u64>,
+    // Event with end_at is stopped by the server once its clock runs out
+    #[serde(default)]
+    pub end_at: Option<DateTime<Utc>>,
+    // Event is started by the server at this time
+    #[serde(default)]
+    pub start_at: Option<DateTime<Utc>>,
+}
+
+// Server side countdown of an active event
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum EventClock {
+    Running { ends_at: DateTime<Utc> },
+    Paused { remaining_ms: i64 },
+}
+
+// Vaderboard ranking of tied scores, ties are broken by earliest to reach the score
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+pub enum RankingMode {
+    #[default]
+    Standard,
+    Dense,
+    Ordinal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+pub enum Aggregation {
+    #[default]
+    Mean,
+    Median,
+    // mean after dropping the highest and lowest value, dropping only one
+    // side is not supported
+    TrimmedMean,
+}
+
+// Judging criterion, full marks contribute weight points to the total
+// weight is a whole number of points, partial marks are rounded to the nearest point
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Criterion {
+    pub name: String,
+    pub weight: f64,
+    pub max_marks: u32,
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct Event<'a, T: Player<'a>, U: EventState = NewEvent> {
@@ -62,6 +152,8 @@ pub struct Event<'a, T: Player<'a>, U: EventState = NewEvent> {
     #[serde(default)]
     pub logo: Option<Cow<'a, str>>,
     pub team_size: Option<u32>,
+    #[serde(default)]
+    pub config: EventConfig,
     pub player_marker: PhantomData<&'a T>,
     pub state_marker: PhantomData<&'a U>,
 }
@@ -108,6 +200,20 @@ pub struct User<'a> {
     pub logo: Option<Cow<'a, str>>,
 }
 
+// CTF challenge of an event, flag holds the bcrypt hash of the flag
+#[derive(Serialize)]
+pub struct Challenge<'a> {
+    pub id: Uuid,
+    pub name: Cow<'a, str>,
+    pub category: Cow<'a, str>,
+    pub points: i64,
+    pub min_points: Option<i64>,
+    pub decay: Option<u32>,
+    pub first_blood: i64,
+    #[serde(skip_serializing)]
+    pub flag: Option<String>,
+}
+
 impl<'a, T: Player<'a>, U: EventState> Event<'a, T, U> {
     pub fn new(name: Cow<'a, str>, logo: Option<Cow<'a, str>>, team_size: Option<u32>) -> Self {
         Self {
@@ -115,10 +221,15 @@ impl<'a, T: Player<'a>, U: EventState> Event<'a, T, U> {
             name,
             logo,
             team_size,
+            config: EventConfig::default(),
             player_marker: PhantomData::<&'a T>,
             state_marker: PhantomData::<&'a U>,
         }
     }
+    pub fn with_config(mut self, config: EventConfig) -> Self {
+        self.config = config;
+        self
+    }
     pub fn delete_event(id: &'a Uuid, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()> {
         let id = id.to_string();
         Box::pin(async move {
@@ -1,19 +1,23 @@
-use std::borrow::Cow;
-
+use chrono::{DateTime, Utc};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
-use super::command_models::MemberInfo;
+use super::command_models::{
+    ClockReq, CriteriaMarks, MatchResult, MemberInfo, RevealMode, RevertTarget, ScoreLog,
+    ScoreMode, ScoreUpdate,
+};
 use super::error_models::VaderError;
-use super::query_models::{EventQuery, EventQueryBuilder, EventQueryState};
+use super::query_models::{EventQuery, EventQueryBuilder, EventQueryState, VboardSource};
+use super::schedule_models::{ScheduleEvent, Transition};
 use super::v_models::{
-    ActiveEvent, AsyncDbRes, EndEvent, Event, NewEvent, Player, Team, User, VaderEvent,
+    ActiveEvent, AsyncDbRes, Challenge, EndEvent, Event, EventClock, EventConfig, NewEvent,
+    PausedEvent, Player, Team, User, VaderEvent,
 };
-use crate::services::event_services::VaderBoard;
 
 pub enum EventStateWrapper<'a, T: Player<'a>> {
     New(Event<'a, T, NewEvent>),
     Active(Event<'a, T, ActiveEvent>),
+    Paused(Event<'a, T, PausedEvent>),
     End(Event<'a, T, EndEvent>),
 }
 
@@ -21,83 +25,473 @@ impl<'a, T> EventStateWrapper<'a, T>
 where
     T: Player<'a>,
 {
-    fn start_event(&mut self) -> Result<(), VaderError> {
-        match self {
-            Self::New(event) => {
-                *self = Self::Active(event.start_event());
-                Ok(())
+    fn start_event<'b>(
+        &'b mut self,
+        db_pool: &'b SqlitePool,
+    ) -> AsyncDbRes<'b, Option<EventClock>> {
+        Box::pin(async move {
+            match self {
+                Self::New(event) => {
+                    let clock = event.save_start(db_pool).await?;
+                    *self = Self::Active(event.start_event());
+                    Ok(clock)
+                }
+                Self::Active(_) | Self::Paused(_) => {
+                    Err(VaderError::EventActive("Event already Started"))
+                }
+                Self::End(_) => Err(VaderError::EventEnded("Event already Ended")),
             }
-            Self::Active(_) => Err(VaderError::EventActive("Event already Started")),
-            Self::End(_) => Err(VaderError::EventEnded("Event already Ended")),
-        }
+        })
     }
-    fn end_event(&mut self) -> Result<(), VaderError> {
-        match self {
-            Self::Active(event) => {
-                *self = Self::End(event.end_event());
-                Ok(())
+    fn end_event<'b>(&'b mut self, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, ()> {
+        Box::pin(async move {
+            match self {
+                Self::Active(event) => {
+                    let event = event.end_event();
+                    event.save_end(db_pool).await?;
+                    *self = Self::End(event);
+                    Ok(())
+                }
+                Self::Paused(event) => {
+                    let event = event.end_event();
+                    event.save_end(db_pool).await?;
+                    *self = Self::End(event);
+                    Ok(())
+                }
+                Self::New(_) => Err(VaderError::EventNotActive("Event didn't start")),
+                Self::End(_) => Err(VaderError::EventEnded("Event already Ended")),
             }
-            Self::New(_) => Err(VaderError::EventNotActive("Event didn't start")),
-            Self::End(_) => Err(VaderError::EventEnded("Event already Ended")),
-        }
+        })
+    }
+    // Returns the event clock, paused along with the event
+    fn pause_event<'b>(
+        &'b mut self,
+        db_pool: &'b SqlitePool,
+    ) -> AsyncDbRes<'b, Option<EventClock>> {
+        Box::pin(async move {
+            match self {
+                Self::Active(event) => {
+                    let event = event.pause_event();
+                    let clock = event.save_pause(db_pool).await?;
+                    *self = Self::Paused(event);
+                    Ok(clock)
+                }
+                Self::Paused(_) => Err(VaderError::EventPaused("Event already Paused")),
+                Self::New(_) => Err(VaderError::EventNotActive("Event didn't start")),
+                Self::End(_) => Err(VaderError::EventEnded("Event already Ended")),
+            }
+        })
+    }
+    fn resume_event<'b>(
+        &'b mut self,
+        db_pool: &'b SqlitePool,
+    ) -> AsyncDbRes<'b, Option<EventClock>> {
+        Box::pin(async move {
+            match self {
+                Self::Paused(event) => {
+                    let event = event.resume_event();
+                    let clock = event.save_resume(db_pool).await?;
+                    *self = Self::Active(event);
+                    Ok(clock)
+                }
+                Self::Active(_) => Err(VaderError::EventActive("Event is not Paused")),
+                Self::New(_) => Err(VaderError::EventNotActive("Event didn't start")),
+                Self::End(_) => Err(VaderError::EventEnded("Event already Ended")),
+            }
+        })
     }
     fn get_id(&self) -> Uuid {
         match self {
             Self::New(e) => e.id,
             Self::Active(e) => e.id,
+            Self::Paused(e) => e.id,
             Self::End(e) => e.id,
         }
     }
+    // New events have no vaderboard yet
+    fn vboard_config(&self) -> Option<EventConfig> {
+        match self {
+            Self::New(_) => None,
+            Self::Active(e) => Some(e.config.clone()),
+            Self::Paused(e) => Some(e.config.clone()),
+            Self::End(e) => Some(e.config.clone()),
+        }
+    }
+    fn next_transition(&self, clock: Option<EventClock>) -> Option<(Transition, DateTime<Utc>)> {
+        match (self, clock) {
+            (Self::New(e), _) => e.config.start_at.map(|at| (Transition::Start, at)),
+            // scheduled events stop when their clock runs out, at end_at or start + duration
+            (Self::Active(e), Some(EventClock::Running { ends_at }))
+                if e.config.end_at.is_some() || e.config.start_at.is_some() =>
+            {
+                Some((Transition::Stop, ends_at))
+            }
+            _ => None,
+        }
+    }
 }
 pub enum EventWrapper<'a> {
     TeamEvent(EventStateWrapper<'a, Team<'a>>),
     UserEvent(EventStateWrapper<'a, User<'a>>),
 }
 impl<'a> EventWrapper<'a> {
-    pub fn start_event(&mut self) -> Result<(), VaderError> {
+    // Returns the clock started with the event
+    pub fn start_event<'b>(
+        &'b mut self,
+        db_pool: &'b SqlitePool,
+    ) -> AsyncDbRes<'b, Option<EventClock>> {
         match self {
-            Self::TeamEvent(sw) => sw.start_event(),
-            Self::UserEvent(sw) => sw.start_event(),
+            Self::TeamEvent(sw) => sw.start_event(db_pool),
+            Self::UserEvent(sw) => sw.start_event(db_pool),
         }
     }
-    pub fn end_event(&mut self) -> Result<(), VaderError> {
+    // End event and record final placements for season boards
+    pub fn end_event<'b>(&'b mut self, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, ()> {
         match self {
-            Self::TeamEvent(sw) => sw.end_event(),
-            Self::UserEvent(sw) => sw.end_event(),
+            Self::TeamEvent(sw) => sw.end_event(db_pool),
+            Self::UserEvent(sw) => sw.end_event(db_pool),
         }
     }
 
+    pub fn is_ended(&self) -> bool {
+        matches!(
+            self,
+            Self::TeamEvent(EventStateWrapper::End(_)) | Self::UserEvent(EventStateWrapper::End(_))
+        )
+    }
+
+    pub fn pause_event<'b>(
+        &'b mut self,
+        db_pool: &'b SqlitePool,
+    ) -> AsyncDbRes<'b, Option<EventClock>> {
+        match self {
+            Self::TeamEvent(sw) => sw.pause_event(db_pool),
+            Self::UserEvent(sw) => sw.pause_event(db_pool),
+        }
+    }
+    pub fn resume_event<'b>(
+        &'b mut self,
+        db_pool: &'b SqlitePool,
+    ) -> AsyncDbRes<'b, Option<EventClock>> {
+        match self {
+            Self::TeamEvent(sw) => sw.resume_event(db_pool),
+            Self::UserEvent(sw) => sw.resume_event(db_pool),
+        }
+    }
+    pub fn is_paused(&self) -> bool {
+        matches!(
+            self,
+            Self::TeamEvent(EventStateWrapper::Paused(_))
+                | Self::UserEvent(EventStateWrapper::Paused(_))
+        )
+    }
+
     pub fn get_id(&self) -> Uuid {
         match self {
             Self::TeamEvent(sw) => sw.get_id(),
             Self::UserEvent(sw) => sw.get_id(),
         }
     }
+    pub fn vboard_source(&self) -> Result<VboardSource, VaderError<'static>> {
+        let (table, config) = match self {
+            Self::TeamEvent(sw) => ("teams", sw.vboard_config()),
+            Self::UserEvent(sw) => ("users", sw.vboard_config()),
+        };
+        match config {
+            Some(config) => Ok(VboardSource {
+                event_id: self.get_id(),
+                table,
+                config,
+            }),
+            None => Err(VaderError::EventNotActive(
+                "Event not Active to get Leaderboard",
+            )),
+        }
+    }
+    // Start at start_at while added, stop when the clock runs out while active
+    pub fn schedule(&self, clock: Option<EventClock>) -> ScheduleEvent {
+        let transition = match self {
+            Self::TeamEvent(sw) => sw.next_transition(clock),
+            Self::UserEvent(sw) => sw.next_transition(clock),
+        };
+        ScheduleEvent(self.get_id(), transition)
+    }
     pub fn update_score_by_id(
         &'a self,
         p_id: &'a Uuid,
         score: i64,
+        mode: ScoreMode,
+        log: &'a ScoreLog,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ()> {
+        match self {
+            Self::TeamEvent(sw) => {
+                match sw {
+                    EventStateWrapper::Active(e) => {
+                        e.update_score_by_id(p_id, score, mode, log, db_pool)
+                    }
+                    EventStateWrapper::Paused(_) => Box::pin(async move {
+                        Err(VaderError::EventPaused("Score updates are on hold"))
+                    }),
+                    _ => Box::pin(async move {
+                        Err(VaderError::EventNotActive(
+                            "Event is not active to Update Score",
+                        ))
+                    }),
+                }
+            }
+            Self::UserEvent(sw) => {
+                match sw {
+                    EventStateWrapper::Active(e) => {
+                        e.update_score_by_id(p_id, score, mode, log, db_pool)
+                    }
+                    EventStateWrapper::Paused(_) => Box::pin(async move {
+                        Err(VaderError::EventPaused("Score updates are on hold"))
+                    }),
+                    _ => Box::pin(async move {
+                        Err(VaderError::EventNotActive(
+                            "Event is not active to Update Score",
+                        ))
+                    }),
+                }
+            }
+        }
+    }
+    pub fn update_marks_by_id(
+        &'a self,
+        marks: &'a CriteriaMarks,
+        log: &'a ScoreLog,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ()> {
+        match self {
+            Self::TeamEvent(sw) => {
+                match sw {
+                    EventStateWrapper::Active(e) => e.update_marks_by_id(marks, log, db_pool),
+                    EventStateWrapper::Paused(_) => Box::pin(async move {
+                        Err(VaderError::EventPaused("Marks updates are on hold"))
+                    }),
+                    _ => Box::pin(async move {
+                        Err(VaderError::EventNotActive(
+                            "Event is not active to Update Marks",
+                        ))
+                    }),
+                }
+            }
+            Self::UserEvent(sw) => {
+                match sw {
+                    EventStateWrapper::Active(e) => e.update_marks_by_id(marks, log, db_pool),
+                    EventStateWrapper::Paused(_) => Box::pin(async move {
+                        Err(VaderError::EventPaused("Marks updates are on hold"))
+                    }),
+                    _ => Box::pin(async move {
+                        Err(VaderError::EventNotActive(
+                            "Event is not active to Update Marks",
+                        ))
+                    }),
+                }
+            }
+        }
+    }
+    pub fn update_scores_by_id(
+        &'a self,
+        updates: &'a [ScoreUpdate],
+        log: &'a ScoreLog,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ()> {
+        match self {
+            Self::TeamEvent(sw) => {
+                match sw {
+                    EventStateWrapper::Active(e) => e.update_scores_by_id(updates, log, db_pool),
+                    EventStateWrapper::Paused(_) => Box::pin(async move {
+                        Err(VaderError::EventPaused("Score updates are on hold"))
+                    }),
+                    _ => Box::pin(async move {
+                        Err(VaderError::EventNotActive(
+                            "Event is not active to Update Score",
+                        ))
+                    }),
+                }
+            }
+            Self::UserEvent(sw) => {
+                match sw {
+                    EventStateWrapper::Active(e) => e.update_scores_by_id(updates, log, db_pool),
+                    EventStateWrapper::Paused(_) => Box::pin(async move {
+                        Err(VaderError::EventPaused("Score updates are on hold"))
+                    }),
+                    _ => Box::pin(async move {
+                        Err(VaderError::EventNotActive(
+                            "Event is not active to Update Score",
+                        ))
+                    }),
+                }
+            }
+        }
+    }
+    pub fn add_challenge(
+        &'a self,
+        challenge: &'a Challenge<'a>,
         db_pool: &'a SqlitePool,
     ) -> AsyncDbRes<'a, ()> {
         match self {
             Self::TeamEvent(sw) => match sw {
-                EventStateWrapper::Active(e) => e.update_score_by_id(p_id, score, db_pool),
+                EventStateWrapper::New(e) => e.add_challenge(challenge, db_pool),
+                EventStateWrapper::Active(e) => e.add_challenge(challenge, db_pool),
+                EventStateWrapper::Paused(e) => e.add_challenge(challenge, db_pool),
+                EventStateWrapper::End(_) => Box::pin(async move {
+                    Err(VaderError::EventEnded(
+                        "Unable to add challenge , Event ended",
+                    ))
+                }),
+            },
+            Self::UserEvent(sw) => match sw {
+                EventStateWrapper::New(e) => e.add_challenge(challenge, db_pool),
+                EventStateWrapper::Active(e) => e.add_challenge(challenge, db_pool),
+                EventStateWrapper::Paused(e) => e.add_challenge(challenge, db_pool),
+                EventStateWrapper::End(_) => Box::pin(async move {
+                    Err(VaderError::EventEnded(
+                        "Unable to add challenge , Event ended",
+                    ))
+                }),
+            },
+        }
+    }
+    pub fn record_solve(
+        &'a self,
+        challenge_id: &'a Uuid,
+        p_id: &'a Uuid,
+        log: &'a ScoreLog,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ()> {
+        match self {
+            Self::TeamEvent(sw) => match sw {
+                EventStateWrapper::Active(e) => e.record_solve(challenge_id, p_id, log, db_pool),
+                EventStateWrapper::Paused(_) => {
+                    Box::pin(async move { Err(VaderError::EventPaused("Solves are on hold")) })
+                }
                 _ => Box::pin(async move {
                     Err(VaderError::EventNotActive(
-                        "Event is not active to Update Score",
+                        "Event is not active to record Solve",
                     ))
                 }),
             },
             Self::UserEvent(sw) => match sw {
-                EventStateWrapper::Active(e) => e.update_score_by_id(p_id, score, db_pool),
+                EventStateWrapper::Active(e) => e.record_solve(challenge_id, p_id, log, db_pool),
+                EventStateWrapper::Paused(_) => {
+                    Box::pin(async move { Err(VaderError::EventPaused("Solves are on hold")) })
+                }
                 _ => Box::pin(async move {
                     Err(VaderError::EventNotActive(
-                        "Event is not active to Update Score",
+                        "Event is not active to record Solve",
                     ))
                 }),
             },
         }
     }
+    pub fn record_match(
+        &'a self,
+        match_id: u32,
+        result: &'a MatchResult,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ()> {
+        match self {
+            Self::TeamEvent(sw) => {
+                match sw {
+                    EventStateWrapper::Active(e) => e.record_match(match_id, result, db_pool),
+                    EventStateWrapper::Paused(_) => Box::pin(async move {
+                        Err(VaderError::EventPaused("Match results are on hold"))
+                    }),
+                    _ => Box::pin(async move {
+                        Err(VaderError::EventNotActive(
+                            "Event is not active to record Match result",
+                        ))
+                    }),
+                }
+            }
+            Self::UserEvent(sw) => {
+                match sw {
+                    EventStateWrapper::Active(e) => e.record_match(match_id, result, db_pool),
+                    EventStateWrapper::Paused(_) => Box::pin(async move {
+                        Err(VaderError::EventPaused("Match results are on hold"))
+                    }),
+                    _ => Box::pin(async move {
+                        Err(VaderError::EventNotActive(
+                            "Event is not active to record Match result",
+                        ))
+                    }),
+                }
+            }
+        }
+    }
+    // Freeze can be revealed on a running or paused event, and on its final standings
+    pub fn reveal(
+        &'a self,
+        mode: RevealMode,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Option<Uuid>> {
+        match self {
+            Self::TeamEvent(EventStateWrapper::Active(e)) => e.reveal(mode, db_pool),
+            Self::TeamEvent(EventStateWrapper::Paused(e)) => e.reveal(mode, db_pool),
+            Self::TeamEvent(EventStateWrapper::End(e)) => e.reveal(mode, db_pool),
+            Self::UserEvent(EventStateWrapper::Active(e)) => e.reveal(mode, db_pool),
+            Self::UserEvent(EventStateWrapper::Paused(e)) => e.reveal(mode, db_pool),
+            Self::UserEvent(EventStateWrapper::End(e)) => e.reveal(mode, db_pool),
+            _ => Box::pin(async move {
+                Err(VaderError::EventNotActive(
+                    "Event is not started to reveal Vaderboard",
+                ))
+            }),
+        }
+    }
+    pub fn update_clock(
+        &'a self,
+        clock_req: &'a ClockReq,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, EventClock> {
+        match self {
+            Self::TeamEvent(EventStateWrapper::Active(e)) => e.update_clock(clock_req, db_pool),
+            Self::UserEvent(EventStateWrapper::Active(e)) => e.update_clock(clock_req, db_pool),
+            _ => Box::pin(async move {
+                Err(VaderError::EventNotActive(
+                    "Event is not active to update Clock",
+                ))
+            }),
+        }
+    }
+    pub fn revert_score(
+        &'a self,
+        revert: &'a RevertTarget,
+        log: &'a ScoreLog,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Vec<i64>> {
+        match self {
+            Self::TeamEvent(sw) => {
+                match sw {
+                    EventStateWrapper::Active(e) => e.revert_score(revert, log, db_pool),
+                    EventStateWrapper::Paused(_) => Box::pin(async move {
+                        Err(VaderError::EventPaused("Score reverts are on hold"))
+                    }),
+                    _ => Box::pin(async move {
+                        Err(VaderError::EventNotActive(
+                            "Event is not active to Revert Score",
+                        ))
+                    }),
+                }
+            }
+            Self::UserEvent(sw) => {
+                match sw {
+                    EventStateWrapper::Active(e) => e.revert_score(revert, log, db_pool),
+                    EventStateWrapper::Paused(_) => Box::pin(async move {
+                        Err(VaderError::EventPaused("Score reverts are on hold"))
+                    }),
+                    _ => Box::pin(async move {
+                        Err(VaderError::EventNotActive(
+                            "Event is not active to Revert Score",
+                        ))
+                    }),
+                }
+            }
+        }
+    }
     pub fn reset_score(&'a self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()> {
         match self {
             Self::TeamEvent(sw) => match sw {
@@ -126,7 +520,7 @@ impl<'a> EventWrapper<'a> {
             Self::TeamEvent(sw) => match sw {
                 EventStateWrapper::New(e) => Box::pin(async move {
                     let _ = team.add_player(db_pool).await;
-                    e.add_participant_from_id(team.id, db_pool).await
+                    e.add_participant(&team, db_pool).await
                 }),
                 _ => Box::pin(async move {
                     Err(VaderError::EventActive(
@@ -142,6 +536,23 @@ impl<'a> EventWrapper<'a> {
         }
     }
 
+    // Enroll a team / user already added in another event
+    pub fn add_participant_by_id(
+        &'a self,
+        p_id: Uuid,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ()> {
+        match self {
+            Self::TeamEvent(EventStateWrapper::New(e)) => e.add_participant_from_id(p_id, db_pool),
+            Self::UserEvent(EventStateWrapper::New(e)) => e.add_participant_from_id(p_id, db_pool),
+            _ => Box::pin(async move {
+                Err(VaderError::EventActive(
+                    "Participant cannot be added as Event already started",
+                ))
+            }),
+        }
+    }
+
     pub fn add_team_with_members(
         &'a self,
         team: &'a Team<'a>,
@@ -170,7 +581,11 @@ impl<'a> EventWrapper<'a> {
         }
     }
 
-    pub fn add_team_members(&self, mi: &'a MemberInfo, db_pool: &'a SqlitePool) -> AsyncDbRes<()> {
+    pub fn add_team_members(
+        &self,
+        mi: &'a MemberInfo,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'_, ()> {
         match self {
             Self::TeamEvent(sw) => match sw {
                 EventStateWrapper::New(e) => Box::pin(async move {
@@ -190,7 +605,7 @@ impl<'a> EventWrapper<'a> {
             }),
         }
     }
-    pub fn add_user(&self, user: &'a User, db_pool: &'a SqlitePool) -> AsyncDbRes<()> {
+    pub fn add_user(&self, user: &'a User, db_pool: &'a SqlitePool) -> AsyncDbRes<'_, ()> {
         match self {
             Self::TeamEvent(sw) => match sw {
                 EventStateWrapper::New(_) => user.add_player(db_pool),
@@ -203,7 +618,7 @@ impl<'a> EventWrapper<'a> {
             Self::UserEvent(sw) => match sw {
                 EventStateWrapper::New(e) => Box::pin(async move {
                     let _ = user.add_player(db_pool).await;
-                    e.add_participant_from_id(user.id, db_pool).await
+                    e.add_participant(user, db_pool).await
                 }),
                 _ => Box::pin(async move {
                     Err(VaderError::EventActive(
@@ -222,6 +637,9 @@ impl<'a> EventWrapper<'a> {
                 EventStateWrapper::Active(e) => {
                     EventQueryBuilder::from(e).build_with_state(EventQueryState::Start)
                 }
+                EventStateWrapper::Paused(e) => {
+                    EventQueryBuilder::from(e).build_with_state(EventQueryState::Paused)
+                }
                 EventStateWrapper::End(e) => {
                     EventQueryBuilder::from(e).build_with_state(EventQueryState::Stop)
                 }
@@ -233,53 +651,48 @@ impl<'a> EventWrapper<'a> {
                 EventStateWrapper::Active(e) => {
                     EventQueryBuilder::from(e).build_with_state(EventQueryState::Start)
                 }
+                EventStateWrapper::Paused(e) => {
+                    EventQueryBuilder::from(e).build_with_state(EventQueryState::Paused)
+                }
                 EventStateWrapper::End(e) => {
                     EventQueryBuilder::from(e).build_with_state(EventQueryState::Stop)
                 }
             },
         }
     }
+}
 
-    pub fn get_vboard(
-        &'a self,
-        db_pool: &'a SqlitePool,
-        count: u32,
-    ) -> AsyncDbRes<'a, Cow<'static, str>> {
-        match self {
-            Self::TeamEvent(sw) => match sw {
-                EventStateWrapper::Active(e) => Box::pin(async move {
-                    let res = e.get_vboard(count, db_pool).await?;
-                    let team_str = serde_json::to_string(&res)?;
-                    Ok(team_str.into())
-                }),
-                EventStateWrapper::End(e) => Box::pin(async move {
-                    let res = e.get_vboard(count, db_pool).await?;
-                    let team_str = serde_json::to_string(&res)?;
-                    Ok(team_str.into())
-                }),
-                EventStateWrapper::New(_) => Box::pin(async move {
-                    Err(VaderError::EventNotActive(
-                        "Event not Active to get Leaderboard",
-                    ))
-                }),
-            },
-            Self::UserEvent(sw) => match sw {
-                EventStateWrapper::Active(e) => Box::pin(async move {
-                    let res = e.get_vboard(count, db_pool).await?;
-                    let users_str = serde_json::to_string(&res)?;
-                    Ok(users_str.into())
-                }),
-                EventStateWrapper::End(e) => Box::pin(async move {
-                    let res = e.get_vboard(count, db_pool).await?;
-                    let users_str = serde_json::to_string(&res)?;
-                    Ok(users_str.into())
-                }),
-                EventStateWrapper::New(_) => Box::pin(async move {
-                    Err(VaderError::EventNotActive(
-                        "Event not Active to get Leaderboard",
-                    ))
-                }),
-            },
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_services::{test_pool, user_event};
+
+    #[tokio::test]
+    async fn paused_event_rejects_scores() {
+        let db_pool = test_pool().await;
+        let (event, ids) = user_event(EventConfig::default(), 1, &db_pool).await;
+        let event_id = event.id;
+        let log = ScoreLog {
+            admin: Some(String::from("admin")),
+            reason: None,
+        };
+        let mut wrapper = EventWrapper::UserEvent(EventStateWrapper::New(event));
+        wrapper.start_event(&db_pool).await.unwrap();
+        wrapper.pause_event(&db_pool).await.unwrap();
+
+        let res = wrapper
+            .update_score_by_id(&ids[0], 10, ScoreMode::Add, &log, &db_pool)
+            .await;
+        assert!(matches!(res, Err(VaderError::EventPaused(_))));
+        let (score, changes): (i64, i64) = sqlx::query_as(
+            "SELECT score,(SELECT COUNT(*) FROM score_ledger WHERE event_id = es.event_id)
+             FROM event_scores es WHERE event_id = ? AND participant_id = ?",
+        )
+        .bind(event_id.to_string())
+        .bind(ids[0].to_string())
+        .fetch_one(&db_pool)
+        .await
+        .unwrap();
+        assert_eq!((score, changes), (0, 0));
     }
 }
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use actix::{Actor, ActorContext, AsyncContext};
+use actix_web::web;
+use actix_web_actors::ws;
+
+// Clients are pinged every WS_HEARTBEAT and dropped after WS_TIMEOUT without any frame
+pub const WS_HEARTBEAT: Duration = Duration::from_secs(5);
+pub const WS_TIMEOUT: Duration = Duration::from_secs(15);
+
+// Limits on open websocket connections, total and per client ip
+pub struct WsLimits {
+    pub max_conns: usize,
+    pub max_conns_per_ip: usize,
+    pub conns: Mutex<WsConns>,
+}
+
+#[derive(Default)]
+pub struct WsConns {
+    pub total: usize,
+    pub per_ip: HashMap<IpAddr, usize>,
+}
+
+// Slot taken in WsLimits, given back on drop
+pub struct WsPermit {
+    pub limits: web::Data<WsLimits>,
+    pub ip: IpAddr,
+}
+
+// Liveness of a websocket client, owned by its actor
+pub struct WsConn {
+    pub last_seen: Instant,
+    pub permit: WsPermit,
+}
+
+pub trait WsHeartbeat: Actor<Context = ws::WebsocketContext<Self>> {
+    fn conn(&mut self) -> &mut WsConn;
+
+    fn start_heartbeat(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(WS_HEARTBEAT, |act, ctx| {
+            if act.conn().last_seen.elapsed() > WS_TIMEOUT {
+                log::debug!("Websocket client timed out [ip : {}]", act.conn().permit.ip);
+                ctx.stop();
+                return;
+            }
+            ctx.ping(b"");
+        });
+    }
+
+    // Any frame from the client counts as a heartbeat, returns false when the client is gone
+    fn beat(
+        &mut self,
+        msg: &Result<ws::Message, ws::ProtocolError>,
+        ctx: &mut Self::Context,
+    ) -> bool {
+        match msg {
+            Ok(ws::Message::Close(reason)) => {
+                ctx.close(reason.clone());
+                ctx.stop();
+                false
+            }
+            Ok(_) => {
+                self.conn().last_seen = Instant::now();
+                true
+            }
+            Err(_) => {
+                ctx.stop();
+                false
+            }
+        }
+    }
+}
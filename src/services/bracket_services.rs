@@ -0,0 +1,749 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use actix::{AsyncContext, ContextFutureSpawner, Handler, StreamHandler, WrapFuture};
+use actix_web_actors::ws;
+use chrono::Utc;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, Row, SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::bracket_models::{
+    BrConnect, BrDisconnect, Bracket, BracketClient, BracketFormat, BracketGet, BracketRes,
+    BracketSrv, BracketState, Match, MatchInfo, Slot, SlotInfo, Stage, Standing,
+};
+use crate::models::command_models::MatchResult;
+use crate::models::error_models::VaderError;
+use crate::models::query_models::TransferType;
+use crate::models::v_models::AsyncDbRes;
+use crate::models::ws_models::{WsConn, WsHeartbeat};
+
+impl BracketFormat {
+    // value stored in events.bracket column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SingleElimination => "single_elimination",
+            Self::DoubleElimination => "double_elimination",
+            Self::RoundRobin => "round_robin",
+        }
+    }
+    pub fn from_column(value: &str) -> Option<Self> {
+        match value {
+            "single_elimination" => Some(Self::SingleElimination),
+            "double_elimination" => Some(Self::DoubleElimination),
+            "round_robin" => Some(Self::RoundRobin),
+            _ => None,
+        }
+    }
+}
+
+impl Stage {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Winners => "winners",
+            Self::Losers => "losers",
+            Self::GrandFinal => "grand_final",
+            Self::RoundRobin => "round_robin",
+        }
+    }
+    fn from_column(value: &str) -> Option<Self> {
+        match value {
+            "winners" => Some(Self::Winners),
+            "losers" => Some(Self::Losers),
+            "grand_final" => Some(Self::GrandFinal),
+            "round_robin" => Some(Self::RoundRobin),
+            _ => None,
+        }
+    }
+}
+
+impl Match {
+    fn new(id: u32, stage: Stage, round: u32, position: u32) -> Self {
+        Match {
+            id,
+            stage,
+            round,
+            position,
+            slots: [Slot::Pending, Slot::Pending],
+            scores: [None, None],
+            winner: None,
+            done: false,
+            next: None,
+            loser_next: None,
+            completed_at: None,
+        }
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Match {
+    fn from_row(row: &'_ SqliteRow) -> Result<Self, sqlx::Error> {
+        let decode_err = |field: &'static str| sqlx::Error::ColumnDecode {
+            index: "0".to_string(),
+            source: Box::new(VaderError::SqlxFieldError(field)),
+        };
+        let parse_id = |id: Option<String>| match id {
+            Some(id) => Uuid::parse_str(&id)
+                .map(Some)
+                .map_err(|_e| decode_err("Error decoding Participant Id")),
+            None => Ok(None),
+        };
+        let slot = |participant: Option<Uuid>, bye: bool| match (participant, bye) {
+            (Some(id), _) => Slot::Filled(id),
+            (None, true) => Slot::Bye,
+            (None, false) => Slot::Pending,
+        };
+        let stage: String = row.get("stage");
+        let stage = Stage::from_column(&stage).ok_or_else(|| decode_err("Error decoding Stage"))?;
+        let link = |m: Option<u32>, s: Option<u32>| m.zip(s).map(|(m, s)| (m, s as usize));
+        Ok(Match {
+            id: row.get("id"),
+            stage,
+            round: row.get("round"),
+            position: row.get("position"),
+            slots: [
+                slot(parse_id(row.get("participant_a"))?, row.get("bye_a")),
+                slot(parse_id(row.get("participant_b"))?, row.get("bye_b")),
+            ],
+            scores: [row.get("score_a"), row.get("score_b")],
+            winner: parse_id(row.get("winner"))?,
+            done: row.get("done"),
+            next: link(row.get("next_match"), row.get("next_slot")),
+            loser_next: link(row.get("loser_match"), row.get("loser_slot")),
+            completed_at: row.get("completed_at"),
+        })
+    }
+}
+
+// Seed positions of a bracket of given size, 1st seed meets the last one
+fn seed_order(size: usize) -> Vec<usize> {
+    let mut order = vec![0];
+    while order.len() < size {
+        let n = order.len() * 2;
+        order = order.iter().flat_map(|&s| [s, n - 1 - s]).collect();
+    }
+    order
+}
+
+impl Bracket {
+    // Generate matches for participants in seed order
+    pub fn generate(
+        event_id: Uuid,
+        format: BracketFormat,
+        seeds: &[Uuid],
+    ) -> Result<Self, VaderError<'static>> {
+        if seeds.len() < 2 {
+            return Err(VaderError::BracketError(
+                "At least 2 participants needed for bracket",
+            ));
+        }
+        let mut bracket = Bracket {
+            event_id,
+            format,
+            matches: Vec::new(),
+        };
+        match format {
+            BracketFormat::SingleElimination => {
+                bracket.winners_bracket(seeds);
+            }
+            BracketFormat::DoubleElimination => {
+                let winners = bracket.winners_bracket(seeds);
+                bracket.losers_bracket(&winners);
+            }
+            BracketFormat::RoundRobin => bracket.round_robin(seeds),
+        }
+        bracket.resolve();
+        Ok(bracket)
+    }
+
+    fn add_match(&mut self, stage: Stage, round: u32, position: u32) -> u32 {
+        let id = self.matches.len() as u32;
+        self.matches.push(Match::new(id, stage, round, position));
+        id
+    }
+
+    // Returns match ids of each winners round
+    fn winners_bracket(&mut self, seeds: &[Uuid]) -> Vec<Vec<u32>> {
+        let size = seeds.len().next_power_of_two();
+        let order = seed_order(size);
+        let mut rounds: Vec<Vec<u32>> = Vec::new();
+        let mut count = size / 2;
+        let mut round = 1;
+        while count > 0 {
+            let ids: Vec<u32> = (0..count)
+                .map(|p| self.add_match(Stage::Winners, round, p as u32))
+                .collect();
+            if let Some(prev) = rounds.last() {
+                for (p, prev_id) in prev.iter().enumerate() {
+                    self.matches[*prev_id as usize].next = Some((ids[p / 2], p % 2));
+                }
+            } else {
+                for (p, id) in ids.iter().enumerate() {
+                    for slot in 0..2 {
+                        self.matches[*id as usize].slots[slot] =
+                            match seeds.get(order[2 * p + slot]) {
+                                Some(seed) => Slot::Filled(*seed),
+                                None => Slot::Bye,
+                            };
+                    }
+                }
+            }
+            rounds.push(ids);
+            count /= 2;
+            round += 1;
+        }
+        rounds
+    }
+
+    // Losers bracket fed by losers of winners rounds, ending in a grand final
+    // and its reset, played when the losers bracket champion wins the first final
+    fn losers_bracket(&mut self, winners: &[Vec<u32>]) {
+        let k = winners.len();
+        let mut prev: Vec<u32> = Vec::new();
+        for round in 1..=2 * (k - 1) {
+            let count = if round == 1 {
+                winners[0].len() / 2
+            } else if round % 2 == 0 {
+                prev.len()
+            } else {
+                prev.len() / 2
+            };
+            let ids: Vec<u32> = (0..count)
+                .map(|p| self.add_match(Stage::Losers, round as u32, p as u32))
+                .collect();
+            for (p, id) in ids.iter().enumerate() {
+                if round == 1 {
+                    self.matches[winners[0][2 * p] as usize].loser_next = Some((*id, 0));
+                    self.matches[winners[0][2 * p + 1] as usize].loser_next = Some((*id, 1));
+                } else if round % 2 == 0 {
+                    // losers dropping down meet the other half to avoid early rematches
+                    let dropped = &winners[round / 2];
+                    self.matches[prev[p] as usize].next = Some((*id, 0));
+                    self.matches[dropped[count - 1 - p] as usize].loser_next = Some((*id, 1));
+                } else {
+                    self.matches[prev[2 * p] as usize].next = Some((*id, 0));
+                    self.matches[prev[2 * p + 1] as usize].next = Some((*id, 1));
+                }
+            }
+            prev = ids;
+        }
+        let final_id = self.add_match(Stage::GrandFinal, 1, 0);
+        let reset_id = self.add_match(Stage::GrandFinal, 2, 0);
+        self.matches[final_id as usize].next = Some((reset_id, 0));
+        self.matches[final_id as usize].loser_next = Some((reset_id, 1));
+        let winners_final = winners[k - 1][0] as usize;
+        self.matches[winners_final].next = Some((final_id, 0));
+        match prev.first() {
+            Some(losers_final) => self.matches[*losers_final as usize].next = Some((final_id, 1)),
+            None => self.matches[winners_final].loser_next = Some((final_id, 1)),
+        }
+    }
+
+    // Circle method, participant with a bye sits out the round
+    fn round_robin(&mut self, seeds: &[Uuid]) {
+        let mut circle: Vec<Option<Uuid>> = seeds.iter().map(|s| Some(*s)).collect();
+        if circle.len() % 2 == 1 {
+            circle.push(None);
+        }
+        let n = circle.len();
+        for round in 1..n {
+            let mut position = 0;
+            for i in 0..n / 2 {
+                if let (Some(a), Some(b)) = (circle[i], circle[n - 1 - i]) {
+                    let id = self.add_match(Stage::RoundRobin, round as u32, position);
+                    self.matches[id as usize].slots = [Slot::Filled(a), Slot::Filled(b)];
+                    position += 1;
+                }
+            }
+            circle[1..].rotate_right(1);
+        }
+    }
+
+    fn advance(&mut self, idx: usize, winner: Slot, loser: Slot) {
+        if let Some((m, s)) = self.matches[idx].next {
+            self.matches[m as usize].slots[s] = winner;
+        }
+        if let Some((m, s)) = self.matches[idx].loser_next {
+            self.matches[m as usize].slots[s] = loser;
+        }
+    }
+
+    // Complete matches decided by byes until nothing changes
+    fn resolve(&mut self) {
+        loop {
+            let mut changed = false;
+            for idx in 0..self.matches.len() {
+                let m = &self.matches[idx];
+                if m.done || m.stage == Stage::RoundRobin {
+                    continue;
+                }
+                let winner = match m.slots {
+                    [Slot::Filled(id), Slot::Bye] | [Slot::Bye, Slot::Filled(id)] => {
+                        Slot::Filled(id)
+                    }
+                    [Slot::Bye, Slot::Bye] => Slot::Bye,
+                    _ => continue,
+                };
+                let m = &mut self.matches[idx];
+                m.done = true;
+                if let Slot::Filled(id) = winner {
+                    m.winner = Some(id);
+                }
+                self.advance(idx, winner, Slot::Bye);
+                changed = true;
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    pub fn record_result(
+        &mut self,
+        match_id: u32,
+        result: &MatchResult,
+    ) -> Result<(), VaderError<'static>> {
+        let idx = match_id as usize;
+        let m = match self.matches.get_mut(idx) {
+            Some(m) => m,
+            None => return Err(VaderError::BracketError("No match found in Event")),
+        };
+        if m.done {
+            return Err(VaderError::BracketError("Match already completed"));
+        }
+        let (a, b) = match m.slots {
+            [Slot::Filled(a), Slot::Filled(b)] => (a, b),
+            _ => {
+                return Err(VaderError::BracketError(
+                    "Match participants are not decided yet",
+                ))
+            }
+        };
+        let loser = match result.winner {
+            Some(winner) if winner == a => b,
+            Some(winner) if winner == b => a,
+            Some(_) => {
+                return Err(VaderError::BracketError(
+                    "Winner is not a participant of the match",
+                ))
+            }
+            None if m.stage == Stage::RoundRobin => a,
+            None => {
+                return Err(VaderError::BracketError(
+                    "Draw is only allowed in round robin",
+                ))
+            }
+        };
+        // winners bracket champion is undefeated, winning the first grand final skips the reset
+        let no_reset = m.stage == Stage::GrandFinal && m.round == 1 && result.winner == Some(a);
+        m.scores = [result.score_a, result.score_b];
+        m.winner = result.winner;
+        m.done = true;
+        m.completed_at = Some(Utc::now());
+        if let Some(winner) = result.winner {
+            let loser = if no_reset {
+                Slot::Bye
+            } else {
+                Slot::Filled(loser)
+            };
+            self.advance(idx, Slot::Filled(winner), loser);
+        }
+        self.resolve();
+        Ok(())
+    }
+
+    // Create bracket of event from its participants in joining order
+    // Seeded in order of joining the event, saved along with the event start
+    pub async fn create(
+        event_id: Uuid,
+        format: BracketFormat,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), VaderError<'static>> {
+        let seeds = sqlx::query_scalar::<_, String>(
+            "SELECT participant_id FROM event_scores WHERE event_id = ? ORDER BY rowid",
+        )
+        .bind(event_id.to_string())
+        .fetch_all(&mut *conn)
+        .await?
+        .iter()
+        .map(|id| Uuid::parse_str(id))
+        .collect::<Result<Vec<Uuid>, _>>()
+        .map_err(|_e| VaderError::SqlxFieldError("Error decoding Participant Id"))?;
+        let bracket = Self::generate(event_id, format, &seeds)?;
+        bracket.save(conn).await
+    }
+
+    pub async fn load(
+        event_id: Uuid,
+        conn: &mut SqliteConnection,
+    ) -> Result<Self, VaderError<'static>> {
+        let id = event_id.to_string();
+        let format =
+            sqlx::query_scalar::<_, Option<String>>("SELECT bracket FROM events WHERE id = ?")
+                .bind(&id)
+                .fetch_optional(&mut *conn)
+                .await?;
+        let format = match format {
+            Some(Some(format)) => BracketFormat::from_column(&format)
+                .ok_or(VaderError::SqlxFieldError("Error decoding Bracket Format"))?,
+            Some(None) => return Err(VaderError::BracketError("Event has no bracket")),
+            None => return Err(VaderError::EventNotFound("No event found")),
+        };
+        let matches = sqlx::query_as::<_, Match>(
+            "SELECT id,stage,round,position,participant_a,participant_b,bye_a,bye_b,
+             score_a,score_b,winner,done,next_match,next_slot,loser_match,loser_slot,completed_at
+             FROM matches WHERE event_id = ? ORDER BY id",
+        )
+        .bind(&id)
+        .fetch_all(&mut *conn)
+        .await?;
+        Ok(Bracket {
+            event_id,
+            format,
+            matches,
+        })
+    }
+
+    async fn save(&self, conn: &mut SqliteConnection) -> Result<(), VaderError<'static>> {
+        let event_id = self.event_id.to_string();
+        sqlx::query!("DELETE FROM matches WHERE event_id = ?", event_id)
+            .execute(&mut *conn)
+            .await?;
+        for m in self.matches.iter() {
+            let slot = |s: usize| match m.slots[s] {
+                Slot::Filled(id) => (Some(id.to_string()), false),
+                Slot::Bye => (None, true),
+                Slot::Pending => (None, false),
+            };
+            let (participant_a, bye_a) = slot(0);
+            let (participant_b, bye_b) = slot(1);
+            let stage = m.stage.as_str();
+            let winner = m.winner.map(|w| w.to_string());
+            let (next_match, next_slot) = m.next.map(|(m, s)| (m, s as u32)).unzip();
+            let (loser_match, loser_slot) = m.loser_next.map(|(m, s)| (m, s as u32)).unzip();
+            sqlx::query!(
+                "INSERT INTO matches (event_id,id,stage,round,position,participant_a,participant_b,bye_a,bye_b,
+                 score_a,score_b,winner,done,next_match,next_slot,loser_match,loser_slot,completed_at)
+                 VALUES (?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?)",
+                event_id,
+                m.id,
+                stage,
+                m.round,
+                m.position,
+                participant_a,
+                participant_b,
+                bye_a,
+                bye_b,
+                m.scores[0],
+                m.scores[1],
+                winner,
+                m.done,
+                next_match,
+                next_slot,
+                loser_match,
+                loser_slot,
+                m.completed_at
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub fn record_match<'a>(
+        event_id: Uuid,
+        match_id: u32,
+        result: &'a MatchResult,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ()> {
+        Box::pin(async move {
+            let mut transaction = db_pool.begin().await?;
+            let mut bracket = Self::load(event_id, &mut transaction).await?;
+            bracket.record_result(match_id, result)?;
+            bracket.save(&mut transaction).await?;
+            transaction.commit().await?;
+            Ok(())
+        })
+    }
+
+    // Every participant has a row, zero until their first match is played
+    fn standings<'a>(&self, names: &HashMap<Uuid, String>) -> Vec<Standing<'a>> {
+        let mut table: HashMap<Uuid, Standing> = HashMap::new();
+        for m in self.matches.iter() {
+            for slot in m.slots {
+                if let Slot::Filled(id) = slot {
+                    table.entry(id).or_insert_with(|| Standing {
+                        rank: 0,
+                        id,
+                        name: names.get(&id).cloned().unwrap_or_default().into(),
+                        played: 0,
+                        wins: 0,
+                        draws: 0,
+                        losses: 0,
+                        points: 0,
+                        score_for: 0,
+                        score_against: 0,
+                    });
+                }
+            }
+        }
+        for m in self.matches.iter().filter(|m| m.done) {
+            if let [Slot::Filled(a), Slot::Filled(b)] = m.slots {
+                let score = [m.scores[0].unwrap_or(0), m.scores[1].unwrap_or(0)];
+                for (i, id) in [a, b].into_iter().enumerate() {
+                    let Some(row) = table.get_mut(&id) else {
+                        continue;
+                    };
+                    row.played += 1;
+                    row.score_for += score[i];
+                    row.score_against += score[1 - i];
+                    match m.winner {
+                        Some(winner) if winner == id => {
+                            row.wins += 1;
+                            row.points += 3;
+                        }
+                        Some(_) => row.losses += 1,
+                        None => {
+                            row.draws += 1;
+                            row.points += 1;
+                        }
+                    }
+                }
+            }
+        }
+        let mut standings: Vec<Standing> = table.into_values().collect();
+        standings.sort_by(|x, y| {
+            y.points
+                .cmp(&x.points)
+                .then((y.score_for - y.score_against).cmp(&(x.score_for - x.score_against)))
+                .then(y.score_for.cmp(&x.score_for))
+                .then(x.name.cmp(&y.name))
+        });
+        standings
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, row)| row.rank = i as u32 + 1);
+        standings
+    }
+
+    fn into_state<'a>(self, names: &HashMap<Uuid, String>) -> BracketState<'a> {
+        let standings = (self.format == BracketFormat::RoundRobin).then(|| self.standings(names));
+        let slot_info = |slot: Slot| match slot {
+            Slot::Pending => SlotInfo::Pending,
+            Slot::Bye => SlotInfo::Bye,
+            Slot::Filled(id) => SlotInfo::Participant {
+                id,
+                name: Cow::Owned(names.get(&id).cloned().unwrap_or_default()),
+            },
+        };
+        let matches = self
+            .matches
+            .into_iter()
+            .map(|m| MatchInfo {
+                id: m.id,
+                stage: m.stage,
+                round: m.round,
+                position: m.position,
+                a: slot_info(m.slots[0]),
+                b: slot_info(m.slots[1]),
+                score_a: m.scores[0],
+                score_b: m.scores[1],
+                winner: m.winner,
+                done: m.done,
+                completed_at: m.completed_at,
+            })
+            .collect();
+        BracketState {
+            event_id: self.event_id,
+            format: self.format,
+            matches,
+            standings,
+        }
+    }
+}
+
+impl BracketState<'_> {
+    pub fn get_bracket_state(event_id: Uuid, db_pool: &SqlitePool) -> AsyncDbRes<'_, Self> {
+        Box::pin(async move {
+            let mut conn = db_pool.acquire().await?;
+            let bracket = Bracket::load(event_id, &mut conn).await?;
+            let names = sqlx::query_as::<_, (String, String)>(
+                "SELECT es.participant_id,COALESCE(t.name,u.name,'') FROM event_scores es
+                 LEFT JOIN teams t ON t.id = es.participant_id
+                 LEFT JOIN users u ON u.id = es.participant_id
+                 WHERE es.event_id = ?",
+            )
+            .bind(event_id.to_string())
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .filter_map(|(id, name)| Uuid::parse_str(&id).ok().map(|id| (id, name)))
+            .collect::<HashMap<Uuid, String>>();
+            Ok(bracket.into_state(&names))
+        })
+    }
+}
+
+impl WsHeartbeat for BracketClient {
+    fn conn(&mut self) -> &mut WsConn {
+        &mut self.conn
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for BracketClient {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        if !self.beat(&msg, ctx) {
+            return;
+        }
+        use ws::Message::*;
+        match msg {
+            Ok(Ping(msg)) => ctx.pong(&msg),
+            Ok(Text(_)) => self.srv_addr.do_send(BracketGet(
+                self.event_id,
+                TransferType::Unicast(self.addr.clone().unwrap()),
+            )),
+            _ => {}
+        }
+    }
+}
+
+impl Handler<BracketRes<'_>> for BracketClient {
+    type Result = ();
+    fn handle(&mut self, msg: BracketRes, ctx: &mut Self::Context) -> Self::Result {
+        ctx.text(msg.0.as_ref());
+    }
+}
+
+impl Handler<BrConnect> for BracketSrv {
+    type Result = ();
+    fn handle(&mut self, msg: BrConnect, ctx: &mut Self::Context) -> Self::Result {
+        self.br_addr.entry(msg.0).or_default().insert(msg.1.clone());
+        log::debug!(
+            "New bracket client of event [{}].Total connection count : {}",
+            msg.0,
+            self.br_addr[&msg.0].len()
+        );
+        ctx.address()
+            .do_send(BracketGet(msg.0, TransferType::Unicast(msg.1)));
+    }
+}
+impl Handler<BrDisconnect> for BracketSrv {
+    type Result = ();
+    fn handle(&mut self, msg: BrDisconnect, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(clients) = self.br_addr.get_mut(&msg.0) {
+            clients.remove(&msg.1);
+            if clients.is_empty() {
+                self.br_addr.remove(&msg.0);
+            }
+        }
+    }
+}
+impl Handler<BracketRes<'static>> for BracketSrv {
+    type Result = ();
+    fn handle(&mut self, msg: BracketRes<'static>, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some((event_id, transfer)) = msg.1 {
+            match transfer {
+                TransferType::Unicast(addr) => addr.do_send(BracketRes(msg.0, None)),
+                TransferType::Broadcast => {
+                    if let Some(clients) = self.br_addr.get(&event_id) {
+                        clients
+                            .iter()
+                            .for_each(|addr| addr.do_send(BracketRes(msg.0.clone(), None)));
+                    }
+                }
+            }
+        }
+    }
+}
+impl Handler<BracketGet> for BracketSrv {
+    type Result = ();
+    fn handle(&mut self, msg: BracketGet, ctx: &mut Self::Context) -> Self::Result {
+        let addr = ctx.address();
+        let db_pool = self.db_pool.clone();
+        let event_id = msg.0;
+        async move {
+            let state = BracketState::get_bracket_state(event_id, &db_pool).await;
+            match state.and_then(|s| Ok(serde_json::to_string(&s)?)) {
+                Ok(state_str) => {
+                    addr.do_send(BracketRes(state_str.into(), Some((event_id, msg.1))))
+                }
+                Err(e) => log::debug!("Error sending Bracket : {}", e),
+            }
+        }
+        .into_actor(self)
+        .spawn(ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn win(bracket: &mut Bracket, match_id: u32, winner: Uuid) {
+        let result = MatchResult {
+            winner: Some(winner),
+            score_a: None,
+            score_b: None,
+        };
+        bracket.record_result(match_id, &result).unwrap();
+    }
+
+    fn grand_finals(bracket: &Bracket) -> (u32, u32) {
+        let round = |r| {
+            bracket
+                .matches
+                .iter()
+                .find(|m| m.stage == Stage::GrandFinal && m.round == r)
+                .unwrap()
+                .id
+        };
+        (round(1), round(2))
+    }
+
+    #[test]
+    fn grand_final_without_reset() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut bracket =
+            Bracket::generate(Uuid::new_v4(), BracketFormat::DoubleElimination, &[a, b]).unwrap();
+        let (first, reset) = grand_finals(&bracket);
+        win(&mut bracket, 0, a);
+        win(&mut bracket, first, a);
+        let reset = &bracket.matches[reset as usize];
+        assert!(reset.done);
+        assert_eq!(reset.winner, Some(a));
+    }
+
+    #[test]
+    fn grand_final_reset() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut bracket =
+            Bracket::generate(Uuid::new_v4(), BracketFormat::DoubleElimination, &[a, b]).unwrap();
+        let (first, reset) = grand_finals(&bracket);
+        win(&mut bracket, 0, a);
+        // losers bracket champion takes the first final, both play again
+        win(&mut bracket, first, b);
+        let m = &bracket.matches[reset as usize];
+        assert!(!m.done);
+        assert!(m.slots == [Slot::Filled(b), Slot::Filled(a)]);
+        win(&mut bracket, reset, b);
+        assert_eq!(bracket.matches[reset as usize].winner, Some(b));
+    }
+
+    #[test]
+    fn round_robin_standings_start_at_zero() {
+        let seeds: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        let mut bracket =
+            Bracket::generate(Uuid::new_v4(), BracketFormat::RoundRobin, &seeds).unwrap();
+        let standings = bracket.standings(&HashMap::new());
+        assert_eq!(standings.len(), 3);
+        assert!(standings.iter().all(|s| s.played == 0 && s.points == 0));
+        let m = &bracket.matches[0];
+        let [Slot::Filled(winner), _] = m.slots else {
+            panic!("round robin match without participants");
+        };
+        win(&mut bracket, 0, winner);
+        let standings = bracket.standings(&HashMap::new());
+        assert_eq!(standings.len(), 3);
+        assert_eq!(standings[0].id, winner);
+        assert_eq!(standings[0].points, 3);
+    }
+}
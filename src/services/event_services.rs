@@ -1,20 +1,30 @@
 use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::sync::Arc;
 
-use chrono::Utc;
-use sqlx::SqlitePool;
+use bcrypt::{hash, verify, DEFAULT_COST};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::{SqliteConnection, SqlitePool};
 use uuid::Uuid;
 
-use super::query_services::Queriable;
+use crate::models::bracket_models::Bracket;
+use crate::models::command_models::{
+    ChallengeReq, ClockReq, CriteriaMarks, MatchResult, RevealMode, RevertTarget, ScoreLog,
+    ScoreMode, ScoreUpdate,
+};
 use crate::models::error_models::VaderError;
-use crate::models::query_models::TeamInfo;
+use crate::models::query_models::{VboardEntry, VboardSource};
+use crate::models::season_models::Season;
 use crate::models::v_models::{
-    ActiveEvent, AsyncDbRes, EndEvent, Event, EventState, NewEvent, Player, Team, User, VaderEvent,
+    ActiveEvent, Aggregation, AsyncDbRes, Challenge, EndEvent, Event, EventClock, EventConfig,
+    EventState, NewEvent, PausedEvent, Player, RankingMode, Team, User, VaderEvent,
 };
 
 impl<'a> Player<'a> for User<'a> {
+    const TABLE: &'static str = "users";
     fn add_player(&'a self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()> {
         let id = self.id.to_string();
         let name = &self.name;
@@ -43,6 +53,7 @@ impl<'a> Player<'a> for User<'a> {
 }
 
 impl<'a> Player<'a> for Team<'a> {
+    const TABLE: &'static str = "teams";
     fn add_player(&'a self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()> {
         Box::pin(async move {
             let id = self.id.to_string();
@@ -182,20 +193,37 @@ impl<'a> VaderEvent<'a> for Event<'a, Team<'a>> {
         let created_at = Utc::now();
         Box::pin(async move {
             if let Some(team_size) = self.team_size {
+                let criteria = self.config.criteria_json()?;
+                let aggregation = self.config.aggregation.as_str();
+                let ranking = self.config.ranking.as_str();
+                let bracket = self.config.bracket.map(|b| b.as_str());
+                let duration = self.config.duration.map(|d| d as i64);
                 sqlx::query!(
-                    "INSERT INTO events (id,name,logo,event_type,team_size,created_at) VALUES (?,?,?,?,?,?)",
+                    "INSERT INTO events (id,name,logo,event_type,team_size,min_score,max_score,criteria,show_breakdown,aggregation,ranking,bracket,freeze_at,duration,end_at,start_at,created_at) 
+                     VALUES (?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?)",
                     id,
                     name,
                     logo,
                     "team_event",
                     team_size,
+                    self.config.min_score,
+                    self.config.max_score,
+                    criteria,
+                    self.config.show_breakdown,
+                    aggregation,
+                    ranking,
+                    bracket,
+                    self.config.freeze_at,
+                    duration,
+                    self.config.end_at,
+                    self.config.start_at,
                     created_at
                 )
                 .execute(db_pool)
                 .await?;
                 Ok(())
             } else {
-                return Err(VaderError::TeamSizeMismatch("Team size not specified"));
+                Err(VaderError::TeamSizeMismatch("Team size not specified"))
             }
         })
     }
@@ -237,12 +265,29 @@ impl<'a> VaderEvent<'a> for Event<'a, User<'a>> {
         let name = &self.name;
         let created_at = Utc::now();
         Box::pin(async move {
+            let criteria = self.config.criteria_json()?;
+            let aggregation = self.config.aggregation.as_str();
+            let ranking = self.config.ranking.as_str();
+            let bracket = self.config.bracket.map(|b| b.as_str());
+            let duration = self.config.duration.map(|d| d as i64);
             sqlx::query!(
-                "INSERT INTO events (id,name,logo,event_type,created_at) VALUES (?,?,?,?,?)",
+                "INSERT INTO events (id,name,logo,event_type,min_score,max_score,criteria,show_breakdown,aggregation,ranking,bracket,freeze_at,duration,end_at,start_at,created_at) 
+                 VALUES (?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?)",
                 id,
                 name,
                 logo,
                 "user_event",
+                self.config.min_score,
+                self.config.max_score,
+                criteria,
+                self.config.show_breakdown,
+                aggregation,
+                ranking,
+                bracket,
+                self.config.freeze_at,
+                duration,
+                self.config.end_at,
+                self.config.start_at,
                 created_at
             )
             .execute(db_pool)
@@ -260,19 +305,67 @@ impl<'a> Event<'a, User<'a>, ActiveEvent> {
         &self,
         user_id: &Uuid,
         points: i64,
+        mode: ScoreMode,
+        log: &'a ScoreLog,
         db_pool: &'a SqlitePool,
     ) -> AsyncDbRes<'a, ()> {
+        let event_id = self.id.to_string();
+        let config = self.config.clone();
         let id = user_id.to_string();
         Box::pin(async move {
-            let res = sqlx::query("UPDATE users set score=score+? WHERE id=?")
-                .bind(points)
-                .bind(id)
-                .execute(db_pool)
-                .await;
-            match res {
-                Ok(_) => Ok(()),
-                Err(err) => return Err(VaderError::SqlxError(err)),
+            let mut transaction = db_pool.begin().await?;
+            let change = ScoreChange {
+                points,
+                mode,
+                log,
+                revert_of: None,
+                source: ScoreSource::Manual,
+            };
+            let res = apply_score_delta(&mut transaction, &event_id, &id, &config, change).await?;
+            if res.is_none() {
+                transaction.rollback().await?;
+                return Err(VaderError::UserNotFound("No User found in Event"));
+            }
+            transaction.commit().await?;
+            Ok(())
+        })
+    }
+    pub fn update_marks_by_id(
+        &self,
+        marks: &'a CriteriaMarks,
+        log: &'a ScoreLog,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ()> {
+        let event_id = self.id.to_string();
+        let config = self.config.clone();
+        Box::pin(async move {
+            let mut transaction = db_pool.begin().await?;
+            let res =
+                apply_criteria_marks(&mut transaction, &event_id, &config, marks, log).await?;
+            if res.is_none() {
+                transaction.rollback().await?;
+                return Err(VaderError::UserNotFound("No User found in Event"));
+            }
+            transaction.commit().await?;
+            Ok(())
+        })
+    }
+    pub fn update_scores_by_id(
+        &self,
+        updates: &'a [ScoreUpdate],
+        log: &'a ScoreLog,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ()> {
+        let event_id = self.id.to_string();
+        let config = self.config.clone();
+        Box::pin(async move {
+            let mut transaction = db_pool.begin().await?;
+            if !apply_score_batch(&mut transaction, &event_id, &config, updates, log).await? {
+                transaction.rollback().await?;
+                return Err(VaderError::UserNotFound("No User found in Event"));
             }
+            transaction.commit().await?;
+            Ok(())
         })
     }
 }
@@ -283,7 +376,7 @@ impl<'a> Event<'a, Team<'a>> {
         team_id: &'a Uuid,
         members: &'a [Uuid],
         db_pool: &'a SqlitePool,
-    ) -> AsyncDbRes<()> {
+    ) -> AsyncDbRes<'a, ()> {
         Team::add_members_from_id(team_id, members, db_pool)
     }
 }
@@ -293,193 +386,1332 @@ impl<'a> Event<'a, Team<'a>, ActiveEvent> {
         &self,
         team_id: &Uuid,
         points: i64,
+        mode: ScoreMode,
+        log: &'a ScoreLog,
         db_pool: &'a SqlitePool,
     ) -> AsyncDbRes<'a, ()> {
+        let event_id = self.id.to_string();
+        let config = self.config.clone();
         let id = team_id.to_string();
         Box::pin(async move {
-            let res = sqlx::query("UPDATE teams set score=score+? WHERE id=?")
-                .bind(points)
-                .bind(id)
-                .execute(db_pool)
-                .await;
-            match res {
-                Ok(_) => Ok(()),
-                Err(err) => return Err(VaderError::SqlxError(err)),
+            let mut transaction = db_pool.begin().await?;
+            let change = ScoreChange {
+                points,
+                mode,
+                log,
+                revert_of: None,
+                source: ScoreSource::Manual,
+            };
+            let res = apply_score_delta(&mut transaction, &event_id, &id, &config, change).await?;
+            if res.is_none() {
+                transaction.rollback().await?;
+                return Err(VaderError::TeamNotFound("No Team found in Event"));
             }
+            transaction.commit().await?;
+            Ok(())
         })
     }
-}
-
-impl<'a> Event<'a, Team<'a>, NewEvent> {
-    pub fn reset_score(&self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()> {
+    pub fn update_marks_by_id(
+        &self,
+        marks: &'a CriteriaMarks,
+        log: &'a ScoreLog,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ()> {
         let event_id = self.id.to_string();
+        let config = self.config.clone();
         Box::pin(async move {
-            sqlx::query!(
-                "UPDATE teams SET score=0 WHERE 
-                 id IN(SELECT t.id FROM events e 
-                 JOIN event_teams et ON et.event_id = e.id 
-                 JOIN teams t ON et.team_id = t.id 
-                 WHERE e.id = ?)",
-                event_id
-            )
-            .execute(db_pool)
-            .await?;
-            sqlx::query!(
-                "UPDATE users SET score=0 
-                 WHERE id IN(SELECT u.id FROM events e 
-                 JOIN event_teams et ON et.event_id = e.id 
-                 JOIN teams t ON et.team_id = t.id 
-                 JOIN team_members tm ON tm.team_id = t.id 
-                 JOIN users u ON tm.user_id = u.id 
-                 WHERE e.id = ? )",
-                event_id
-            )
-            .execute(db_pool)
-            .await?;
+            let mut transaction = db_pool.begin().await?;
+            let res =
+                apply_criteria_marks(&mut transaction, &event_id, &config, marks, log).await?;
+            if res.is_none() {
+                transaction.rollback().await?;
+                return Err(VaderError::TeamNotFound("No Team found in Event"));
+            }
+            transaction.commit().await?;
             Ok(())
         })
     }
-}
-impl<'a> Event<'a, User<'a>, NewEvent> {
-    pub fn reset_score(&self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()> {
+    pub fn update_scores_by_id(
+        &self,
+        updates: &'a [ScoreUpdate],
+        log: &'a ScoreLog,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ()> {
         let event_id = self.id.to_string();
+        let config = self.config.clone();
         Box::pin(async move {
-            sqlx::query!(
-                "UPDATE users SET score=0 
-                 WHERE id IN(SELECT u.id FROM events e 
-                 JOIN event_users eu ON eu.event_id = e.id 
-                 JOIN users u ON eu.user_id = u.id 
-                 WHERE e.id = ?)",
-                event_id
-            )
-            .execute(db_pool)
-            .await?;
+            let mut transaction = db_pool.begin().await?;
+            if !apply_score_batch(&mut transaction, &event_id, &config, updates, log).await? {
+                transaction.rollback().await?;
+                return Err(VaderError::TeamNotFound("No Team found in Event"));
+            }
+            transaction.commit().await?;
             Ok(())
         })
     }
 }
 
-impl<'a, T> Event<'a, T, NewEvent>
-where
-    T: Player<'a>,
-{
-    pub fn start_event(&self) -> Event<'a, T, ActiveEvent> {
-        Into::<Event<'a, T, ActiveEvent>>::into(self)
-    }
-}
-
 impl<'a, T> Event<'a, T, ActiveEvent>
 where
     T: Player<'a>,
 {
-    pub fn end_event(&self) -> Event<'a, T, EndEvent> {
-        Into::<Event<'a, T, EndEvent>>::into(self)
+    // Revert score updates by applying compensating deltas in a single transaction.
+    // Returns ids of the reverted score_ledger entries.
+    pub fn revert_score(
+        &self,
+        revert: &'a RevertTarget,
+        log: &'a ScoreLog,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Vec<i64>> {
+        let event_id = self.id.to_string();
+        let config = self.config.clone();
+        Box::pin(async move {
+            let mut transaction = db_pool.begin().await?;
+            // solve / decay / marks entries are owned by the solves and marks they came from
+            let manual = ScoreSource::Manual.column();
+            let entries = match revert {
+                RevertTarget::Entry { entry_id } => {
+                    let entry = sqlx::query_as::<_, (i64, String, i64, String)>(
+                        "SELECT id,participant_id,delta,source FROM score_ledger 
+                         WHERE id = ? AND event_id = ? 
+                         AND reverted_by IS NULL AND revert_of IS NULL",
+                    )
+                    .bind(entry_id)
+                    .bind(&event_id)
+                    .fetch_optional(&mut *transaction)
+                    .await?;
+                    match entry {
+                        Some((_, _, _, source)) if source != manual => {
+                            transaction.rollback().await?;
+                            return Err(VaderError::ScoreRevertError(
+                                "Only manual score updates can be reverted",
+                            ));
+                        }
+                        Some((id, p_id, delta, _)) => vec![(id, p_id, delta)],
+                        None => Vec::new(),
+                    }
+                }
+                RevertTarget::Last {
+                    participant_id,
+                    count,
+                } => {
+                    sqlx::query_as::<_, (i64, String, i64)>(
+                        "SELECT id,participant_id,delta FROM score_ledger 
+                         WHERE event_id = ? AND participant_id = ? AND source = ? 
+                         AND reverted_by IS NULL AND revert_of IS NULL 
+                         ORDER BY id DESC LIMIT ?",
+                    )
+                    .bind(&event_id)
+                    .bind(participant_id.to_string())
+                    .bind(manual)
+                    .bind(count)
+                    .fetch_all(&mut *transaction)
+                    .await?
+                }
+            };
+            if entries.is_empty() {
+                transaction.rollback().await?;
+                return Err(VaderError::ScoreRevertError(
+                    "No manual score update found to revert",
+                ));
+            }
+            let mut reverted = Vec::with_capacity(entries.len());
+            for (entry_id, p_id, delta) in entries {
+                let change = ScoreChange {
+                    points: -delta,
+                    mode: ScoreMode::Add,
+                    log,
+                    revert_of: Some(entry_id),
+                    source: ScoreSource::Revert,
+                };
+                let revert_id =
+                    apply_score_delta(&mut transaction, &event_id, &p_id, &config, change).await?;
+                let revert_id = match revert_id {
+                    Some(revert_id) => revert_id,
+                    None => {
+                        transaction.rollback().await?;
+                        return Err(VaderError::ScoreRevertError(
+                            "Participant of score update no longer in Event",
+                        ));
+                    }
+                };
+                sqlx::query!(
+                    "UPDATE score_ledger SET reverted_by = ? WHERE id = ?",
+                    revert_id,
+                    entry_id
+                )
+                .execute(&mut *transaction)
+                .await?;
+                reverted.push(entry_id);
+            }
+            transaction.commit().await?;
+            Ok(reverted)
+        })
     }
-}
-
-impl<'a, T> From<&Event<'a, T, NewEvent>> for Event<'a, T, ActiveEvent>
-where
-    T: Player<'a>,
-{
-    fn from(e: &Event<'a, T, NewEvent>) -> Self {
-        Event {
-            id: e.id,
-            name: e.name.clone(),
-            logo: e.logo.clone(),
-            player_marker: PhantomData::<&'a T>,
-            state_marker: PhantomData::<&'a ActiveEvent>,
-            team_size: e.team_size,
+    // Record a challenge solve, previous solvers lose points decayed by the new solve
+    pub fn record_solve(
+        &self,
+        challenge_id: &'a Uuid,
+        p_id: &'a Uuid,
+        log: &'a ScoreLog,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ()> {
+        let event_id = self.id.to_string();
+        let config = self.config.clone();
+        Box::pin(async move {
+            let mut transaction = db_pool.begin().await?;
+            let challenge = sqlx::query_as::<_, Challenge>(
+                "SELECT id,name,category,points,min_points,decay,first_blood,flag FROM challenges 
+                 WHERE id = ? AND event_id = ?",
+            )
+            .bind(challenge_id.to_string())
+            .bind(&event_id)
+            .fetch_optional(&mut *transaction)
+            .await?;
+            let challenge = match challenge {
+                Some(challenge) => challenge,
+                None => {
+                    transaction.rollback().await?;
+                    return Err(VaderError::ChallengeError("No challenge found in Event"));
+                }
+            };
+            let c_id = challenge.id.to_string();
+            let p_id = p_id.to_string();
+            let solved_at = Utc::now();
+            let res = sqlx::query!(
+                "INSERT INTO solves (challenge_id,event_id,participant_id,solved_at) 
+                 SELECT ?,event_id,participant_id,? FROM event_scores 
+                 WHERE event_id = ? AND participant_id = ? 
+                 ON CONFLICT (challenge_id,participant_id) DO NOTHING",
+                c_id,
+                solved_at,
+                event_id,
+                p_id
+            )
+            .execute(&mut *transaction)
+            .await?;
+            if res.rows_affected() == 0 {
+                transaction.rollback().await?;
+                return Err(VaderError::ChallengeError(
+                    "Participant not in Event or challenge already solved",
+                ));
+            }
+            let solvers = sqlx::query_scalar::<_, String>(
+                "SELECT participant_id FROM solves WHERE challenge_id = ? AND participant_id != ?",
+            )
+            .bind(&c_id)
+            .bind(&p_id)
+            .fetch_all(&mut *transaction)
+            .await?;
+            let solves = solvers.len() as i64 + 1;
+            let decay = challenge.value(solves) - challenge.value(solves - 1);
+            if decay != 0 {
+                let decay_log = ScoreLog {
+                    admin: log.admin.clone(),
+                    reason: Some(format!("Decay of challenge {}", challenge.name)),
+                };
+                for solver in solvers.iter() {
+                    let change = ScoreChange {
+                        points: decay,
+                        mode: ScoreMode::Add,
+                        log: &decay_log,
+                        revert_of: None,
+                        source: ScoreSource::Decay,
+                    };
+                    apply_score_delta(&mut transaction, &event_id, solver, &config, change).await?;
+                }
+            }
+            let mut points = challenge.value(solves);
+            if solves == 1 {
+                points += challenge.first_blood;
+            }
+            let solve_log = ScoreLog {
+                admin: log.admin.clone(),
+                reason: Some(format!("Solve of challenge {}", challenge.name)),
+            };
+            let change = ScoreChange {
+                points,
+                mode: ScoreMode::Add,
+                log: &solve_log,
+                revert_of: None,
+                source: ScoreSource::Solve,
+            };
+            apply_score_delta(&mut transaction, &event_id, &p_id, &config, change).await?;
+            transaction.commit().await?;
+            Ok(())
+        })
+    }
+    pub fn record_match(
+        &self,
+        match_id: u32,
+        result: &'a MatchResult,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ()> {
+        if self.config.bracket.is_none() {
+            return Box::pin(async move { Err(VaderError::BracketError("Event has no bracket")) });
         }
+        Bracket::record_match(self.id, match_id, result, db_pool)
     }
 }
-impl<'a, T> From<&Event<'a, T, ActiveEvent>> for Event<'a, T, EndEvent>
+
+// Freeze is revealed while the event runs or once it has ended
+impl<'a, T, S> Event<'a, T, S>
 where
     T: Player<'a>,
+    S: VbStateMarker + EventState,
+    Self: VaderBoard<'a, VbRes = VboardEntry<'a>>,
 {
-    fn from(e: &Event<'a, T, ActiveEvent>) -> Self {
-        Event {
-            id: e.id,
-            name: e.name.clone(),
-            logo: e.logo.clone(),
-            player_marker: PhantomData::<&'a T>,
-            state_marker: PhantomData::<&'a EndEvent>,
-            team_size: e.team_size,
-        }
-    }
-}
-
-impl<'a> Team<'a> {
-    pub fn new(name: Cow<'a, str>, logo: Option<Cow<'a, str>>) -> Self {
-        Self {
-            id: Uuid::new_v4(),
-            name,
-            logo,
-            members: Vec::new(),
-            score: 0,
-        }
-    }
-    pub fn get_team<'b>(team_id: &'b Uuid, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, Self> {
-        let id = team_id.to_string();
+    // Returns the revealed participant when revealing step by step
+    pub fn reveal(
+        &'a self,
+        mode: RevealMode,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Option<Uuid>> {
         Box::pin(async move {
-            let team = sqlx::query_as::<_, Team>(
-                "SELECT t.id AS id,t.name AS name, t.score AS score,t.logo AS logo,
-                GROUP_CONCAT(tm.user_id,',') AS team_members 
-                FROM teams t JOIN team_members tm ON tm.team_id = t.id 
-                WHERE t.id = ? GROUP BY t.id",
-            )
-            .bind(id)
-            .fetch_one(db_pool)
-            .await?;
-            Ok(team)
+            match mode {
+                RevealMode::All => self.reveal_all(db_pool).await.map(|_| None),
+                RevealMode::Step => {
+                    let vboard = self.get_vboard(u32::MAX, db_pool).await?;
+                    self.reveal_next(&vboard, db_pool).await.map(Some)
+                }
+            }
         })
     }
-    pub fn delete_team<'b>(id: &'b Uuid, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, ()> {
-        let id = id.to_string();
+    // Unfreeze vaderboard, showing every real score
+    pub fn reveal_all(&self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()> {
+        let event_id = self.id.to_string();
+        let config = self.config.clone();
         Box::pin(async move {
-            let res = sqlx::query!("DELETE FROM teams WHERE id = ? ", id)
-                .execute(db_pool)
-                .await?;
-            if res.rows_affected().eq(&0) {
-                return Err(VaderError::TeamNotFound("No team found"));
+            let mut transaction = db_pool.begin().await?;
+            if vboard_cutoff(&event_id, &config, &mut transaction)
+                .await?
+                .is_none()
+            {
+                transaction.rollback().await?;
+                return Err(VaderError::FreezeError("Vaderboard is not frozen"));
+            }
+            let res = match sqlx::query("UPDATE events SET revealed = 1 WHERE id = ?")
+                .bind(&event_id)
+                .execute(&mut *transaction)
+                .await
+            {
+                Ok(_) if S::STATE == EndEvent::STATE => {
+                    save_placements(T::TABLE, &event_id, &config, &mut transaction).await
+                }
+                Ok(_) => Ok(()),
+                Err(err) => Err(err.into()),
+            };
+            if let Err(err) = res {
+                transaction.rollback().await?;
+                return Err(err);
             }
+            transaction.commit().await?;
             Ok(())
         })
     }
-}
-impl<'a> User<'a> {
-    pub fn new(name: Cow<'a, str>, logo: Option<Cow<'a, str>>) -> Self {
-        Self {
-            id: Uuid::new_v4(),
-            name,
-            logo,
-            score: 0,
-        }
-    }
-    pub fn get_user<'b>(user_id: &'b Uuid, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, Self> {
-        let id = user_id.to_string();
+    // Reveal real score of the lowest ranked frozen row of the vaderboard
+    // vaderboard is unfrozen once no frozen row is left
+    pub fn reveal_next(
+        &self,
+        vboard: &[VboardEntry<'_>],
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Uuid> {
+        let event_id = self.id.to_string();
+        let config = self.config.clone();
+        let frozen: Vec<Uuid> = vboard.iter().filter(|e| e.frozen).map(|e| e.id).collect();
         Box::pin(async move {
-            let user =
-                sqlx::query_as::<_, User>("SELECT id,name,score,logo FROM users WHERE id = ?")
-                    .bind(id)
-                    .fetch_one(db_pool)
+            let p_id = match frozen.last() {
+                Some(p_id) => *p_id,
+                None => return Err(VaderError::FreezeError("Vaderboard is not frozen")),
+            };
+            let mut transaction = db_pool.begin().await?;
+            sqlx::query(
+                "INSERT INTO revealed_participants (event_id,participant_id,revealed_at) VALUES (?,?,?)",
+            )
+            .bind(&event_id)
+            .bind(p_id.to_string())
+            .bind(Utc::now())
+            .execute(&mut *transaction)
+            .await?;
+            if frozen.len() == 1 {
+                sqlx::query("UPDATE events SET revealed = 1 WHERE id = ?")
+                    .bind(&event_id)
+                    .execute(&mut *transaction)
                     .await?;
-            Ok(user)
+            }
+            // placements of an ended event move with each revealed score
+            if S::STATE == EndEvent::STATE {
+                save_placements(T::TABLE, &event_id, &config, &mut transaction).await?;
+            }
+            transaction.commit().await?;
+            Ok(p_id)
         })
     }
-    pub fn delete_user<'b>(id: &'b Uuid, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, ()> {
-        let id = id.to_string();
+}
+
+impl<'a, T, U> Event<'a, T, U>
+where
+    T: Player<'a>,
+    U: EventState,
+{
+    // Pause and resume write state and clock in one transaction
+    fn save_toggle<'b>(
+        &self,
+        toggle: fn(EventClock, DateTime<Utc>) -> EventClock,
+        db_pool: &'b SqlitePool,
+    ) -> AsyncDbRes<'b, Option<EventClock>> {
+        let event_id = self.id.to_string();
         Box::pin(async move {
-            let res = sqlx::query!("DELETE FROM users WHERE id = ? ", id)
-                .execute(db_pool)
+            let mut transaction = db_pool.begin().await?;
+            let res = sqlx::query("UPDATE events SET state = ? WHERE id = ?")
+                .bind(U::STATE)
+                .bind(&event_id)
+                .execute(&mut *transaction)
                 .await?;
             if res.rows_affected().eq(&0) {
-                return Err(VaderError::UserNotFound("No User found"));
+                return Err(VaderError::EventNotFound("No event found to update state"));
             }
-            Ok(())
+            let clock = read_clock(&event_id, &mut transaction)
+                .await?
+                .map(|clock| toggle(clock, Utc::now()));
+            if clock.is_some() {
+                save_clock(&event_id, clock, &mut transaction).await?;
+            }
+            transaction.commit().await?;
+            Ok(clock)
         })
     }
-    pub fn get_all_user(db_pool: &SqlitePool) -> AsyncDbRes<'_, Vec<Self>> {
-        Box::pin(async move {
+    pub fn add_challenge(
+        &self,
+        challenge: &'a Challenge<'a>,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ()> {
+        let event_id = self.id.to_string();
+        Box::pin(async move {
+            let id = challenge.id.to_string();
+            let created_at = Utc::now();
+            sqlx::query!(
+                "INSERT INTO challenges (id,event_id,name,category,points,min_points,decay,first_blood,flag,created_at) 
+                 VALUES (?,?,?,?,?,?,?,?,?,?)",
+                id,
+                event_id,
+                challenge.name,
+                challenge.category,
+                challenge.points,
+                challenge.min_points,
+                challenge.decay,
+                challenge.first_blood,
+                challenge.flag,
+                created_at
+            )
+            .execute(db_pool)
+            .await?;
+            Ok(())
+        })
+    }
+}
+
+impl<'a> Challenge<'a> {
+    // Validate request and hash the flag
+    pub fn from_req(req: ChallengeReq<'a>) -> AsyncDbRes<'a, Self> {
+        Box::pin(async move {
+            if req.points <= 0 || req.first_blood < 0 {
+                return Err(VaderError::ChallengeError(
+                    "Challenge points should be positive",
+                ));
+            }
+            match (req.min_points, req.decay) {
+                (Some(min), Some(decay)) if min < 0 || min > req.points || decay == 0 => {
+                    return Err(VaderError::ChallengeError(
+                        "min_points should be within points and decay greater than 0",
+                    ));
+                }
+                (Some(_), None) | (None, Some(_)) => {
+                    return Err(VaderError::ChallengeError(
+                        "Both min_points and decay are needed for dynamic scoring",
+                    ));
+                }
+                _ => {}
+            }
+            let flag = match req.flag {
+                Some(flag) => Some(actix_web::web::block(move || hash(flag, DEFAULT_COST)).await??),
+                None => None,
+            };
+            Ok(Challenge {
+                id: Uuid::new_v4(),
+                name: req.name,
+                category: req.category,
+                points: req.points,
+                min_points: req.min_points,
+                decay: req.decay,
+                first_blood: req.first_blood,
+                flag,
+            })
+        })
+    }
+    // Point value with given solve count, decays quadratically to min_points over decay solves
+    pub fn value(&self, solves: i64) -> i64 {
+        match (self.min_points, self.decay) {
+            (Some(min), Some(decay)) if solves > 1 => {
+                let n = (solves - 1) as f64;
+                let decay = decay as f64;
+                let value =
+                    (min - self.points) as f64 / (decay * decay) * n * n + self.points as f64;
+                (value.ceil() as i64).max(min)
+            }
+            _ => self.points,
+        }
+    }
+    // Check submitted flag against the challenge flag hash
+    pub fn check_flag(
+        event_id: &'a Uuid,
+        challenge_id: &'a Uuid,
+        flag: String,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, bool> {
+        let event_id = event_id.to_string();
+        let challenge_id = challenge_id.to_string();
+        Box::pin(async move {
+            let hash = sqlx::query_scalar!(
+                "SELECT flag FROM challenges WHERE id = ? AND event_id = ?",
+                challenge_id,
+                event_id
+            )
+            .fetch_optional(db_pool)
+            .await?;
+            match hash {
+                Some(Some(hash)) => Ok(actix_web::web::block(move || verify(flag, &hash)).await??),
+                Some(None) => Err(VaderError::ChallengeError("Challenge has no flag")),
+                None => Err(VaderError::ChallengeError("No challenge found in Event")),
+            }
+        })
+    }
+}
+
+// Apply every update of a batch on the same connection.
+// Returns false as soon as a participant is not part of the event.
+async fn apply_score_batch(
+    conn: &mut SqliteConnection,
+    event_id: &str,
+    config: &EventConfig,
+    updates: &[ScoreUpdate],
+    log: &ScoreLog,
+) -> Result<bool, VaderError<'static>> {
+    for su in updates {
+        let log = ScoreLog {
+            admin: log.admin.clone(),
+            reason: su.reason.clone().or_else(|| log.reason.clone()),
+        };
+        let change = ScoreChange {
+            points: su.score,
+            mode: su.mode,
+            log: &log,
+            revert_of: None,
+            source: ScoreSource::Manual,
+        };
+        let res =
+            apply_score_delta(&mut *conn, event_id, &su.id.to_string(), config, change).await?;
+        if res.is_none() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// Store criteria marks of a judge and set participant score to the aggregate
+// of weighted totals of all judges.
+// Returns the score_ledger entry id, or None if participant is not part of the event.
+async fn apply_criteria_marks(
+    conn: &mut SqliteConnection,
+    event_id: &str,
+    config: &EventConfig,
+    marks: &CriteriaMarks,
+    log: &ScoreLog,
+) -> Result<Option<i64>, VaderError<'static>> {
+    if config.criteria.is_empty() {
+        return Err(VaderError::CriteriaMismatch(
+            "Event has no judging criteria",
+        ));
+    }
+    for (name, mark) in marks.marks.iter() {
+        match config.criteria.iter().find(|c| &c.name == name) {
+            Some(criterion) if *mark > criterion.max_marks => {
+                return Err(VaderError::CriteriaMismatch(
+                    "Marks greater than max marks of criterion",
+                ));
+            }
+            Some(_) => {}
+            None => return Err(VaderError::CriteriaMismatch("No such criterion in Event")),
+        }
+    }
+    let judge = match log.admin.as_ref() {
+        Some(judge) => judge,
+        None => return Err(VaderError::CriteriaMismatch("No logged in judge for marks")),
+    };
+    let p_id = marks.id.to_string();
+    for (name, mark) in marks.marks.iter() {
+        let res = sqlx::query!(
+            "INSERT INTO judge_marks (event_id,participant_id,judge,criterion,marks) 
+             SELECT event_id,participant_id,?,?,? FROM event_scores 
+             WHERE event_id = ? AND participant_id = ? 
+             ON CONFLICT (event_id,participant_id,judge,criterion) DO UPDATE SET marks = excluded.marks",
+            judge,
+            name,
+            mark,
+            event_id,
+            p_id
+        )
+        .execute(&mut *conn)
+        .await?;
+        if res.rows_affected() == 0 {
+            return Ok(None);
+        }
+    }
+    let rows = sqlx::query_as::<_, (String, String, i64)>(
+        "SELECT judge,criterion,marks FROM judge_marks WHERE event_id = ? AND participant_id = ?",
+    )
+    .bind(event_id)
+    .bind(&p_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut sheets: HashMap<String, HashMap<String, i64>> = HashMap::new();
+    for (judge, criterion, mark) in rows {
+        sheets.entry(judge).or_default().insert(criterion, mark);
+    }
+    let mut totals: Vec<f64> = sheets
+        .values()
+        .map(|sheet| config.weighted_total(sheet))
+        .collect();
+    let total = config.aggregation.aggregate(&mut totals).round() as i64;
+    let change = ScoreChange {
+        points: total,
+        mode: ScoreMode::Set,
+        log,
+        revert_of: None,
+        source: ScoreSource::Marks,
+    };
+    apply_score_delta(conn, event_id, &p_id, config, change).await
+}
+
+// Score change applied by apply_score_delta
+struct ScoreChange<'c> {
+    points: i64,
+    mode: ScoreMode,
+    log: &'c ScoreLog,
+    revert_of: Option<i64>,
+    source: ScoreSource,
+}
+
+// What made a score change, stored in score_ledger.source
+#[derive(Clone, Copy, PartialEq)]
+enum ScoreSource {
+    Manual,
+    Solve,
+    Decay,
+    // criteria events only take scores computed from marks
+    Marks,
+    Revert,
+}
+impl ScoreSource {
+    const fn column(self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Solve => "solve",
+            Self::Decay => "decay",
+            Self::Marks => "marks",
+            Self::Revert => "revert",
+        }
+    }
+}
+
+// Apply a score change to participant score and append it to score_ledger.
+// Rejects changes taking the total out of event bounds.
+// Returns the score_ledger entry id, or None if participant is not part of the event.
+async fn apply_score_delta(
+    conn: &mut SqliteConnection,
+    event_id: &str,
+    p_id: &str,
+    config: &EventConfig,
+    change: ScoreChange<'_>,
+) -> Result<Option<i64>, VaderError<'static>> {
+    if !config.criteria.is_empty() && change.source != ScoreSource::Marks {
+        return Err(VaderError::CriteriaMismatch(
+            "Event is scored by criteria marks",
+        ));
+    }
+    let score = sqlx::query_scalar!(
+        "SELECT score FROM event_scores WHERE event_id=? AND participant_id=?",
+        event_id,
+        p_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    let score = match score {
+        Some(score) => score,
+        None => return Ok(None),
+    };
+    let total = match change.mode {
+        ScoreMode::Add => score + change.points,
+        ScoreMode::Set => change.points,
+    };
+    // compensating entry restores an earlier total, bounds may have changed since
+    // decay follows solves of others, it can't be refused for the solver's total
+    if !matches!(change.source, ScoreSource::Revert | ScoreSource::Decay) {
+        config.check_score(total)?;
+    }
+    let delta = total - score;
+    let created_at = Utc::now();
+    // updated_at is kept when score is unchanged so ties go to the earliest to reach it
+    if delta != 0 {
+        sqlx::query!(
+            "UPDATE event_scores SET score=?,updated_at=? WHERE event_id=? AND participant_id=?",
+            total,
+            created_at,
+            event_id,
+            p_id
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    let reason = match (change.revert_of, &change.log.reason) {
+        (Some(entry_id), None) => Some(format!("Revert of #{}", entry_id)),
+        (_, reason) => reason.clone(),
+    };
+    let source = change.source.column();
+    let entry_id = sqlx::query!(
+        "INSERT INTO score_ledger (event_id,participant_id,delta,total,admin,reason,created_at,revert_of,source) 
+         VALUES (?,?,?,?,?,?,?,?,?)",
+        event_id,
+        p_id,
+        delta,
+        total,
+        change.log.admin,
+        reason,
+        created_at,
+        change.revert_of,
+        source
+    )
+    .execute(&mut *conn)
+    .await?
+    .last_insert_rowid();
+    Ok(Some(entry_id))
+}
+
+impl EventConfig {
+    pub fn validate(&self) -> Result<(), VaderError<'static>> {
+        if let (Some(min), Some(max)) = (self.min_score, self.max_score) {
+            if min > max {
+                return Err(VaderError::ScoreOutOfBounds(
+                    "min_score is greater than max_score",
+                ));
+            }
+        }
+        for (i, criterion) in self.criteria.iter().enumerate() {
+            if criterion.name.is_empty() {
+                return Err(VaderError::CriteriaMismatch("Criterion name is empty"));
+            }
+            if !(criterion.weight.is_finite() && criterion.weight > 0.0) {
+                return Err(VaderError::CriteriaMismatch(
+                    "Criterion weight should be greater than 0",
+                ));
+            }
+            // scores are whole points, a fractional weight would be lost to rounding
+            if criterion.weight.fract() != 0.0 {
+                return Err(VaderError::CriteriaMismatch(
+                    "Criterion weight should be a whole number of points",
+                ));
+            }
+            if criterion.max_marks == 0 {
+                return Err(VaderError::CriteriaMismatch(
+                    "Criterion max marks should be greater than 0",
+                ));
+            }
+            if self.criteria[..i].iter().any(|c| c.name == criterion.name) {
+                return Err(VaderError::CriteriaMismatch("Duplicate criterion name"));
+            }
+        }
+        if self.duration.is_some() && self.end_at.is_some() {
+            return Err(VaderError::ClockError(
+                "Only one of duration and end_at can be set",
+            ));
+        }
+        if let Some(duration) = self.duration {
+            check_duration(duration)?;
+        }
+        if self.end_at.is_some_and(|end_at| end_at <= Utc::now()) {
+            return Err(VaderError::ClockError("end_at should be in the future"));
+        }
+        if let Some(start_at) = self.start_at {
+            if start_at <= Utc::now() {
+                return Err(VaderError::ScheduleError(
+                    "start_at should be in the future",
+                ));
+            }
+            if self.end_at.is_some_and(|end_at| end_at <= start_at) {
+                return Err(VaderError::ScheduleError("end_at should be after start_at"));
+            }
+        }
+        if let Some(freeze_at) = self.freeze_at {
+            if self.start_at.is_some_and(|start_at| freeze_at < start_at) {
+                return Err(VaderError::FreezeError(
+                    "freeze_at should not be before start_at",
+                ));
+            }
+            // a scheduled event with a duration ends at start_at + duration
+            let end_at = self.end_at.or_else(|| {
+                self.start_at
+                    .zip(self.duration)
+                    .map(|(start_at, duration)| start_at + Duration::seconds(duration as i64))
+            });
+            if end_at.is_some_and(|end_at| freeze_at >= end_at) {
+                return Err(VaderError::FreezeError("freeze_at should be before end_at"));
+            }
+        }
+        Ok(())
+    }
+    // Clock started along with the event
+    fn initial_clock(&self, now: DateTime<Utc>) -> Option<EventClock> {
+        match (self.duration, self.end_at) {
+            (Some(duration), _) => Some(EventClock::Running {
+                ends_at: now + Duration::seconds(duration as i64),
+            }),
+            (None, Some(end_at)) => Some(EventClock::Running { ends_at: end_at }),
+            (None, None) => None,
+        }
+    }
+    fn criteria_json(&self) -> Result<Option<String>, VaderError<'static>> {
+        if self.criteria.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(serde_json::to_string(&self.criteria)?))
+        }
+    }
+    // Sum of marks / max_marks * weight over criteria
+    fn weighted_total(&self, marks: &HashMap<String, i64>) -> f64 {
+        self.criteria
+            .iter()
+            .map(|c| {
+                let mark = marks.get(&c.name).copied().unwrap_or(0);
+                mark as f64 / c.max_marks as f64 * c.weight
+            })
+            .sum()
+    }
+    fn check_score(&self, total: i64) -> Result<(), VaderError<'static>> {
+        if self.min_score.is_some_and(|min| total < min) {
+            return Err(VaderError::ScoreOutOfBounds(
+                "Score update takes total below event minimum",
+            ));
+        }
+        if self.max_score.is_some_and(|max| total > max) {
+            return Err(VaderError::ScoreOutOfBounds(
+                "Score update takes total above event maximum",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl RankingMode {
+    // value stored in events.ranking column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Dense => "dense",
+            Self::Ordinal => "ordinal",
+        }
+    }
+    pub fn from_column(value: &str) -> Option<Self> {
+        match value {
+            "standard" => Some(Self::Standard),
+            "dense" => Some(Self::Dense),
+            "ordinal" => Some(Self::Ordinal),
+            _ => None,
+        }
+    }
+    // Rank rows sorted by score and tie-breakers
+    // Standard : 1224, Dense : 1223, Ordinal : 1234
+    pub fn rank(&self, entries: &mut [VboardEntry<'_>]) {
+        let mut rank = 0;
+        let mut prev_score = None;
+        for (i, entry) in entries.iter_mut().enumerate() {
+            let tied = prev_score == Some(entry.score);
+            rank = match self {
+                Self::Standard if tied => rank,
+                Self::Standard => i as u32 + 1,
+                Self::Dense if tied => rank,
+                Self::Dense => rank + 1,
+                Self::Ordinal => i as u32 + 1,
+            };
+            entry.rank = rank;
+            prev_score = Some(entry.score);
+        }
+    }
+}
+
+impl Aggregation {
+    // value stored in events.aggregation column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mean => "mean",
+            Self::Median => "median",
+            Self::TrimmedMean => "trimmed_mean",
+        }
+    }
+    pub fn from_column(value: &str) -> Option<Self> {
+        match value {
+            "mean" => Some(Self::Mean),
+            "median" => Some(Self::Median),
+            "trimmed_mean" => Some(Self::TrimmedMean),
+            _ => None,
+        }
+    }
+    // Combine values of judges, 0 when there are none
+    fn aggregate(&self, values: &mut [f64]) -> f64 {
+        if values.is_empty() {
+            return 0.0;
+        }
+        values.sort_by(f64::total_cmp);
+        let mean = |v: &[f64]| v.iter().sum::<f64>() / v.len() as f64;
+        match self {
+            Self::Mean => mean(values),
+            Self::Median => {
+                let mid = values.len() / 2;
+                if values.len().is_multiple_of(2) {
+                    (values[mid - 1] + values[mid]) / 2.0
+                } else {
+                    values[mid]
+                }
+            }
+            // falls back to mean when there are less than 3 judges
+            Self::TrimmedMean if values.len() > 2 => mean(&values[1..values.len() - 1]),
+            Self::TrimmedMean => mean(values),
+        }
+    }
+}
+
+impl<'a, T> Event<'a, T, EndEvent>
+where
+    T: Player<'a>,
+{
+    // State and final placements are saved together, a failed stop can be retried
+    pub fn save_end<'b>(&self, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, ()> {
+        let event_id = self.id.to_string();
+        let config = self.config.clone();
+        Box::pin(async move {
+            let mut transaction = db_pool.begin().await?;
+            let res = sqlx::query(
+                "UPDATE events SET state = ?,clock_ends_at = NULL,clock_remaining = NULL WHERE id = ?",
+            )
+            .bind(EndEvent::STATE)
+            .bind(&event_id)
+            .execute(&mut *transaction)
+            .await?;
+            if res.rows_affected().eq(&0) {
+                return Err(VaderError::EventNotFound("No event found to update state"));
+            }
+            save_placements(T::TABLE, &event_id, &config, &mut transaction).await?;
+            transaction.commit().await?;
+            Ok(())
+        })
+    }
+}
+
+impl<'a, T> Event<'a, T, NewEvent>
+where
+    T: Player<'a>,
+{
+    pub fn reset_score<'b>(&self, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, ()> {
+        let event_id = self.id.to_string();
+        Box::pin(async move {
+            sqlx::query!(
+                "UPDATE event_scores SET score=0,updated_at=NULL WHERE event_id = ?",
+                event_id
+            )
+            .execute(db_pool)
+            .await?;
+            Ok(())
+        })
+    }
+}
+
+impl<'a, T> Event<'a, T, NewEvent>
+where
+    T: Player<'a>,
+{
+    // Scores reset, bracket drawn, state and clock saved in one transaction
+    // returns the clock started with the event
+    pub fn save_start<'b>(&self, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, Option<EventClock>> {
+        let id = self.id;
+        let event_id = id.to_string();
+        let bracket = self.config.bracket;
+        let clock = self.config.initial_clock(Utc::now());
+        Box::pin(async move {
+            let mut transaction = db_pool.begin().await?;
+            sqlx::query("UPDATE event_scores SET score=0,updated_at=NULL WHERE event_id = ?")
+                .bind(&event_id)
+                .execute(&mut *transaction)
+                .await?;
+            if let Some(format) = bracket {
+                Bracket::create(id, format, &mut transaction).await?;
+            }
+            let res = sqlx::query("UPDATE events SET state = ? WHERE id = ?")
+                .bind(ActiveEvent::STATE)
+                .bind(&event_id)
+                .execute(&mut *transaction)
+                .await?;
+            if res.rows_affected().eq(&0) {
+                transaction.rollback().await?;
+                return Err(VaderError::EventNotFound("No event found to update state"));
+            }
+            save_clock(&event_id, clock, &mut transaction).await?;
+            transaction.commit().await?;
+            Ok(clock)
+        })
+    }
+    pub fn start_event(&self) -> Event<'a, T, ActiveEvent> {
+        Into::<Event<'a, T, ActiveEvent>>::into(self)
+    }
+}
+
+impl<'a, T> Event<'a, T, ActiveEvent>
+where
+    T: Player<'a>,
+{
+    pub fn end_event(&self) -> Event<'a, T, EndEvent> {
+        Into::<Event<'a, T, EndEvent>>::into(self)
+    }
+    pub fn pause_event(&self) -> Event<'a, T, PausedEvent> {
+        Into::<Event<'a, T, PausedEvent>>::into(self)
+    }
+}
+
+impl<'a, T> Event<'a, T, PausedEvent>
+where
+    T: Player<'a>,
+{
+    pub fn resume_event(&self) -> Event<'a, T, ActiveEvent> {
+        Into::<Event<'a, T, ActiveEvent>>::into(self)
+    }
+    pub fn end_event(&self) -> Event<'a, T, EndEvent> {
+        Into::<Event<'a, T, EndEvent>>::into(self)
+    }
+    // Paused state is saved along with the stopped clock
+    pub fn save_pause<'b>(&self, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, Option<EventClock>> {
+        self.save_toggle(EventClock::paused, db_pool)
+    }
+}
+
+impl<'a, T> Event<'a, T, ActiveEvent>
+where
+    T: Player<'a>,
+{
+    // Start configured countdown, events without duration or end_at have no clock
+    // Active state is saved along with the clock running again
+    pub fn save_resume<'b>(&self, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, Option<EventClock>> {
+        self.save_toggle(EventClock::resumed, db_pool)
+    }
+    pub fn update_clock(
+        &self,
+        clock_req: &'a ClockReq,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, EventClock> {
+        let id = self.id;
+        Box::pin(async move {
+            let now = Utc::now();
+            let clock = EventClock::get(id, db_pool).await?;
+            let clock = match (clock_req, clock) {
+                (ClockReq::Pause, Some(clock @ EventClock::Running { .. })) => clock.paused(now),
+                (ClockReq::Resume, Some(clock @ EventClock::Paused { .. })) => clock.resumed(now),
+                (ClockReq::Pause, Some(EventClock::Paused { .. })) => {
+                    return Err(VaderError::ClockError("Clock already paused"))
+                }
+                (ClockReq::Resume, Some(EventClock::Running { .. })) => {
+                    return Err(VaderError::ClockError("Clock is not paused"))
+                }
+                (ClockReq::Pause | ClockReq::Resume, None) => {
+                    return Err(VaderError::ClockError("Event has no clock"))
+                }
+                (ClockReq::Set { duration, end_at }, clock) => {
+                    let remaining_ms = match (duration, end_at) {
+                        (Some(duration), None) => {
+                            check_duration(*duration)?;
+                            *duration as i64 * 1000
+                        }
+                        (None, Some(end_at)) if *end_at > now => (*end_at - now).num_milliseconds(),
+                        (None, Some(_)) => {
+                            return Err(VaderError::ClockError("end_at should be in the future"))
+                        }
+                        _ => {
+                            return Err(VaderError::ClockError(
+                                "Exactly one of duration and end_at should be set",
+                            ))
+                        }
+                    };
+                    // paused clock stays paused with the new time left
+                    match clock {
+                        Some(EventClock::Paused { .. }) => EventClock::Paused { remaining_ms },
+                        _ => EventClock::Running {
+                            ends_at: now + Duration::milliseconds(remaining_ms),
+                        },
+                    }
+                }
+            };
+            save_clock(&id.to_string(), Some(clock), &mut *db_pool.acquire().await?).await?;
+            Ok(clock)
+        })
+    }
+}
+
+impl EventClock {
+    pub fn remaining_ms(&self, now: DateTime<Utc>) -> i64 {
+        match self {
+            Self::Running { ends_at } => (*ends_at - now).num_milliseconds().max(0),
+            Self::Paused { remaining_ms } => *remaining_ms,
+        }
+    }
+    fn paused(self, now: DateTime<Utc>) -> Self {
+        Self::Paused {
+            remaining_ms: self.remaining_ms(now),
+        }
+    }
+    fn resumed(self, now: DateTime<Utc>) -> Self {
+        Self::Running {
+            ends_at: now + Duration::milliseconds(self.remaining_ms(now)),
+        }
+    }
+    fn from_columns(ends_at: Option<DateTime<Utc>>, remaining_ms: Option<i64>) -> Option<Self> {
+        match (ends_at, remaining_ms) {
+            (Some(ends_at), _) => Some(Self::Running { ends_at }),
+            (None, Some(remaining_ms)) => Some(Self::Paused { remaining_ms }),
+            (None, None) => None,
+        }
+    }
+    pub fn get(event_id: Uuid, db_pool: &SqlitePool) -> AsyncDbRes<'_, Option<Self>> {
+        Box::pin(async move {
+            let mut conn = db_pool.acquire().await?;
+            read_clock(&event_id.to_string(), &mut conn).await
+        })
+    }
+    // Clocks of active / paused events, pushed to vaderboards on server restart
+    pub fn get_all(db_pool: &SqlitePool) -> AsyncDbRes<'_, Vec<(Uuid, Self)>> {
+        Box::pin(async move {
+            let rows = sqlx::query_as::<_, (String, Option<DateTime<Utc>>, Option<i64>)>(
+                "SELECT id,clock_ends_at,clock_remaining FROM events WHERE state IN (?,?)",
+            )
+            .bind(ActiveEvent::STATE)
+            .bind(PausedEvent::STATE)
+            .fetch_all(db_pool)
+            .await?;
+            let mut clocks = Vec::with_capacity(rows.len());
+            for (id, ends_at, remaining_ms) in rows {
+                let id = Uuid::parse_str(&id)
+                    .map_err(|_e| VaderError::SqlxFieldError("Error decoding Event Id"))?;
+                if let Some(clock) = Self::from_columns(ends_at, remaining_ms) {
+                    clocks.push((id, clock));
+                }
+            }
+            Ok(clocks)
+        })
+    }
+}
+
+async fn read_clock(
+    event_id: &str,
+    conn: &mut SqliteConnection,
+) -> Result<Option<EventClock>, VaderError<'static>> {
+    let (ends_at, remaining_ms) = sqlx::query_as::<_, (Option<DateTime<Utc>>, Option<i64>)>(
+        "SELECT clock_ends_at,clock_remaining FROM events WHERE id = ?",
+    )
+    .bind(event_id)
+    .fetch_optional(conn)
+    .await?
+    .ok_or(VaderError::EventNotFound("No event found to get clock"))?;
+    Ok(EventClock::from_columns(ends_at, remaining_ms))
+}
+
+async fn save_clock(
+    event_id: &str,
+    clock: Option<EventClock>,
+    conn: &mut SqliteConnection,
+) -> Result<(), VaderError<'static>> {
+    let (ends_at, remaining_ms) = match clock {
+        Some(EventClock::Running { ends_at }) => (Some(ends_at), None),
+        Some(EventClock::Paused { remaining_ms }) => (None, Some(remaining_ms)),
+        None => (None, None),
+    };
+    sqlx::query("UPDATE events SET clock_ends_at = ?,clock_remaining = ? WHERE id = ?")
+        .bind(ends_at)
+        .bind(remaining_ms)
+        .bind(event_id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+// Longer durations overflow the clock
+fn check_duration(duration: u64) -> Result<(), VaderError<'static>> {
+    if duration == 0 {
+        return Err(VaderError::ClockError("Duration should be greater than 0"));
+    }
+    if duration > u32::MAX as u64 {
+        return Err(VaderError::ClockError("Duration is too long"));
+    }
+    Ok(())
+}
+
+impl<'a, T> From<&Event<'a, T, NewEvent>> for Event<'a, T, ActiveEvent>
+where
+    T: Player<'a>,
+{
+    fn from(e: &Event<'a, T, NewEvent>) -> Self {
+        Event {
+            id: e.id,
+            name: e.name.clone(),
+            logo: e.logo.clone(),
+            player_marker: PhantomData::<&'a T>,
+            state_marker: PhantomData::<&'a ActiveEvent>,
+            team_size: e.team_size,
+            config: e.config.clone(),
+        }
+    }
+}
+impl<'a, T> From<&Event<'a, T, ActiveEvent>> for Event<'a, T, EndEvent>
+where
+    T: Player<'a>,
+{
+    fn from(e: &Event<'a, T, ActiveEvent>) -> Self {
+        Event {
+            id: e.id,
+            name: e.name.clone(),
+            logo: e.logo.clone(),
+            player_marker: PhantomData::<&'a T>,
+            state_marker: PhantomData::<&'a EndEvent>,
+            team_size: e.team_size,
+            config: e.config.clone(),
+        }
+    }
+}
+impl<'a, T> From<&Event<'a, T, ActiveEvent>> for Event<'a, T, PausedEvent>
+where
+    T: Player<'a>,
+{
+    fn from(e: &Event<'a, T, ActiveEvent>) -> Self {
+        Event {
+            id: e.id,
+            name: e.name.clone(),
+            logo: e.logo.clone(),
+            player_marker: PhantomData::<&'a T>,
+            state_marker: PhantomData::<&'a PausedEvent>,
+            team_size: e.team_size,
+            config: e.config.clone(),
+        }
+    }
+}
+impl<'a, T> From<&Event<'a, T, PausedEvent>> for Event<'a, T, ActiveEvent>
+where
+    T: Player<'a>,
+{
+    fn from(e: &Event<'a, T, PausedEvent>) -> Self {
+        Event {
+            id: e.id,
+            name: e.name.clone(),
+            logo: e.logo.clone(),
+            player_marker: PhantomData::<&'a T>,
+            state_marker: PhantomData::<&'a ActiveEvent>,
+            team_size: e.team_size,
+            config: e.config.clone(),
+        }
+    }
+}
+impl<'a, T> From<&Event<'a, T, PausedEvent>> for Event<'a, T, EndEvent>
+where
+    T: Player<'a>,
+{
+    fn from(e: &Event<'a, T, PausedEvent>) -> Self {
+        Event {
+            id: e.id,
+            name: e.name.clone(),
+            logo: e.logo.clone(),
+            player_marker: PhantomData::<&'a T>,
+            state_marker: PhantomData::<&'a EndEvent>,
+            team_size: e.team_size,
+            config: e.config.clone(),
+        }
+    }
+}
+
+impl<'a> Team<'a> {
+    pub fn new(name: Cow<'a, str>, logo: Option<Cow<'a, str>>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name,
+            logo,
+            members: Vec::new(),
+            score: 0,
+        }
+    }
+    pub fn get_team<'b>(team_id: &'b Uuid, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, Self> {
+        let id = team_id.to_string();
+        Box::pin(async move {
+            let team = sqlx::query_as::<_, Team>(
+                "SELECT t.id AS id,t.name AS name, t.score AS score,t.logo AS logo,
+                GROUP_CONCAT(tm.user_id,',') AS team_members 
+                FROM teams t JOIN team_members tm ON tm.team_id = t.id 
+                WHERE t.id = ? GROUP BY t.id",
+            )
+            .bind(id)
+            .fetch_one(db_pool)
+            .await?;
+            Ok(team)
+        })
+    }
+    pub fn delete_team<'b>(id: &'b Uuid, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, ()> {
+        let id = id.to_string();
+        Box::pin(async move {
+            let res = sqlx::query!("DELETE FROM teams WHERE id = ? ", id)
+                .execute(db_pool)
+                .await?;
+            if res.rows_affected().eq(&0) {
+                return Err(VaderError::TeamNotFound("No team found"));
+            }
+            Ok(())
+        })
+    }
+}
+impl<'a> User<'a> {
+    pub fn new(name: Cow<'a, str>, logo: Option<Cow<'a, str>>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name,
+            logo,
+            score: 0,
+        }
+    }
+    pub fn get_user<'b>(user_id: &'b Uuid, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, Self> {
+        let id = user_id.to_string();
+        Box::pin(async move {
+            let user =
+                sqlx::query_as::<_, User>("SELECT id,name,score,logo FROM users WHERE id = ?")
+                    .bind(id)
+                    .fetch_one(db_pool)
+                    .await?;
+            Ok(user)
+        })
+    }
+    pub fn delete_user<'b>(id: &'b Uuid, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, ()> {
+        let id = id.to_string();
+        Box::pin(async move {
+            let res = sqlx::query!("DELETE FROM users WHERE id = ? ", id)
+                .execute(db_pool)
+                .await?;
+            if res.rows_affected().eq(&0) {
+                return Err(VaderError::UserNotFound("No User found"));
+            }
+            Ok(())
+        })
+    }
+    pub fn get_all_user(db_pool: &SqlitePool) -> AsyncDbRes<'_, Vec<Self>> {
+        Box::pin(async move {
             let users = sqlx::query_as::<_, User>("SELECT id,name,score,logo from users")
                 .fetch_all(db_pool)
                 .await?;
@@ -496,9 +1728,9 @@ impl<'a> User<'a> {
         Box::pin(async move {
             let users = if param.is_empty() {
                 sqlx::query_as::<_, User>(
-                    "SELECT id,name,score,logo FROM users u 
-                     JOIN event_users ut ON ut.user_id=u.id 
-                     WHERE ut.event_id = ? 
+                    "SELECT u.id AS id,u.name AS name,es.score AS score,u.logo AS logo FROM users u 
+                     JOIN event_scores es ON es.participant_id=u.id 
+                     WHERE es.event_id = ? 
                      LIMIT ?",
                 )
                 .bind(&event_id)
@@ -507,9 +1739,9 @@ impl<'a> User<'a> {
                 .await?
             } else {
                 sqlx::query_as::<_, User>(
-                    "SELECT id,name,score,logo FROM users_fts u 
-                     JOIN event_users ut ON ut.user_id=u.id 
-                     WHERE ut.event_id = ? AND name MATCH  ? || '*'
+                    "SELECT u.id AS id,u.name AS name,es.score AS score,u.logo AS logo FROM users_fts u 
+                     JOIN event_scores es ON es.participant_id=u.id 
+                     WHERE es.event_id = ? AND name MATCH  ? || '*'
                      LIMIT ?",
                 )
                 .bind(&event_id)
@@ -525,10 +1757,11 @@ impl<'a> User<'a> {
 
 pub trait VbStateMarker {}
 impl VbStateMarker for ActiveEvent {}
+impl VbStateMarker for PausedEvent {}
 impl VbStateMarker for EndEvent {}
 
 pub trait VaderBoard<'a> {
-    type VbRes: Queriable;
+    type VbRes: Serialize;
     fn get_vboard<'b>(
         &'a self,
         count: u32,
@@ -538,8 +1771,101 @@ pub trait VaderBoard<'a> {
         'b: 'a;
 }
 
+// Event scores as of cutoff for participants not revealed yet, binds (cutoff,event_id)
+// score changes after the cutoff are taken back using score_ledger
+const VBOARD_SCORES: &str = "SELECT fz.event_id,fz.participant_id,fz.cutoff,
+    fz.score - IFNULL((SELECT SUM(l.delta) FROM score_ledger l 
+     WHERE l.event_id=fz.event_id AND l.participant_id=fz.participant_id AND l.created_at > fz.cutoff),0) AS score,
+    CASE WHEN fz.cutoff IS NULL THEN fz.updated_at ELSE 
+     (SELECT MAX(l.created_at) FROM score_ledger l 
+      WHERE l.event_id=fz.event_id AND l.participant_id=fz.participant_id AND l.delta != 0 AND l.created_at <= fz.cutoff) 
+    END AS updated_at
+    FROM (SELECT es.*,CASE WHEN rp.participant_id IS NULL THEN ? END AS cutoff FROM event_scores es 
+     LEFT JOIN revealed_participants rp ON rp.event_id=es.event_id AND rp.participant_id=es.participant_id
+     WHERE es.event_id=?) fz";
+
+// Vaderboard of teams / users table, binds (cutoff,event_id,count)
+fn vboard_sql(table: &str) -> String {
+    format!(
+        "SELECT p.id AS id,p.name AS name,fs.score AS score,p.logo AS logo,
+         (SELECT COUNT(*) FROM solves s 
+          WHERE s.event_id=fs.event_id AND s.participant_id=fs.participant_id 
+          AND (fs.cutoff IS NULL OR s.solved_at <= fs.cutoff)) AS solves,
+         fs.cutoff IS NOT NULL AS frozen
+         FROM {} p JOIN ({}) fs ON p.id=fs.participant_id
+         ORDER BY fs.score DESC,fs.updated_at IS NULL,fs.updated_at,p.name LIMIT ?",
+        table, VBOARD_SCORES
+    )
+}
+
+// Freeze time if vaderboard is currently frozen
+async fn vboard_cutoff(
+    event_id: &str,
+    config: &EventConfig,
+    conn: &mut SqliteConnection,
+) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    match config.freeze_at {
+        Some(freeze_at) if freeze_at <= Utc::now() => {
+            let revealed =
+                sqlx::query_scalar::<_, bool>("SELECT revealed FROM events WHERE id = ?")
+                    .bind(event_id)
+                    .fetch_one(conn)
+                    .await?;
+            Ok((!revealed).then_some(freeze_at))
+        }
+        _ => Ok(None),
+    }
+}
+
+// Ranked vaderboard rows along with the freeze cutoff they are scored at
+async fn ranked_vboard(
+    table: &str,
+    event_id: &str,
+    config: &EventConfig,
+    count: u32,
+    conn: &mut SqliteConnection,
+) -> Result<(Vec<VboardEntry<'static>>, Option<DateTime<Utc>>), sqlx::Error> {
+    let cutoff = vboard_cutoff(event_id, config, &mut *conn).await?;
+    let mut entries = sqlx::query_as::<_, VboardEntry>(&vboard_sql(table))
+        .bind(cutoff)
+        .bind(event_id)
+        .bind(count)
+        .fetch_all(conn)
+        .await?;
+    config.ranking.rank(&mut entries);
+    Ok((entries, cutoff))
+}
+
+// Vaderboard of an event from its config, the same for every state that has one
+async fn fetch_vboard(
+    table: &str,
+    event_id: &str,
+    config: &EventConfig,
+    count: u32,
+    db_pool: &SqlitePool,
+) -> Result<Vec<VboardEntry<'static>>, sqlx::Error> {
+    let mut conn = db_pool.acquire().await?;
+    let (mut entries, cutoff) = ranked_vboard(table, event_id, config, count, &mut conn).await?;
+    // marks breakdown would give away frozen scores
+    if config.show_breakdown && cutoff.is_none() {
+        add_breakdown(&mut entries, event_id, config.aggregation, db_pool).await?;
+    }
+    Ok(entries)
+}
+
+// Season placements follow the vaderboard, frozen scores count until revealed
+async fn save_placements(
+    table: &str,
+    event_id: &str,
+    config: &EventConfig,
+    conn: &mut SqliteConnection,
+) -> Result<(), VaderError<'static>> {
+    let (entries, _) = ranked_vboard(table, event_id, config, u32::MAX, &mut *conn).await?;
+    Season::record_placements(event_id, &entries, conn).await
+}
+
 impl<'a, S: VbStateMarker + EventState> VaderBoard<'a> for Event<'a, Team<'a>, S> {
-    type VbRes = TeamInfo<'a>;
+    type VbRes = VboardEntry<'a>;
     fn get_vboard<'b>(
         &'a self,
         count: u32,
@@ -550,23 +1876,13 @@ impl<'a, S: VbStateMarker + EventState> VaderBoard<'a> for Event<'a, Team<'a>, S
     {
         let event_id = self.id.to_string();
         Box::pin(async move {
-            let teams = sqlx::query_as::<_, TeamInfo>(
-                "SELECT id,name,score,logo FROM teams t  
-                 JOIN event_teams et ON t.id=et.team_id 
-                 WHERE et.event_id=?
-                 ORDER BY score DESC LIMIT ?",
-            )
-            .bind(&event_id)
-            .bind(count)
-            .fetch_all(db_pool)
-            .await?;
-            Ok(teams)
+            Ok(fetch_vboard("teams", &event_id, &self.config, count, db_pool).await?)
         })
     }
 }
 
 impl<'a, S: VbStateMarker + EventState> VaderBoard<'a> for Event<'a, User<'a>, S> {
-    type VbRes = User<'a>;
+    type VbRes = VboardEntry<'a>;
     fn get_vboard<'b>(
         &'a self,
         count: u32,
@@ -577,17 +1893,520 @@ impl<'a, S: VbStateMarker + EventState> VaderBoard<'a> for Event<'a, User<'a>, S
     {
         let event_id = self.id.to_string();
         Box::pin(async move {
-            let users = sqlx::query_as::<_, User>(
-                "SELECT id,name,score,logo FROM users u
-                 JOIN event_users eu ON u.id=eu.user_id
-                 WHERE eu.event_id=?
-                 ORDER BY score DESC LIMIT ?",
-            )
-            .bind(&event_id)
-            .bind(count)
-            .fetch_all(db_pool)
-            .await?;
-            Ok(users)
+            Ok(fetch_vboard("users", &event_id, &self.config, count, db_pool).await?)
         })
     }
 }
+
+impl VboardSource {
+    pub fn get_vboard<'a>(
+        &'a self,
+        count: u32,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Vec<VboardEntry<'static>>> {
+        let event_id = self.event_id.to_string();
+        Box::pin(async move {
+            Ok(fetch_vboard(self.table, &event_id, &self.config, count, db_pool).await?)
+        })
+    }
+}
+
+// Attach aggregated criteria marks of each vaderboard row
+async fn add_breakdown(
+    entries: &mut [VboardEntry<'_>],
+    event_id: &str,
+    aggregation: Aggregation,
+    db_pool: &SqlitePool,
+) -> Result<(), sqlx::Error> {
+    let rows = sqlx::query_as::<_, (String, String, i64)>(
+        "SELECT participant_id,criterion,marks FROM judge_marks WHERE event_id = ?",
+    )
+    .bind(event_id)
+    .fetch_all(db_pool)
+    .await?;
+    let mut marks: HashMap<String, BTreeMap<String, Vec<f64>>> = HashMap::new();
+    for (p_id, criterion, mark) in rows {
+        marks
+            .entry(p_id)
+            .or_default()
+            .entry(criterion)
+            .or_default()
+            .push(mark as f64);
+    }
+    for entry in entries.iter_mut() {
+        let p_marks = marks.remove(&entry.id.to_string()).unwrap_or_default();
+        entry.breakdown = Some(
+            p_marks
+                .into_iter()
+                .map(|(criterion, mut values)| (criterion, aggregation.aggregate(&mut values)))
+                .collect(),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::query_models::LedgerEntry;
+    use crate::models::v_models::Criterion;
+    use crate::services::test_services::{test_pool, user_event};
+
+    fn score_log(reason: Option<&str>) -> ScoreLog {
+        ScoreLog {
+            admin: Some(String::from("admin")),
+            reason: reason.map(String::from),
+        }
+    }
+
+    async fn score(event_id: Uuid, p_id: Uuid, db_pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar(
+            "SELECT score FROM event_scores WHERE event_id = ? AND participant_id = ?",
+        )
+        .bind(event_id.to_string())
+        .bind(p_id.to_string())
+        .fetch_one(db_pool)
+        .await
+        .unwrap()
+    }
+
+    fn entries(scores: &[i64]) -> Vec<VboardEntry<'static>> {
+        scores
+            .iter()
+            .map(|&score| VboardEntry {
+                rank: 0,
+                id: Uuid::new_v4(),
+                name: Cow::Borrowed(""),
+                score,
+                logo: None,
+                solves: 0,
+                breakdown: None,
+                frozen: false,
+            })
+            .collect()
+    }
+
+    fn ranks(mode: RankingMode, scores: &[i64]) -> Vec<u32> {
+        let mut entries = entries(scores);
+        mode.rank(&mut entries);
+        entries.iter().map(|e| e.rank).collect()
+    }
+
+    fn challenge(points: i64, min_points: Option<i64>, decay: Option<u32>) -> Challenge<'static> {
+        Challenge {
+            id: Uuid::new_v4(),
+            name: Cow::Borrowed("c"),
+            category: Cow::Borrowed("web"),
+            points,
+            min_points,
+            decay,
+            first_blood: 0,
+            flag: None,
+        }
+    }
+
+    #[test]
+    fn rank_ties() {
+        let scores = [10, 8, 8, 5];
+        assert_eq!(ranks(RankingMode::Standard, &scores), [1, 2, 2, 4]);
+        assert_eq!(ranks(RankingMode::Dense, &scores), [1, 2, 2, 3]);
+        assert_eq!(ranks(RankingMode::Ordinal, &scores), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn rank_all_tied() {
+        let scores = [3, 3, 3];
+        assert_eq!(ranks(RankingMode::Standard, &scores), [1, 1, 1]);
+        assert_eq!(ranks(RankingMode::Dense, &scores), [1, 1, 1]);
+        assert_eq!(ranks(RankingMode::Ordinal, &scores), [1, 2, 3]);
+        assert!(ranks(RankingMode::Standard, &[]).is_empty());
+    }
+
+    #[test]
+    fn aggregate_judges() {
+        assert_eq!(Aggregation::Mean.aggregate(&mut [4.0, 1.0, 7.0]), 4.0);
+        assert_eq!(Aggregation::Median.aggregate(&mut [9.0, 1.0, 4.0]), 4.0);
+        assert_eq!(
+            Aggregation::Median.aggregate(&mut [9.0, 1.0, 4.0, 2.0]),
+            3.0
+        );
+        assert_eq!(
+            Aggregation::TrimmedMean.aggregate(&mut [10.0, 0.0, 4.0, 6.0]),
+            5.0
+        );
+    }
+
+    #[test]
+    fn aggregate_few_judges() {
+        assert_eq!(Aggregation::Mean.aggregate(&mut []), 0.0);
+        assert_eq!(Aggregation::Median.aggregate(&mut []), 0.0);
+        // trimmed mean needs 3 judges to drop the extremes
+        assert_eq!(Aggregation::TrimmedMean.aggregate(&mut [2.0, 6.0]), 4.0);
+        assert_eq!(Aggregation::TrimmedMean.aggregate(&mut [5.0]), 5.0);
+    }
+
+    #[test]
+    fn criteria_weights() {
+        let criterion = |weight| Criterion {
+            name: String::from("innovation"),
+            weight,
+            max_marks: 10,
+        };
+        let config = EventConfig {
+            criteria: vec![criterion(30.0)],
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+        let marks = HashMap::from([(String::from("innovation"), 7)]);
+        assert_eq!(config.weighted_total(&marks), 21.0);
+        let config = EventConfig {
+            criteria: vec![criterion(0.3)],
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn challenge_decay() {
+        let c = challenge(500, Some(100), Some(10));
+        assert_eq!(c.value(0), 500);
+        assert_eq!(c.value(1), 500);
+        assert_eq!(c.value(6), 400);
+        assert_eq!(c.value(11), 100);
+        // stays at min_points past decay solves
+        assert_eq!(c.value(50), 100);
+    }
+
+    #[test]
+    fn challenge_without_decay() {
+        assert_eq!(challenge(300, None, None).value(20), 300);
+        assert_eq!(challenge(300, Some(100), None).value(20), 300);
+        assert_eq!(challenge(300, None, Some(5)).value(20), 300);
+    }
+
+    #[test]
+    fn clock_pause_resume() {
+        let now = Utc::now();
+        let clock = EventClock::Running {
+            ends_at: now + Duration::seconds(90),
+        };
+        assert_eq!(clock.remaining_ms(now), 90_000);
+        let paused = clock.paused(now + Duration::seconds(30));
+        assert_eq!(
+            paused,
+            EventClock::Paused {
+                remaining_ms: 60_000
+            }
+        );
+        // paused clock does not run
+        assert_eq!(paused.remaining_ms(now + Duration::hours(1)), 60_000);
+        let later = now + Duration::minutes(10);
+        assert_eq!(
+            paused.resumed(later),
+            EventClock::Running {
+                ends_at: later + Duration::seconds(60)
+            }
+        );
+    }
+
+    #[test]
+    fn clock_runs_out() {
+        let now = Utc::now();
+        let clock = EventClock::Running { ends_at: now };
+        assert_eq!(clock.remaining_ms(now + Duration::seconds(5)), 0);
+        assert_eq!(
+            clock.paused(now + Duration::seconds(5)),
+            EventClock::Paused { remaining_ms: 0 }
+        );
+    }
+
+    #[test]
+    fn clock_columns() {
+        let now = Utc::now();
+        assert_eq!(EventClock::from_columns(None, None), None);
+        assert_eq!(
+            EventClock::from_columns(None, Some(500)),
+            Some(EventClock::Paused { remaining_ms: 500 })
+        );
+        assert_eq!(
+            EventClock::from_columns(Some(now), Some(500)),
+            Some(EventClock::Running { ends_at: now })
+        );
+    }
+
+    #[test]
+    fn freeze_within_schedule() {
+        let start_at = Utc::now() + Duration::hours(1);
+        let config = |freeze_at| EventConfig {
+            start_at: Some(start_at),
+            duration: Some(3600),
+            freeze_at: Some(freeze_at),
+            ..Default::default()
+        };
+        assert!(config(start_at + Duration::minutes(45)).validate().is_ok());
+        assert!(config(start_at - Duration::minutes(1)).validate().is_err());
+        assert!(config(start_at + Duration::hours(1)).validate().is_err());
+        let config = EventConfig {
+            end_at: Some(start_at),
+            freeze_at: Some(start_at + Duration::minutes(1)),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn ledger_records_changes() {
+        let db_pool = test_pool().await;
+        let (event, ids) = user_event(EventConfig::default(), 2, &db_pool).await;
+        let event = event.start_event();
+        let log = score_log(Some("round 1"));
+        event
+            .update_score_by_id(&ids[0], 10, ScoreMode::Add, &log, &db_pool)
+            .await
+            .unwrap();
+        event
+            .update_score_by_id(&ids[0], -3, ScoreMode::Add, &log, &db_pool)
+            .await
+            .unwrap();
+        event
+            .update_score_by_id(&ids[1], 4, ScoreMode::Add, &log, &db_pool)
+            .await
+            .unwrap();
+        let ledger = LedgerEntry::get_participant_ledger(&event.id, &ids[0], &db_pool)
+            .await
+            .unwrap();
+        let rows: Vec<_> = ledger.iter().map(|e| (e.delta, e.total)).collect();
+        assert_eq!(rows, [(-3, 7), (10, 10)]);
+        assert!(
+            ledger
+                .iter()
+                .all(|e| e.admin.as_deref() == Some("admin")
+                    && e.reason.as_deref() == Some("round 1"))
+        );
+        // unknown participants leave no entry behind
+        assert!(event
+            .update_score_by_id(&Uuid::new_v4(), 5, ScoreMode::Add, &log, &db_pool)
+            .await
+            .is_err());
+        let ledger = LedgerEntry::get_event_ledger(&event.id, &db_pool)
+            .await
+            .unwrap();
+        assert_eq!(ledger.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn revert_restores_score() {
+        let db_pool = test_pool().await;
+        let (event, ids) = user_event(EventConfig::default(), 1, &db_pool).await;
+        let event = event.start_event();
+        let log = score_log(None);
+        for points in [10, 20, 30] {
+            event
+                .update_score_by_id(&ids[0], points, ScoreMode::Add, &log, &db_pool)
+                .await
+                .unwrap();
+        }
+        let ledger = LedgerEntry::get_event_ledger(&event.id, &db_pool)
+            .await
+            .unwrap();
+        let first = ledger.last().unwrap().id;
+        let target = RevertTarget::Entry { entry_id: first };
+        assert_eq!(
+            event.revert_score(&target, &log, &db_pool).await.unwrap(),
+            [first]
+        );
+        assert_eq!(score(event.id, ids[0], &db_pool).await, 50);
+        // an entry is reverted only once
+        assert!(event.revert_score(&target, &log, &db_pool).await.is_err());
+        let target = RevertTarget::Last {
+            participant_id: ids[0],
+            count: 5,
+        };
+        assert_eq!(
+            event
+                .revert_score(&target, &log, &db_pool)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(score(event.id, ids[0], &db_pool).await, 0);
+        let ledger = LedgerEntry::get_event_ledger(&event.id, &db_pool)
+            .await
+            .unwrap();
+        let reverts: Vec<_> = ledger.iter().filter_map(|e| e.revert_of).collect();
+        assert_eq!(reverts.len(), 3);
+        assert_eq!(
+            ledger.iter().find(|e| e.id == first).unwrap().reverted_by,
+            ledger
+                .iter()
+                .find(|e| e.revert_of == Some(first))
+                .map(|e| e.id)
+        );
+        assert_eq!(
+            ledger
+                .iter()
+                .find(|e| e.revert_of == Some(first))
+                .unwrap()
+                .reason
+                .as_deref(),
+            Some(format!("Revert of #{}", first).as_str())
+        );
+    }
+
+    fn update(id: Uuid, score: i64, mode: ScoreMode) -> ScoreUpdate {
+        ScoreUpdate {
+            id,
+            score,
+            mode,
+            reason: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_is_all_or_nothing() {
+        let db_pool = test_pool().await;
+        let (event, ids) = user_event(EventConfig::default(), 2, &db_pool).await;
+        let event = event.start_event();
+        let log = score_log(Some("batch"));
+        let updates = [
+            update(ids[0], 10, ScoreMode::Add),
+            update(ids[1], 5, ScoreMode::Add),
+        ];
+        event
+            .update_scores_by_id(&updates, &log, &db_pool)
+            .await
+            .unwrap();
+        // unknown participant at the end undoes the whole batch
+        let updates = [
+            update(ids[0], 10, ScoreMode::Add),
+            update(ids[1], 5, ScoreMode::Add),
+            update(Uuid::new_v4(), 1, ScoreMode::Add),
+        ];
+        assert!(event
+            .update_scores_by_id(&updates, &log, &db_pool)
+            .await
+            .is_err());
+        assert_eq!(score(event.id, ids[0], &db_pool).await, 10);
+        assert_eq!(score(event.id, ids[1], &db_pool).await, 5);
+        let ledger = LedgerEntry::get_event_ledger(&event.id, &db_pool)
+            .await
+            .unwrap();
+        assert_eq!(ledger.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn set_mode_within_bounds() {
+        let db_pool = test_pool().await;
+        let config = EventConfig {
+            min_score: Some(0),
+            max_score: Some(100),
+            ..Default::default()
+        };
+        let (event, ids) = user_event(config, 2, &db_pool).await;
+        let event = event.start_event();
+        let log = score_log(None);
+        event
+            .update_score_by_id(&ids[0], 40, ScoreMode::Add, &log, &db_pool)
+            .await
+            .unwrap();
+        event
+            .update_score_by_id(&ids[0], 75, ScoreMode::Set, &log, &db_pool)
+            .await
+            .unwrap();
+        assert_eq!(score(event.id, ids[0], &db_pool).await, 75);
+        let ledger = LedgerEntry::get_participant_ledger(&event.id, &ids[0], &db_pool)
+            .await
+            .unwrap();
+        assert_eq!((ledger[0].delta, ledger[0].total), (35, 75));
+        for (points, mode) in [
+            (30, ScoreMode::Add),
+            (101, ScoreMode::Set),
+            (-1, ScoreMode::Set),
+        ] {
+            assert!(event
+                .update_score_by_id(&ids[0], points, mode, &log, &db_pool)
+                .await
+                .is_err());
+        }
+        assert_eq!(score(event.id, ids[0], &db_pool).await, 75);
+        // a batch going out of bounds is rolled back as a whole
+        let updates = [
+            update(ids[1], 50, ScoreMode::Set),
+            update(ids[0], 50, ScoreMode::Add),
+        ];
+        assert!(event
+            .update_scores_by_id(&updates, &log, &db_pool)
+            .await
+            .is_err());
+        assert_eq!(score(event.id, ids[1], &db_pool).await, 0);
+    }
+
+    #[test]
+    fn score_bounds() {
+        let config = EventConfig {
+            min_score: Some(-10),
+            max_score: Some(10),
+            ..Default::default()
+        };
+        assert!(config.check_score(-10).is_ok());
+        assert!(config.check_score(10).is_ok());
+        assert!(config.check_score(-11).is_err());
+        assert!(config.check_score(11).is_err());
+        let config = EventConfig {
+            min_score: Some(10),
+            max_score: Some(0),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    fn board_rows(vboard: &[VboardEntry<'_>]) -> Vec<(Uuid, i64, bool)> {
+        vboard.iter().map(|e| (e.id, e.score, e.frozen)).collect()
+    }
+
+    #[tokio::test]
+    async fn frozen_board_hides_late_scores() {
+        let db_pool = test_pool().await;
+        let freeze_at = Utc::now() + Duration::milliseconds(300);
+        let config = EventConfig {
+            freeze_at: Some(freeze_at),
+            ..Default::default()
+        };
+        let (event, ids) = user_event(config, 2, &db_pool).await;
+        let event = event.start_event();
+        let log = score_log(None);
+        for (id, points) in [(ids[0], 10), (ids[1], 5)] {
+            event
+                .update_score_by_id(&id, points, ScoreMode::Add, &log, &db_pool)
+                .await
+                .unwrap();
+        }
+        // ledger is append only, wait for the freeze instead of backdating entries
+        let wait = (freeze_at - Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait + std::time::Duration::from_millis(10)).await;
+        event
+            .update_score_by_id(&ids[1], 20, ScoreMode::Add, &log, &db_pool)
+            .await
+            .unwrap();
+        let vboard = event.get_vboard(10, &db_pool).await.unwrap();
+        assert_eq!(board_rows(&vboard), [(ids[0], 10, true), (ids[1], 5, true)]);
+        // lowest frozen row is revealed first
+        assert_eq!(
+            event.reveal(RevealMode::Step, &db_pool).await.unwrap(),
+            Some(ids[1])
+        );
+        let vboard = event.get_vboard(10, &db_pool).await.unwrap();
+        assert_eq!(
+            board_rows(&vboard),
+            [(ids[1], 25, false), (ids[0], 10, true)]
+        );
+        assert_eq!(event.reveal(RevealMode::All, &db_pool).await.unwrap(), None);
+        let vboard = event.get_vboard(10, &db_pool).await.unwrap();
+        assert_eq!(
+            board_rows(&vboard),
+            [(ids[1], 25, false), (ids[0], 10, false)]
+        );
+        assert!(event.reveal(RevealMode::All, &db_pool).await.is_err());
+    }
+}
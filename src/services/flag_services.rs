@@ -0,0 +1,153 @@
+use std::time::{Duration, Instant};
+
+use actix_web::HttpRequest;
+use bcrypt::{hash, verify, DEFAULT_COST};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::command_models::ParticipantLogin;
+use crate::models::error_models::VaderError;
+use crate::models::flag_models::{AttemptKey, FailedAttempts, FlagThrottle};
+use crate::models::v_models::AsyncDbRes;
+
+impl FlagThrottle {
+    pub fn new(max_attempts: u32, window: Duration) -> Self {
+        FlagThrottle {
+            max_attempts,
+            window,
+            failures: Default::default(),
+        }
+    }
+    // Keys of a request, the participant if known and the peer ip
+    pub fn keys(p_id: Option<Uuid>, req: &HttpRequest) -> Vec<AttemptKey> {
+        p_id.map(AttemptKey::Participant)
+            .into_iter()
+            .chain(req.peer_addr().map(|addr| AttemptKey::Ip(addr.ip())))
+            .collect()
+    }
+    pub fn check<'a>(&self, keys: &[AttemptKey]) -> Result<(), VaderError<'a>> {
+        let failures = self.failures.lock().unwrap();
+        let blocked = keys.iter().any(|key| {
+            failures
+                .get(key)
+                .is_some_and(|f| f.since.elapsed() < self.window && f.count >= self.max_attempts)
+        });
+        if blocked {
+            return Err(VaderError::TooManyAttempts(
+                "Too many failed attempts, try again later",
+            ));
+        }
+        Ok(())
+    }
+    pub fn failed(&self, keys: &[AttemptKey]) {
+        let mut failures = self.failures.lock().unwrap();
+        let now = Instant::now();
+        failures.retain(|_, f| now.duration_since(f.since) < self.window);
+        for key in keys {
+            failures
+                .entry(*key)
+                .or_insert(FailedAttempts {
+                    count: 0,
+                    since: now,
+                })
+                .count += 1;
+        }
+    }
+}
+
+// Session key holding the participant logged in to an event
+pub fn participant_session_key(event_id: &Uuid) -> String {
+    format!("participant_{}", event_id)
+}
+
+impl ParticipantLogin {
+    // New key for a participant of the event, replaces any key issued before
+    pub fn issue_key<'a>(
+        event_id: &'a Uuid,
+        p_id: &'a Uuid,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, String> {
+        let event_id = event_id.to_string();
+        let p_id = p_id.to_string();
+        Box::pin(async move {
+            let key = Uuid::new_v4().simple().to_string();
+            let key_clone = key.clone();
+            let key_hash = actix_web::web::block(move || hash(key_clone, DEFAULT_COST)).await??;
+            let res = sqlx::query!(
+                "UPDATE event_scores SET key_hash = ? WHERE event_id = ? AND participant_id = ?",
+                key_hash,
+                event_id,
+                p_id
+            )
+            .execute(db_pool)
+            .await?;
+            if res.rows_affected() == 0 {
+                return Err(VaderError::ParticipantAuth(
+                    "Participant is not part of the Event",
+                ));
+            }
+            Ok(key)
+        })
+    }
+    pub fn verify_key<'a>(
+        self,
+        event_id: &'a Uuid,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, bool> {
+        let event_id = event_id.to_string();
+        Box::pin(async move {
+            let p_id = self.id.to_string();
+            let key_hash = sqlx::query_scalar!(
+                "SELECT key_hash FROM event_scores WHERE event_id = ? AND participant_id = ?",
+                event_id,
+                p_id
+            )
+            .fetch_optional(db_pool)
+            .await?;
+            match key_hash {
+                Some(Some(key_hash)) => {
+                    Ok(actix_web::web::block(move || verify(self.key, &key_hash)).await??)
+                }
+                _ => Ok(false),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use super::*;
+
+    #[test]
+    fn throttle_blocks_after_max_attempts() {
+        let throttle = FlagThrottle::new(2, Duration::from_secs(60));
+        let p_id = Uuid::new_v4();
+        let keys = [
+            AttemptKey::Participant(p_id),
+            AttemptKey::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+        ];
+        throttle.failed(&keys);
+        assert!(throttle.check(&keys).is_ok());
+        throttle.failed(&keys);
+        assert!(throttle.check(&keys).is_err());
+        // same ip is blocked for another participant too
+        let other = [
+            AttemptKey::Participant(Uuid::new_v4()),
+            AttemptKey::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+        ];
+        assert!(throttle.check(&other).is_err());
+        assert!(throttle
+            .check(&[AttemptKey::Participant(Uuid::new_v4())])
+            .is_ok());
+    }
+
+    #[test]
+    fn throttle_window_expires() {
+        let throttle = FlagThrottle::new(1, Duration::ZERO);
+        let keys = [AttemptKey::Participant(Uuid::new_v4())];
+        throttle.failed(&keys);
+        assert!(throttle.check(&keys).is_ok());
+    }
+}
@@ -1,4 +1,12 @@
+pub mod bracket_services;
 pub mod event_services;
+pub mod flag_services;
 pub mod query_services;
+pub mod replay_services;
+pub mod schedule_services;
+pub mod season_services;
+#[cfg(test)]
+pub mod test_services;
 pub mod v_middlewares;
 pub mod vb_services;
+pub mod ws_services;
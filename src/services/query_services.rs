@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::marker::PhantomData;
 
 use actix::{
@@ -5,16 +6,24 @@ use actix::{
     WrapFuture,
 };
 use actix_web_actors::ws;
+use chrono::Utc;
 use sqlx::sqlite::SqliteRow;
 use sqlx::{FromRow, Row, SqlitePool};
 use uuid::Uuid;
 
+use crate::models::bracket_models::BracketFormat;
 use crate::models::error_models::VaderError;
 use crate::models::query_models::{
-    CurEventFts, CurEventFtsWrapper, CurFtsConnect, CurFtsDisconnect, CurFtsServer, CurFtsStop,
-    EventInfo, EventQueryBuilder, EventType, FtsQuery, TeamFtsOpt, TeamInfo,
+    ChallengeInfo, CurEventFts, CurEventFtsMarker, CurEventFtsWrapper, CurFtsConnect,
+    CurFtsDisconnect, CurFtsServer, CurFtsStop, EventInfo, EventQueryBuilder, EventType, FtsQuery,
+    JudgeMarks, LedgerEntry, TeamFtsOpt, TeamInfo, VboardEntry,
 };
-use crate::models::v_models::{AsyncDbRes, Event, EventState, Player, Team, User};
+use crate::models::v_models::{
+    ActiveEvent, Aggregation, AppState, AsyncDbRes, Challenge, EndEvent, Event, EventConfig,
+    EventState, NewEvent, PausedEvent, Player, RankingMode, Team, User,
+};
+use crate::models::wrapper_models::{EventStateWrapper, EventWrapper};
+use crate::models::ws_models::{WsConn, WsHeartbeat};
 
 impl FromRow<'_, SqliteRow> for Team<'_> {
     fn from_row(row: &'_ SqliteRow) -> Result<Self, sqlx::Error> {
@@ -82,6 +91,119 @@ impl FromRow<'_, SqliteRow> for TeamInfo<'_> {
     }
 }
 
+impl FromRow<'_, SqliteRow> for VboardEntry<'_> {
+    fn from_row(row: &'_ SqliteRow) -> Result<Self, sqlx::Error> {
+        let id: Uuid = Uuid::parse_str(row.get("id")).map_err(|_e| sqlx::Error::ColumnDecode {
+            index: "0".to_string(),
+            source: Box::new(VaderError::SqlxFieldError("Error decoding Participant Id")),
+        })?;
+        let name: String = row.get("name");
+        let score: i64 = row.get("score");
+        let logo: Option<String> = row.get("logo");
+        Ok(VboardEntry {
+            rank: 0,
+            id,
+            name: name.into(),
+            logo: logo.map(|s| s.into()),
+            score,
+            solves: row.get("solves"),
+            breakdown: None,
+            frozen: row.get("frozen"),
+        })
+    }
+}
+
+impl VboardEntry<'_> {
+    pub fn into_owned(self) -> VboardEntry<'static> {
+        VboardEntry {
+            name: Cow::Owned(self.name.into_owned()),
+            logo: self.logo.map(|l| Cow::Owned(l.into_owned())),
+            ..self
+        }
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Challenge<'_> {
+    fn from_row(row: &'_ SqliteRow) -> Result<Self, sqlx::Error> {
+        let id: Uuid = Uuid::parse_str(row.get("id")).map_err(|_e| sqlx::Error::ColumnDecode {
+            index: "0".to_string(),
+            source: Box::new(VaderError::SqlxFieldError("Error decoding Challenge Id")),
+        })?;
+        let name: String = row.get("name");
+        let category: String = row.get("category");
+        Ok(Challenge {
+            id,
+            name: name.into(),
+            category: category.into(),
+            points: row.get("points"),
+            min_points: row.get("min_points"),
+            decay: row.get("decay"),
+            first_blood: row.get("first_blood"),
+            flag: row.get("flag"),
+        })
+    }
+}
+
+impl FromRow<'_, SqliteRow> for ChallengeInfo<'_> {
+    fn from_row(row: &'_ SqliteRow) -> Result<Self, sqlx::Error> {
+        let challenge = Challenge::from_row(row)?;
+        let solves: u32 = row.get("solves");
+        let value = challenge.value(solves.into());
+        Ok(ChallengeInfo {
+            challenge,
+            solves,
+            value,
+        })
+    }
+}
+
+impl FromRow<'_, SqliteRow> for JudgeMarks<'_> {
+    fn from_row(row: &'_ SqliteRow) -> Result<Self, sqlx::Error> {
+        let participant_id: Uuid =
+            Uuid::parse_str(row.get("participant_id")).map_err(|_e| sqlx::Error::ColumnDecode {
+                index: "0".to_string(),
+                source: Box::new(VaderError::SqlxFieldError("Error decoding Participant Id")),
+            })?;
+        let judge: String = row.get("judge");
+        let criterion: String = row.get("criterion");
+        Ok(JudgeMarks {
+            participant_id,
+            judge: judge.into(),
+            criterion: criterion.into(),
+            marks: row.get("marks"),
+        })
+    }
+}
+
+impl FromRow<'_, SqliteRow> for LedgerEntry<'_> {
+    fn from_row(row: &'_ SqliteRow) -> Result<Self, sqlx::Error> {
+        let event_id: Uuid =
+            Uuid::parse_str(row.get("event_id")).map_err(|_e| sqlx::Error::ColumnDecode {
+                index: "0".to_string(),
+                source: Box::new(VaderError::SqlxFieldError("Error decoding Event Id")),
+            })?;
+        let participant_id: Uuid =
+            Uuid::parse_str(row.get("participant_id")).map_err(|_e| sqlx::Error::ColumnDecode {
+                index: "0".to_string(),
+                source: Box::new(VaderError::SqlxFieldError("Error decoding Participant Id")),
+            })?;
+        let admin: Option<String> = row.get("admin");
+        let reason: Option<String> = row.get("reason");
+        Ok(LedgerEntry {
+            id: row.get("id"),
+            event_id,
+            participant_id,
+            delta: row.get("delta"),
+            total: row.get("total"),
+            admin: admin.map(|s| s.into()),
+            reason: reason.map(|s| s.into()),
+            created_at: row.get("created_at"),
+            reverted_by: row.get("reverted_by"),
+            revert_of: row.get("revert_of"),
+        })
+    }
+}
+
 impl<'a, T, U> FromRow<'_, SqliteRow> for Event<'a, T, U>
 where
     T: Player<'a>,
@@ -97,17 +219,82 @@ where
         let name: String = row.get("name");
         let logo: Option<String> = row.get("logo");
         let team_size: Option<u32> = row.get("team_size");
+        let criteria: Option<String> = row.get("criteria");
+        let criteria = match criteria {
+            Some(criteria) => {
+                serde_json::from_str(&criteria).map_err(|_e| sqlx::Error::ColumnDecode {
+                    index: "0".to_string(),
+                    source: Box::new(VaderError::SqlxFieldError("Error decoding Event Criteria")),
+                })?
+            }
+            None => Vec::new(),
+        };
+        let aggregation: String = row.get("aggregation");
+        let aggregation =
+            Aggregation::from_column(&aggregation).ok_or_else(|| sqlx::Error::ColumnDecode {
+                index: "0".to_string(),
+                source: Box::new(VaderError::SqlxFieldError(
+                    "Error decoding Event Aggregation",
+                )),
+            })?;
+        let ranking: String = row.get("ranking");
+        let ranking =
+            RankingMode::from_column(&ranking).ok_or_else(|| sqlx::Error::ColumnDecode {
+                index: "0".to_string(),
+                source: Box::new(VaderError::SqlxFieldError("Error decoding Event Ranking")),
+            })?;
+        let bracket = match row.get::<Option<String>, _>("bracket") {
+            Some(bracket) => Some(BracketFormat::from_column(&bracket).ok_or_else(|| {
+                sqlx::Error::ColumnDecode {
+                    index: "0".to_string(),
+                    source: Box::new(VaderError::SqlxFieldError("Error decoding Event Bracket")),
+                }
+            })?),
+            None => None,
+        };
+        let config = EventConfig {
+            min_score: row.get("min_score"),
+            max_score: row.get("max_score"),
+            criteria,
+            show_breakdown: row.get("show_breakdown"),
+            aggregation,
+            ranking,
+            bracket,
+            freeze_at: row.get("freeze_at"),
+            duration: row.get::<Option<i64>, _>("duration").map(|d| d as u64),
+            end_at: row.get("end_at"),
+            start_at: row.get("start_at"),
+        };
 
         Ok(Event {
             id,
             name: name.into(),
             logo: logo.map(|s| s.into()),
+            config,
             player_marker: PhantomData::<&'a T>,
             state_marker: PhantomData::<&'a U>,
             team_size,
         })
     }
 }
+impl<'a, T> FromRow<'_, SqliteRow> for EventStateWrapper<'a, T>
+where
+    T: Player<'a>,
+{
+    fn from_row(row: &'_ SqliteRow) -> Result<EventStateWrapper<'a, T>, sqlx::Error> {
+        let state: String = row.get("state");
+        match state.as_str() {
+            NewEvent::STATE => Ok(Self::New(Event::from_row(row)?)),
+            ActiveEvent::STATE => Ok(Self::Active(Event::from_row(row)?)),
+            PausedEvent::STATE => Ok(Self::Paused(Event::from_row(row)?)),
+            EndEvent::STATE => Ok(Self::End(Event::from_row(row)?)),
+            _ => Err(sqlx::Error::ColumnDecode {
+                index: "0".to_string(),
+                source: Box::new(VaderError::SqlxFieldError("Error decoding Event State")),
+            }),
+        }
+    }
+}
 impl<'a, 'b> FromRow<'a, SqliteRow> for EventInfo<'b> {
     fn from_row(row: &'a SqliteRow) -> Result<EventInfo<'b>, sqlx::Error> {
         let id_str: String = row.get("id");
@@ -171,6 +358,172 @@ impl EventInfo<'_> {
         })
     }
 }
+const EVENT_COLUMNS: &str = "id,name,logo,event_type,team_size,min_score,max_score,criteria,show_breakdown,aggregation,ranking,bracket,freeze_at,duration,end_at,start_at,state";
+
+impl EventWrapper<'static> {
+    fn from_event_row(row: &SqliteRow) -> Result<Self, VaderError<'static>> {
+        let event_type: String = row.get("event_type");
+        match event_type.as_str() {
+            "team_event" => Ok(Self::TeamEvent(EventStateWrapper::from_row(row)?)),
+            "user_event" => Ok(Self::UserEvent(EventStateWrapper::from_row(row)?)),
+            _ => Err(VaderError::SqlxFieldError("Error decoding EventType")),
+        }
+    }
+    // Rebuild Added/Active/Paused events from db on server restart : )
+    pub fn restore_all(db_pool: &SqlitePool) -> AsyncDbRes<'_, Vec<Self>> {
+        Box::pin(async move {
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM events WHERE state IN (?,?,?) ORDER BY created_at DESC",
+                EVENT_COLUMNS
+            ))
+            .bind(NewEvent::STATE)
+            .bind(ActiveEvent::STATE)
+            .bind(PausedEvent::STATE)
+            .fetch_all(db_pool)
+            .await?;
+            rows.iter().map(Self::from_event_row).collect()
+        })
+    }
+    // Ended events are left out of restore_all, rebuilt one at a time when their board is asked for
+    pub fn restore_ended(event_id: Uuid, db_pool: &SqlitePool) -> AsyncDbRes<'_, Option<Self>> {
+        Box::pin(async move {
+            let row = sqlx::query(&format!(
+                "SELECT {} FROM events WHERE id = ? AND state = ?",
+                EVENT_COLUMNS
+            ))
+            .bind(event_id.to_string())
+            .bind(EndEvent::STATE)
+            .fetch_optional(db_pool)
+            .await?;
+            row.as_ref().map(Self::from_event_row).transpose()
+        })
+    }
+}
+impl AppState {
+    // Whether the event is in the registry, loading it back first if it ended before a restart
+    pub async fn load_event<'a>(
+        &self,
+        event_id: Uuid,
+        db_pool: &'a SqlitePool,
+    ) -> Result<bool, VaderError<'a>> {
+        if self.events.lock().await.contains_key(&event_id) {
+            return Ok(true);
+        }
+        match EventWrapper::restore_ended(event_id, db_pool).await? {
+            Some(event) => {
+                self.events.lock().await.entry(event_id).or_insert(event);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+impl ChallengeInfo<'_> {
+    // Solves after the vaderboard freeze are only counted for revealed participants,
+    // same cutoff as the frozen vaderboard
+    pub fn get_event_challenges<'a>(
+        event_id: &'a Uuid,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Vec<Self>> {
+        let event_id = event_id.to_string();
+        Box::pin(async move {
+            let challenges = sqlx::query_as::<_, ChallengeInfo>(
+                "WITH fz AS (SELECT CASE WHEN freeze_at <= ? AND NOT revealed THEN freeze_at END AS cutoff 
+                  FROM events WHERE id = ?) 
+                 SELECT c.id AS id,c.name AS name,c.category AS category,c.points AS points,
+                 c.min_points AS min_points,c.decay AS decay,c.first_blood AS first_blood,c.flag AS flag,
+                 (SELECT COUNT(*) FROM solves s WHERE s.challenge_id = c.id 
+                  AND ((SELECT cutoff FROM fz) IS NULL OR s.solved_at <= (SELECT cutoff FROM fz) 
+                   OR EXISTS (SELECT 1 FROM revealed_participants rp 
+                    WHERE rp.event_id = s.event_id AND rp.participant_id = s.participant_id))) AS solves 
+                 FROM challenges c WHERE c.event_id = ? 
+                 ORDER BY c.category,c.points,c.name",
+            )
+            .bind(Utc::now())
+            .bind(&event_id)
+            .bind(&event_id)
+            .fetch_all(db_pool)
+            .await?;
+            Ok(challenges)
+        })
+    }
+}
+
+impl JudgeMarks<'_> {
+    pub fn get_event_marks<'a>(
+        event_id: &'a Uuid,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Vec<Self>> {
+        let event_id = event_id.to_string();
+        Box::pin(async move {
+            let marks = sqlx::query_as::<_, JudgeMarks>(
+                "SELECT participant_id,judge,criterion,marks FROM judge_marks 
+                 WHERE event_id = ? ORDER BY participant_id,judge,criterion",
+            )
+            .bind(&event_id)
+            .fetch_all(db_pool)
+            .await?;
+            Ok(marks)
+        })
+    }
+    pub fn get_participant_marks<'a>(
+        event_id: &'a Uuid,
+        participant_id: &'a Uuid,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Vec<Self>> {
+        let event_id = event_id.to_string();
+        let participant_id = participant_id.to_string();
+        Box::pin(async move {
+            let marks = sqlx::query_as::<_, JudgeMarks>(
+                "SELECT participant_id,judge,criterion,marks FROM judge_marks 
+                 WHERE event_id = ? AND participant_id = ? ORDER BY judge,criterion",
+            )
+            .bind(&event_id)
+            .bind(&participant_id)
+            .fetch_all(db_pool)
+            .await?;
+            Ok(marks)
+        })
+    }
+}
+
+impl LedgerEntry<'_> {
+    pub fn get_event_ledger<'a>(
+        event_id: &'a Uuid,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Vec<Self>> {
+        let event_id = event_id.to_string();
+        Box::pin(async move {
+            let entries = sqlx::query_as::<_, LedgerEntry>(
+                "SELECT id,event_id,participant_id,delta,total,admin,reason,created_at,reverted_by,revert_of 
+                 FROM score_ledger WHERE event_id = ? ORDER BY id DESC",
+            )
+            .bind(&event_id)
+            .fetch_all(db_pool)
+            .await?;
+            Ok(entries)
+        })
+    }
+    pub fn get_participant_ledger<'a>(
+        event_id: &'a Uuid,
+        participant_id: &'a Uuid,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Vec<Self>> {
+        let event_id = event_id.to_string();
+        let participant_id = participant_id.to_string();
+        Box::pin(async move {
+            let entries = sqlx::query_as::<_, LedgerEntry>(
+                "SELECT id,event_id,participant_id,delta,total,admin,reason,created_at,reverted_by,revert_of 
+                 FROM score_ledger WHERE event_id = ? AND participant_id = ? ORDER BY id DESC",
+            )
+            .bind(&event_id)
+            .bind(&participant_id)
+            .fetch_all(db_pool)
+            .await?;
+            Ok(entries)
+        })
+    }
+}
 impl<'a> TeamInfo<'a> {
     pub fn get_all_team_info(db_pool: &SqlitePool) -> AsyncDbRes<'_, Vec<Self>> {
         Box::pin(async move {
@@ -192,9 +545,9 @@ impl<'a> TeamInfo<'a> {
         Box::pin(async move {
             let teams = if param.is_empty() {
                 sqlx::query_as::<_, TeamInfo>(
-                    "SELECT id,name,score,logo FROM teams t 
-                     JOIN event_teams et ON et.team_id=t.id 
-                     WHERE et.event_id = ?
+                    "SELECT t.id AS id,t.name AS name,es.score AS score,t.logo AS logo FROM teams t 
+                     JOIN event_scores es ON es.participant_id=t.id 
+                     WHERE es.event_id = ?
                      ORDER by t.created_at DESC 
                      LIMIT ?
                     ",
@@ -205,9 +558,9 @@ impl<'a> TeamInfo<'a> {
                 .await?
             } else {
                 sqlx::query_as::<_, TeamInfo>(
-                    "SELECT id,name,score,logo FROM teams_fts t 
-                    JOIN event_teams et ON et.team_id=t.id 
-                    WHERE et.event_id = ? AND name MATCH  ? || '*'
+                    "SELECT t.id AS id,t.name AS name,es.score AS score,t.logo AS logo FROM teams_fts t 
+                    JOIN event_scores es ON es.participant_id=t.id 
+                    WHERE es.event_id = ? AND name MATCH  ? || '*'
                     LIMIT ?",
                 )
                 .bind(&event_id)
@@ -378,11 +731,32 @@ impl Queriable for EventInfo<'_> {
 #[rtype(result = "()")]
 struct FtsQueryRes(String);
 
+impl<'a, T: Queriable> WsHeartbeat for FtsQuery<'a, T>
+where
+    Self: Actor<Context = ws::WebsocketContext<Self>>,
+{
+    fn conn(&mut self) -> &mut WsConn {
+        &mut self.conn
+    }
+}
+
+impl<'a, T: CurEventFtsMarker> WsHeartbeat for CurEventFts<'a, T>
+where
+    Self: Actor<Context = ws::WebsocketContext<Self>>,
+{
+    fn conn(&mut self) -> &mut WsConn {
+        &mut self.conn
+    }
+}
+
 impl<'a> Actor for FtsQuery<'a, TeamInfo<'_>>
 where
     'a: 'static,
 {
     type Context = ws::WebsocketContext<Self>;
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.start_heartbeat(ctx);
+    }
 }
 
 impl<'a> Handler<FtsQueryRes> for FtsQuery<'a, TeamInfo<'_>>
@@ -401,6 +775,9 @@ where
     'a: 'static,
 {
     fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        if !self.beat(&msg, ctx) {
+            return;
+        }
         use ws::Message::*;
         let pool = self.db_pool.clone();
         let addr = ctx.address();
@@ -430,6 +807,9 @@ where
     'a: 'static,
 {
     type Context = ws::WebsocketContext<Self>;
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.start_heartbeat(ctx);
+    }
 }
 
 impl<'a> Handler<FtsQueryRes> for FtsQuery<'a, User<'_>>
@@ -448,6 +828,9 @@ where
     'a: 'static,
 {
     fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        if !self.beat(&msg, ctx) {
+            return;
+        }
         use ws::Message::*;
         let pool = self.db_pool.clone();
         let addr = ctx.address();
@@ -477,6 +860,9 @@ where
     'a: 'static,
 {
     type Context = ws::WebsocketContext<Self>;
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.start_heartbeat(ctx);
+    }
 }
 
 impl<'a> Handler<FtsQueryRes> for FtsQuery<'a, EventInfo<'_>>
@@ -495,6 +881,9 @@ where
     'a: 'static,
 {
     fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        if !self.beat(&msg, ctx) {
+            return;
+        }
         use ws::Message::*;
         let pool = self.db_pool.clone();
         let addr = ctx.address();
@@ -527,6 +916,7 @@ where
     fn started(&mut self, ctx: &mut Self::Context) {
         let addr = ctx.address();
         self.addr = Some(addr.clone());
+        self.start_heartbeat(ctx);
         self.srv_addr
             .do_send(CurFtsConnect(CurEventFtsWrapper(actix_web::Either::Left(
                 addr,
@@ -545,6 +935,9 @@ where
     'a: 'static,
 {
     fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        if !self.beat(&msg, ctx) {
+            return;
+        }
         use ws::Message::*;
         let pool = self.db_pool.clone();
         let addr = ctx.address();
@@ -602,6 +995,7 @@ where
     fn started(&mut self, ctx: &mut Self::Context) {
         let addr = ctx.address();
         self.addr = Some(addr.clone());
+        self.start_heartbeat(ctx);
         self.srv_addr
             .do_send(CurFtsConnect(CurEventFtsWrapper(actix_web::Either::Right(
                 addr,
@@ -620,6 +1014,9 @@ where
     'a: 'static,
 {
     fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        if !self.beat(&msg, ctx) {
+            return;
+        }
         use ws::Message::*;
         let pool = self.db_pool.clone();
         let addr = ctx.address();
@@ -660,14 +1057,23 @@ where
     }
 }
 
+impl CurFtsServer<'_> {
+    pub fn remove_stale(&mut self) {
+        self.cfts_addr.retain(|cfts_wrapper| match &cfts_wrapper.0 {
+            actix_web::Either::Left(addr) => addr.connected(),
+            actix_web::Either::Right(addr) => addr.connected(),
+        });
+    }
+}
+
 impl Handler<CurFtsStop> for CurFtsServer<'_> {
     type Result = ();
-    fn handle(&mut self, _msg: CurFtsStop, _ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: CurFtsStop, _ctx: &mut Self::Context) -> Self::Result {
         self.cfts_addr
             .iter()
             .for_each(|cfts_wrapper| match &cfts_wrapper.0 {
-                actix_web::Either::Left(addr) => addr.do_send(CurFtsStop),
-                actix_web::Either::Right(addr) => addr.do_send(CurFtsStop),
+                actix_web::Either::Left(addr) => addr.do_send(CurFtsStop(msg.0)),
+                actix_web::Either::Right(addr) => addr.do_send(CurFtsStop(msg.0)),
             });
     }
 }
@@ -691,10 +1097,13 @@ where
     'a: 'static,
 {
     type Result = ();
-    fn handle(&mut self, _msg: CurFtsStop, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: CurFtsStop, ctx: &mut Self::Context) -> Self::Result {
+        if msg.0 != self.event_id {
+            return;
+        }
         ctx.close(Some(ws::CloseReason {
             code: ws::CloseCode::Normal,
-            description: Some("The Current Event ended".to_string()),
+            description: Some("The Event ended".to_string()),
         }));
         ctx.stop();
     }
@@ -704,10 +1113,13 @@ where
     'a: 'static,
 {
     type Result = ();
-    fn handle(&mut self, _msg: CurFtsStop, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: CurFtsStop, ctx: &mut Self::Context) -> Self::Result {
+        if msg.0 != self.event_id {
+            return;
+        }
         ctx.close(Some(ws::CloseReason {
             code: ws::CloseCode::Normal,
-            description: Some("The Current Event ended".to_string()),
+            description: Some("The Event ended".to_string()),
         }));
         ctx.stop();
     }
@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use actix::{AsyncContext, StreamHandler};
+use actix_web_actors::ws;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::error_models::VaderError;
+use crate::models::query_models::{VboardEntry, VboardMsg, VboardSub, VboardWindow};
+use crate::models::replay_models::{ReplayReq, ScoreChange, Timeline, VboardReplay};
+use crate::models::v_models::RankingMode;
+use crate::models::ws_models::{WsConn, WsHeartbeat};
+
+// Wait before the first change so clients can render the empty board
+const REPLAY_START: Duration = Duration::from_secs(1);
+
+impl Timeline {
+    pub async fn load(event_id: Uuid, db_pool: &SqlitePool) -> Result<Self, VaderError<'static>> {
+        let id = event_id.to_string();
+        let (ranking, freeze_at, revealed) =
+            sqlx::query_as::<_, (String, Option<DateTime<Utc>>, bool)>(
+                "SELECT ranking,freeze_at,revealed FROM events WHERE id = ?",
+            )
+            .bind(&id)
+            .fetch_optional(db_pool)
+            .await?
+            .ok_or(VaderError::EventNotFound("No event found"))?;
+        let ranking = RankingMode::from_column(&ranking)
+            .ok_or(VaderError::SqlxFieldError("Error decoding Event Ranking"))?;
+        // frozen scores stay hidden in replays too
+        let cutoff = match freeze_at {
+            Some(freeze_at) if !revealed && freeze_at <= Utc::now() => freeze_at,
+            _ => Utc::now(),
+        };
+        let participants = sqlx::query_as::<_, VboardEntry>(
+            "SELECT es.participant_id AS id,COALESCE(t.name,u.name,'') AS name,0 AS score,
+             COALESCE(t.logo,u.logo) AS logo,0 AS solves,0 AS frozen
+             FROM event_scores es
+             LEFT JOIN teams t ON t.id = es.participant_id
+             LEFT JOIN users u ON u.id = es.participant_id
+             WHERE es.event_id = ?",
+        )
+        .bind(&id)
+        .fetch_all(db_pool)
+        .await?
+        .into_iter()
+        .map(VboardEntry::into_owned)
+        .collect();
+        let changes = sqlx::query_as::<_, (DateTime<Utc>, String, i64, i64)>(
+            "SELECT created_at,participant_id,delta,total FROM score_ledger
+             WHERE event_id = ? AND created_at <= ? ORDER BY created_at,id",
+        )
+        .bind(&id)
+        .bind(cutoff)
+        .fetch_all(db_pool)
+        .await?
+        .into_iter()
+        .map(|(at, p_id, delta, total)| {
+            Ok(ScoreChange {
+                at,
+                participant_id: parse_id(&p_id)?,
+                delta,
+                total,
+            })
+        })
+        .collect::<Result<Vec<_>, VaderError>>()?;
+        let solves = sqlx::query_as::<_, (DateTime<Utc>, String)>(
+            "SELECT solved_at,participant_id FROM solves
+             WHERE event_id = ? AND solved_at <= ? ORDER BY solved_at",
+        )
+        .bind(&id)
+        .bind(cutoff)
+        .fetch_all(db_pool)
+        .await?
+        .into_iter()
+        .map(|(at, p_id)| Ok((at, parse_id(&p_id)?)))
+        .collect::<Result<Vec<_>, VaderError>>()?;
+        let mut steps: Vec<DateTime<Utc>> = changes
+            .iter()
+            .map(|c| c.at)
+            .chain(solves.iter().map(|s| s.0))
+            .collect();
+        steps.sort();
+        steps.dedup();
+        Ok(Timeline {
+            event_id,
+            ranking,
+            participants,
+            changes,
+            solves,
+            steps,
+        })
+    }
+
+    // Ranked vaderboard with every change up to at applied
+    pub fn board_at(&self, at: Option<DateTime<Utc>>) -> Vec<VboardEntry<'static>> {
+        let applied = |t: &DateTime<Utc>| at.is_some_and(|at| *t <= at);
+        let mut scores: HashMap<Uuid, (i64, Option<DateTime<Utc>>)> = HashMap::new();
+        for change in self.changes.iter().take_while(|c| applied(&c.at)) {
+            let score = scores.entry(change.participant_id).or_default();
+            score.0 = change.total;
+            if change.delta != 0 {
+                score.1 = Some(change.at);
+            }
+        }
+        let mut solves: HashMap<Uuid, u32> = HashMap::new();
+        for (_, p_id) in self.solves.iter().take_while(|s| applied(&s.0)) {
+            *solves.entry(*p_id).or_default() += 1;
+        }
+        let mut rows: Vec<(Option<DateTime<Utc>>, VboardEntry<'static>)> = self
+            .participants
+            .iter()
+            .map(|p| {
+                let (score, updated_at) = scores.get(&p.id).copied().unwrap_or_default();
+                let mut entry = p.clone();
+                entry.score = score;
+                entry.solves = solves.get(&p.id).copied().unwrap_or_default();
+                (updated_at, entry)
+            })
+            .collect();
+        // same order as the live vaderboard query
+        rows.sort_by(|(a_at, a), (b_at, b)| {
+            b.score
+                .cmp(&a.score)
+                .then(a_at.is_none().cmp(&b_at.is_none()))
+                .then(a_at.cmp(b_at))
+                .then(a.name.cmp(&b.name))
+        });
+        let mut entries: Vec<VboardEntry<'static>> = rows.into_iter().map(|r| r.1).collect();
+        self.ranking.rank(&mut entries);
+        entries
+    }
+}
+
+fn parse_id(id: &str) -> Result<Uuid, VaderError<'static>> {
+    Uuid::parse_str(id).map_err(|_e| VaderError::SqlxFieldError("Error decoding Participant Id"))
+}
+
+fn send_msg(msg: &VboardMsg, ctx: &mut ws::WebsocketContext<VboardReplay>) {
+    match serde_json::to_string(msg) {
+        Ok(msg_str) => ctx.text(msg_str),
+        Err(e) => log::debug!("Error sending Vaderboard replay : {}", e),
+    }
+}
+
+impl VboardReplay {
+    pub fn new(
+        timeline: Timeline,
+        window: VboardWindow,
+        speed: f64,
+        vb_count: u32,
+        conn: WsConn,
+    ) -> Self {
+        VboardReplay {
+            sub: VboardSub::new(timeline.event_id, window),
+            timeline,
+            vb_count,
+            step: 0,
+            speed,
+            paused: false,
+            next: None,
+            conn,
+        }
+    }
+
+    fn at(&self) -> Option<DateTime<Utc>> {
+        self.step
+            .checked_sub(1)
+            .map(|step| self.timeline.steps[step])
+    }
+
+    pub fn send_snapshot(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        let board = self.timeline.board_at(self.at());
+        let rows = self.sub.window.rows(&board, self.vb_count);
+        self.sub.seq += 1;
+        send_msg(
+            &VboardMsg::Snapshot {
+                event_id: self.sub.event_id,
+                window: self.sub.window,
+                seq: self.sub.seq,
+                entries: rows,
+            },
+            ctx,
+        );
+        self.sub.entries = rows.to_vec();
+        self.send_status(ctx);
+    }
+
+    fn send_status(&self, ctx: &mut ws::WebsocketContext<Self>) {
+        send_msg(
+            &VboardMsg::Replay {
+                at: self.at(),
+                speed: self.speed,
+                paused: self.paused,
+                done: self.step >= self.timeline.steps.len(),
+            },
+            ctx,
+        );
+    }
+
+    // Apply the next step after the recorded gap scaled by speed
+    pub fn schedule(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        if let Some(next) = self.next.take() {
+            ctx.cancel_future(next);
+        }
+        if self.paused || self.step >= self.timeline.steps.len() {
+            return;
+        }
+        let gap = match self.at() {
+            Some(at) => (self.timeline.steps[self.step] - at)
+                .to_std()
+                .unwrap_or_default()
+                .div_f64(self.speed),
+            None => REPLAY_START,
+        };
+        self.next = Some(ctx.run_later(gap, |act, ctx| {
+            act.next = None;
+            act.advance(ctx);
+        }));
+    }
+
+    fn advance(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        self.step += 1;
+        let board = self.timeline.board_at(self.at());
+        let rows = self.sub.window.rows(&board, self.vb_count);
+        if let Some(diff) = self.sub.diff(rows) {
+            self.sub.seq += 1;
+            send_msg(&diff, ctx);
+            self.sub.entries = rows.to_vec();
+        }
+        self.send_status(ctx);
+        self.schedule(ctx);
+    }
+}
+
+impl WsHeartbeat for VboardReplay {
+    fn conn(&mut self) -> &mut WsConn {
+        &mut self.conn
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for VboardReplay {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        if !self.beat(&msg, ctx) {
+            return;
+        }
+        use ws::Message::*;
+        match msg {
+            Ok(Ping(msg)) => ctx.pong(&msg),
+            Ok(Text(text)) => match serde_json::from_str::<ReplayReq>(&text) {
+                Ok(ReplayReq::Speed { speed }) if speed.is_finite() && speed > 0.0 => {
+                    self.speed = speed;
+                    self.send_status(ctx);
+                    self.schedule(ctx);
+                }
+                Ok(ReplayReq::Speed { .. }) => send_msg(
+                    &VboardMsg::Error {
+                        msg: "Invalid replay speed",
+                    },
+                    ctx,
+                ),
+                Ok(ReplayReq::Pause) => {
+                    self.paused = true;
+                    self.send_status(ctx);
+                    self.schedule(ctx);
+                }
+                Ok(ReplayReq::Resume) => {
+                    self.paused = false;
+                    self.send_status(ctx);
+                    self.schedule(ctx);
+                }
+                Ok(ReplayReq::Seek { at }) => {
+                    self.step = self.timeline.steps.partition_point(|step| *step <= at);
+                    self.send_snapshot(ctx);
+                    self.schedule(ctx);
+                }
+                // any other text asks for a fresh snapshot like live clients
+                Err(_) => self.send_snapshot(ctx),
+            },
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn entry(name: &'static str) -> VboardEntry<'static> {
+        VboardEntry {
+            rank: 0,
+            id: Uuid::new_v4(),
+            name: name.into(),
+            score: 0,
+            logo: None,
+            solves: 0,
+            breakdown: None,
+            frozen: false,
+        }
+    }
+
+    fn change(at: DateTime<Utc>, participant_id: Uuid, delta: i64, total: i64) -> ScoreChange {
+        ScoreChange {
+            at,
+            participant_id,
+            delta,
+            total,
+        }
+    }
+
+    // (name, score, rank) rows of the replayed board
+    fn board(timeline: &Timeline, at: Option<DateTime<Utc>>) -> Vec<(String, i64, u32)> {
+        timeline
+            .board_at(at)
+            .into_iter()
+            .map(|e| (e.name.into_owned(), e.score, e.rank))
+            .collect()
+    }
+
+    fn rows(rows: &[(&str, i64, u32)]) -> Vec<(String, i64, u32)> {
+        rows.iter().map(|r| (r.0.to_string(), r.1, r.2)).collect()
+    }
+
+    #[test]
+    fn board_at_replays_changes() {
+        let (a, b) = (entry("a"), entry("b"));
+        let (a_id, b_id) = (a.id, b.id);
+        let t0 = Utc::now();
+        let (t1, t2, t3) = (
+            t0 + Duration::seconds(1),
+            t0 + Duration::seconds(2),
+            t0 + Duration::seconds(3),
+        );
+        let timeline = Timeline {
+            event_id: Uuid::new_v4(),
+            ranking: RankingMode::Standard,
+            participants: vec![a, b],
+            changes: vec![
+                change(t1, b_id, 5, 5),
+                change(t2, a_id, 5, 5),
+                change(t3, a_id, 3, 8),
+            ],
+            solves: vec![(t2, a_id)],
+            steps: vec![t1, t2, t3],
+        };
+
+        // nothing applied before the first step
+        assert_eq!(board(&timeline, None), rows(&[("a", 0, 1), ("b", 0, 1)]));
+        assert_eq!(
+            board(&timeline, Some(t1)),
+            rows(&[("b", 5, 1), ("a", 0, 2)])
+        );
+        // tied scores go to whoever got there first
+        assert_eq!(
+            board(&timeline, Some(t2)),
+            rows(&[("b", 5, 1), ("a", 5, 1)])
+        );
+        assert_eq!(
+            board(&timeline, Some(t3)),
+            rows(&[("a", 8, 1), ("b", 5, 2)])
+        );
+        let solves: Vec<u32> = timeline
+            .board_at(Some(t3))
+            .iter()
+            .map(|e| e.solves)
+            .collect();
+        assert_eq!(solves, [1, 0]);
+    }
+}
@@ -0,0 +1,253 @@
+use actix::{ActorFutureExt, AsyncContext, Context, ContextFutureSpawner, Handler, WrapFuture};
+use chrono::{DateTime, Utc};
+use log::{error, info};
+use uuid::Uuid;
+
+use crate::models::bracket_models::BracketGet;
+use crate::models::query_models::{
+    CurFtsStop, EventQueryState, TransferType, VbClock, VbEventState, VboardGet,
+};
+use crate::models::schedule_models::{
+    EventScheduler, EventSrvs, ScheduleEvent, Transition, MAX_TIMER,
+};
+use crate::models::season_models::{Season, SeasonGet};
+use crate::models::v_models::EventClock;
+
+impl EventSrvs {
+    // Push a started event to vaderboard and bracket clients
+    pub fn started(&self, event_id: Uuid, clock: Option<EventClock>) {
+        if clock.is_some() {
+            self.vb_srv.do_send(VbClock(event_id, clock));
+        }
+        self.vb_srv
+            .do_send(VboardGet(event_id, TransferType::Broadcast));
+        self.br_srv
+            .do_send(BracketGet(event_id, TransferType::Broadcast));
+    }
+    // Tell vaderboard clients score intake is on hold, along with the paused clock
+    pub fn paused(&self, event_id: Uuid, clock: Option<EventClock>) {
+        self.vb_srv
+            .do_send(VbEventState(event_id, EventQueryState::Paused));
+        if clock.is_some() {
+            self.vb_srv.do_send(VbClock(event_id, clock));
+        }
+    }
+    pub fn resumed(&self, event_id: Uuid, clock: Option<EventClock>) {
+        self.vb_srv
+            .do_send(VbEventState(event_id, EventQueryState::Start));
+        if clock.is_some() {
+            self.vb_srv.do_send(VbClock(event_id, clock));
+        }
+    }
+    // Close search clients and clock of a stopped event, its seasons have new placements
+    pub async fn stopped(&self, event_id: Uuid) {
+        self.fts_srv.do_send(CurFtsStop(event_id));
+        self.vb_srv.do_send(VbClock(event_id, None));
+        self.vb_srv
+            .do_send(VbEventState(event_id, EventQueryState::Stop));
+        self.placed(event_id).await;
+    }
+    // Refresh season boards counting placements of the event
+    pub async fn placed(&self, event_id: Uuid) {
+        match Season::get_event_seasons(event_id, &self.db_pool).await {
+            Ok(seasons) => seasons.into_iter().for_each(|season_id| {
+                self.sn_srv
+                    .do_send(SeasonGet(season_id, TransferType::Broadcast))
+            }),
+            Err(e) => error!("Error fetching seasons of event : {}", e),
+        }
+    }
+}
+
+impl EventScheduler {
+    // Waits so schedule messages sent meanwhile are handled after the restored ones
+    pub fn restore(&mut self, ctx: &mut Context<Self>) {
+        let srvs = self.srvs.clone();
+        async move {
+            // registry is not locked while reading clocks, events may change in between
+            let ids: Vec<Uuid> = srvs.app_state.events.lock().await.keys().copied().collect();
+            let mut clocks = Vec::with_capacity(ids.len());
+            for event_id in ids {
+                match EventClock::get(event_id, &srvs.db_pool).await {
+                    Ok(clock) => clocks.push((event_id, clock)),
+                    Err(e) => error!("Error restoring schedule of event : {}", e),
+                }
+            }
+            let events = srvs.app_state.events.lock().await;
+            clocks
+                .into_iter()
+                .filter_map(|(event_id, clock)| {
+                    events.get(&event_id).map(|event| event.schedule(clock))
+                })
+                .collect::<Vec<ScheduleEvent>>()
+        }
+        .into_actor(self)
+        .map(|schedules, act, ctx| {
+            for ScheduleEvent(event_id, transition) in schedules {
+                if let Some((transition, at)) = transition {
+                    act.schedule(event_id, transition, at, ctx);
+                }
+            }
+        })
+        .wait(ctx);
+    }
+
+    fn schedule(
+        &mut self,
+        event_id: Uuid,
+        transition: Transition,
+        at: DateTime<Utc>,
+        ctx: &mut Context<Self>,
+    ) {
+        let delay = (at - Utc::now())
+            .to_std()
+            .unwrap_or_default()
+            .min(MAX_TIMER);
+        let handle = ctx.run_later(delay, move |act, ctx| {
+            act.timers.remove(&event_id);
+            if at > Utc::now() {
+                act.schedule(event_id, transition, at, ctx);
+            } else {
+                act.transition(event_id, transition, ctx);
+            }
+        });
+        if let Some(old) = self.timers.insert(event_id, handle) {
+            ctx.cancel_future(old);
+        }
+        log::debug!(
+            "{:?} of event [{}] scheduled at {}",
+            transition,
+            event_id,
+            at
+        );
+    }
+
+    // Same transition and side effects as the admin start / stop handlers
+    fn transition(&mut self, event_id: Uuid, transition: Transition, ctx: &mut Context<Self>) {
+        let srvs = self.srvs.clone();
+        async move {
+            let mut events = srvs.app_state.events.lock().await;
+            let Some(event) = events.get_mut(&event_id) else {
+                error!("Request delined.No event found [id : {}]", event_id);
+                return None;
+            };
+            match transition {
+                Transition::Start => match event.start_event(&srvs.db_pool).await {
+                    Ok(clock) => {
+                        info!("Event id : [{}] started on schedule", event_id);
+                        srvs.started(event_id, clock);
+                        Some(event.schedule(clock))
+                    }
+                    Err(e) => {
+                        error!("Error starting scheduled event : {}", e);
+                        None
+                    }
+                },
+                Transition::Stop => {
+                    if let Err(e) = event.end_event(&srvs.db_pool).await {
+                        error!("Error stopping scheduled event : {}", e);
+                        return None;
+                    }
+                    drop(events);
+                    info!("Event id : [{}] stopped on schedule", event_id);
+                    srvs.stopped(event_id).await;
+                    None
+                }
+            }
+        }
+        .into_actor(self)
+        .map(|schedule, act, ctx| {
+            if let Some(ScheduleEvent(event_id, Some((transition, at)))) = schedule {
+                act.schedule(event_id, transition, at, ctx);
+            }
+        })
+        .spawn(ctx);
+    }
+}
+
+impl Handler<ScheduleEvent> for EventScheduler {
+    type Result = ();
+    fn handle(&mut self, msg: ScheduleEvent, ctx: &mut Self::Context) -> Self::Result {
+        let ScheduleEvent(event_id, transition) = msg;
+        match transition {
+            Some((transition, at)) => self.schedule(event_id, transition, at, ctx),
+            None => {
+                if let Some(handle) = self.timers.remove(&event_id) {
+                    ctx.cancel_future(handle);
+                    log::debug!("Schedule of event [{}] cancelled", event_id);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::models::v_models::{Event, EventConfig, User};
+    use crate::models::wrapper_models::{EventStateWrapper, EventWrapper};
+
+    fn event(config: EventConfig) -> Event<'static, User<'static>> {
+        Event::new("event".into(), None, None).with_config(config)
+    }
+
+    fn transition(
+        wrapper: &EventWrapper,
+        clock: Option<EventClock>,
+    ) -> Option<(Transition, DateTime<Utc>)> {
+        wrapper.schedule(clock).1
+    }
+
+    #[test]
+    fn schedule_start() {
+        let start_at = Utc::now() + Duration::hours(1);
+        let config = EventConfig {
+            start_at: Some(start_at),
+            ..Default::default()
+        };
+        let wrapper = EventWrapper::UserEvent(EventStateWrapper::New(event(config)));
+        assert_eq!(
+            transition(&wrapper, None),
+            Some((Transition::Start, start_at))
+        );
+        let wrapper = EventWrapper::UserEvent(EventStateWrapper::New(event(Default::default())));
+        assert_eq!(transition(&wrapper, None), None);
+    }
+
+    #[test]
+    fn schedule_stop_after_duration() {
+        let start_at = Utc::now() + Duration::hours(1);
+        let config = EventConfig {
+            start_at: Some(start_at),
+            duration: Some(3600),
+            ..Default::default()
+        };
+        let ends_at = start_at + Duration::hours(1);
+        let active = event(config).start_event();
+        let wrapper = EventWrapper::UserEvent(EventStateWrapper::Active(active));
+        let clock = Some(EventClock::Running { ends_at });
+        assert_eq!(
+            transition(&wrapper, clock),
+            Some((Transition::Stop, ends_at))
+        );
+        // paused clocks have no end yet
+        let clock = Some(EventClock::Paused { remaining_ms: 1000 });
+        assert_eq!(transition(&wrapper, clock), None);
+    }
+
+    #[test]
+    fn manual_events_are_not_stopped() {
+        let config = EventConfig {
+            duration: Some(3600),
+            ..Default::default()
+        };
+        let active = event(config).start_event();
+        let wrapper = EventWrapper::UserEvent(EventStateWrapper::Active(active));
+        let clock = Some(EventClock::Running {
+            ends_at: Utc::now(),
+        });
+        assert_eq!(transition(&wrapper, clock), None);
+    }
+}
@@ -0,0 +1,432 @@
+use std::collections::HashMap;
+
+use actix::{AsyncContext, ContextFutureSpawner, Handler, StreamHandler, WrapFuture};
+use actix_web_actors::ws;
+use chrono::Utc;
+use sqlx::{SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::error_models::VaderError;
+use crate::models::query_models::{TransferType, VboardEntry};
+use crate::models::season_models::{
+    Season, SeasonClient, SeasonEntry, SeasonEvent, SeasonEventReq, SeasonGet, SeasonReq,
+    SeasonRes, SeasonResult, SeasonSrv, SnConnect, SnDisconnect,
+};
+use crate::models::v_models::AsyncDbRes;
+use crate::models::ws_models::{WsConn, WsHeartbeat};
+
+fn parse_uuid(id: &str, field: &'static str) -> Result<Uuid, VaderError<'static>> {
+    Uuid::parse_str(id).map_err(|_e| VaderError::SqlxFieldError(field))
+}
+
+impl<'a> Season<'a> {
+    pub fn from_req(req: SeasonReq<'a>) -> Result<Self, VaderError<'a>> {
+        if req.points.is_empty() {
+            return Err(VaderError::SeasonError(
+                "Points table needs at least one placement",
+            ));
+        }
+        Ok(Season {
+            id: Uuid::new_v4(),
+            name: req.name,
+            points: req.points,
+            events: Vec::new(),
+        })
+    }
+
+    pub fn add_season(&'a self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()> {
+        let id = self.id.to_string();
+        let created_at = Utc::now();
+        Box::pin(async move {
+            let points = serde_json::to_string(&self.points)?;
+            sqlx::query("INSERT INTO seasons (id,name,points,created_at) VALUES (?,?,?,?)")
+                .bind(id)
+                .bind(self.name.as_ref())
+                .bind(points)
+                .bind(created_at)
+                .execute(db_pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    // Points of a placement in an event, placements beyond the table get nothing
+    pub fn placement_points(&self, placement: u32, multiplier: f64) -> f64 {
+        let points = placement
+            .checked_sub(1)
+            .and_then(|i| self.points.get(i as usize))
+            .copied()
+            .unwrap_or(0);
+        f64::from(points) * multiplier
+    }
+
+    pub fn get_season(season_id: Uuid, db_pool: &SqlitePool) -> AsyncDbRes<'_, Season<'static>> {
+        Box::pin(async move {
+            let id = season_id.to_string();
+            let season = sqlx::query_as::<_, (String, String)>(
+                "SELECT name,points FROM seasons WHERE id = ?",
+            )
+            .bind(&id)
+            .fetch_optional(db_pool)
+            .await?;
+            let (name, points) = match season {
+                Some(season) => season,
+                None => return Err(VaderError::SeasonError("No season found")),
+            };
+            let events = sqlx::query_as::<_, (String, f64)>(
+                "SELECT event_id,multiplier FROM season_events WHERE season_id = ?",
+            )
+            .bind(&id)
+            .fetch_all(db_pool)
+            .await?
+            .into_iter()
+            .map(|(event_id, multiplier)| {
+                Ok(SeasonEvent {
+                    event_id: parse_uuid(&event_id, "Error decoding Event Id")?,
+                    multiplier,
+                })
+            })
+            .collect::<Result<Vec<SeasonEvent>, VaderError>>()?;
+            Ok(Season {
+                id: season_id,
+                name: name.into(),
+                points: serde_json::from_str(&points)?,
+                events,
+            })
+        })
+    }
+
+    pub fn get_all_seasons(db_pool: &SqlitePool) -> AsyncDbRes<'_, Vec<Season<'static>>> {
+        Box::pin(async move {
+            let ids = sqlx::query_scalar::<_, String>("SELECT id FROM seasons ORDER BY created_at")
+                .fetch_all(db_pool)
+                .await?;
+            let mut seasons = Vec::with_capacity(ids.len());
+            for id in ids.iter() {
+                let season_id = parse_uuid(id, "Error decoding Season Id")?;
+                seasons.push(Self::get_season(season_id, db_pool).await?);
+            }
+            Ok(seasons)
+        })
+    }
+
+    // Add event to season, or update its multiplier if already added
+    pub fn add_event(
+        season_id: Uuid,
+        req: &'a SeasonEventReq,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ()> {
+        Box::pin(async move {
+            if !req.multiplier.is_finite() || req.multiplier < 0.0 {
+                return Err(VaderError::SeasonError(
+                    "Multiplier must be a non negative number",
+                ));
+            }
+            let season_id = season_id.to_string();
+            let event_id = req.event_id.to_string();
+            let event = sqlx::query_scalar::<_, String>("SELECT id FROM events WHERE id = ?")
+                .bind(&event_id)
+                .fetch_optional(db_pool)
+                .await?;
+            if event.is_none() {
+                return Err(VaderError::EventNotFound("No event found"));
+            }
+            let res = sqlx::query(
+                "INSERT INTO season_events (season_id,event_id,multiplier)
+                 SELECT id,?,? FROM seasons WHERE id = ?
+                 ON CONFLICT (season_id,event_id) DO UPDATE SET multiplier = excluded.multiplier",
+            )
+            .bind(&event_id)
+            .bind(req.multiplier)
+            .bind(&season_id)
+            .execute(db_pool)
+            .await?;
+            if res.rows_affected() == 0 {
+                return Err(VaderError::SeasonError("No season found"));
+            }
+            Ok(())
+        })
+    }
+
+    pub fn remove_event(
+        season_id: Uuid,
+        event_id: Uuid,
+        db_pool: &SqlitePool,
+    ) -> AsyncDbRes<'_, ()> {
+        Box::pin(async move {
+            let res = sqlx::query("DELETE FROM season_events WHERE season_id = ? AND event_id = ?")
+                .bind(season_id.to_string())
+                .bind(event_id.to_string())
+                .execute(db_pool)
+                .await?;
+            if res.rows_affected() == 0 {
+                return Err(VaderError::SeasonError("Event not found in season"));
+            }
+            Ok(())
+        })
+    }
+
+    // Seasons which have the event, their boards change when it ends
+    pub fn get_event_seasons(event_id: Uuid, db_pool: &SqlitePool) -> AsyncDbRes<'_, Vec<Uuid>> {
+        Box::pin(async move {
+            sqlx::query_scalar::<_, String>(
+                "SELECT season_id FROM season_events WHERE event_id = ?",
+            )
+            .bind(event_id.to_string())
+            .fetch_all(db_pool)
+            .await?
+            .iter()
+            .map(|id| parse_uuid(id, "Error decoding Season Id"))
+            .collect()
+        })
+    }
+
+    // Replace final placements of an ended event with its ranked vaderboard
+    pub async fn record_placements(
+        event_id: &str,
+        entries: &[VboardEntry<'_>],
+        conn: &mut SqliteConnection,
+    ) -> Result<(), VaderError<'static>> {
+        sqlx::query("DELETE FROM event_placements WHERE event_id = ?")
+            .bind(event_id)
+            .execute(&mut *conn)
+            .await?;
+        for entry in entries.iter() {
+            sqlx::query(
+                "INSERT INTO event_placements (event_id,participant_id,placement) VALUES (?,?,?)",
+            )
+            .bind(event_id)
+            .bind(entry.id.to_string())
+            .bind(entry.rank)
+            .execute(&mut *conn)
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+impl SeasonEntry<'_> {
+    // Aggregate season board, ties share the rank
+    pub fn get_season_board(
+        season_id: Uuid,
+        db_pool: &SqlitePool,
+    ) -> AsyncDbRes<'_, Vec<SeasonEntry<'static>>> {
+        Box::pin(async move {
+            let season = Season::get_season(season_id, db_pool).await?;
+            let rows = sqlx::query_as::<_, (String, String, Option<String>, String, u32, f64)>(
+                "SELECT ep.participant_id,COALESCE(t.name,u.name,''),COALESCE(t.logo,u.logo),
+                 ep.event_id,ep.placement,se.multiplier
+                 FROM season_events se JOIN event_placements ep ON ep.event_id = se.event_id
+                 LEFT JOIN teams t ON t.id = ep.participant_id
+                 LEFT JOIN users u ON u.id = ep.participant_id
+                 WHERE se.season_id = ?",
+            )
+            .bind(season_id.to_string())
+            .fetch_all(db_pool)
+            .await?;
+            let mut board: HashMap<Uuid, SeasonEntry> = HashMap::new();
+            for (p_id, name, logo, event_id, placement, multiplier) in rows {
+                let id = parse_uuid(&p_id, "Error decoding Participant Id")?;
+                let points = season.placement_points(placement, multiplier);
+                let entry = board.entry(id).or_insert_with(|| SeasonEntry {
+                    rank: 0,
+                    id,
+                    name: name.into(),
+                    logo: logo.map(|l| l.into()),
+                    points: 0.0,
+                    results: Vec::new(),
+                });
+                entry.points += points;
+                entry.results.push(SeasonResult {
+                    event_id: parse_uuid(&event_id, "Error decoding Event Id")?,
+                    placement,
+                    points,
+                });
+            }
+            let mut board: Vec<SeasonEntry> = board.into_values().collect();
+            board.sort_by(|x, y| y.points.total_cmp(&x.points).then(x.name.cmp(&y.name)));
+            let mut prev: Option<f64> = None;
+            for i in 0..board.len() {
+                board[i].rank = match prev {
+                    Some(points) if points == board[i].points => board[i - 1].rank,
+                    _ => i as u32 + 1,
+                };
+                prev = Some(board[i].points);
+            }
+            Ok(board)
+        })
+    }
+}
+
+impl WsHeartbeat for SeasonClient {
+    fn conn(&mut self) -> &mut WsConn {
+        &mut self.conn
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for SeasonClient {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        if !self.beat(&msg, ctx) {
+            return;
+        }
+        use ws::Message::*;
+        match msg {
+            Ok(Ping(msg)) => ctx.pong(&msg),
+            Ok(Text(_)) => self.srv_addr.do_send(SeasonGet(
+                self.season_id,
+                TransferType::Unicast(self.addr.clone().unwrap()),
+            )),
+            _ => {}
+        }
+    }
+}
+
+impl Handler<SeasonRes<'_>> for SeasonClient {
+    type Result = ();
+    fn handle(&mut self, msg: SeasonRes, ctx: &mut Self::Context) -> Self::Result {
+        ctx.text(msg.0.as_ref());
+    }
+}
+
+impl Handler<SnConnect> for SeasonSrv {
+    type Result = ();
+    fn handle(&mut self, msg: SnConnect, ctx: &mut Self::Context) -> Self::Result {
+        self.sn_addr.entry(msg.0).or_default().insert(msg.1.clone());
+        log::debug!(
+            "New season board client of season [{}].Total connection count : {}",
+            msg.0,
+            self.sn_addr[&msg.0].len()
+        );
+        ctx.address()
+            .do_send(SeasonGet(msg.0, TransferType::Unicast(msg.1)));
+    }
+}
+impl Handler<SnDisconnect> for SeasonSrv {
+    type Result = ();
+    fn handle(&mut self, msg: SnDisconnect, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(clients) = self.sn_addr.get_mut(&msg.0) {
+            clients.remove(&msg.1);
+            if clients.is_empty() {
+                self.sn_addr.remove(&msg.0);
+            }
+        }
+    }
+}
+impl Handler<SeasonRes<'static>> for SeasonSrv {
+    type Result = ();
+    fn handle(&mut self, msg: SeasonRes<'static>, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some((season_id, transfer)) = msg.1 {
+            match transfer {
+                TransferType::Unicast(addr) => addr.do_send(SeasonRes(msg.0, None)),
+                TransferType::Broadcast => {
+                    if let Some(clients) = self.sn_addr.get(&season_id) {
+                        clients
+                            .iter()
+                            .for_each(|addr| addr.do_send(SeasonRes(msg.0.clone(), None)));
+                    }
+                }
+            }
+        }
+    }
+}
+impl Handler<SeasonGet> for SeasonSrv {
+    type Result = ();
+    fn handle(&mut self, msg: SeasonGet, ctx: &mut Self::Context) -> Self::Result {
+        let addr = ctx.address();
+        let db_pool = self.db_pool.clone();
+        let season_id = msg.0;
+        async move {
+            let board = SeasonEntry::get_season_board(season_id, &db_pool).await;
+            match board.and_then(|b| Ok(serde_json::to_string(&b)?)) {
+                Ok(board_str) => {
+                    addr.do_send(SeasonRes(board_str.into(), Some((season_id, msg.1))))
+                }
+                Err(e) => log::debug!("Error sending Season board : {}", e),
+            }
+        }
+        .into_actor(self)
+        .spawn(ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::command_models::{ScoreLog, ScoreMode};
+    use crate::models::v_models::{ActiveEvent, Event, EventConfig, User, VaderEvent};
+    use crate::services::test_services::{test_pool, user_event};
+
+    fn season(points: Vec<u32>) -> Season<'static> {
+        Season::from_req(SeasonReq {
+            name: "season".into(),
+            points,
+        })
+        .unwrap()
+    }
+
+    // Ends an event where participants score the given points
+    async fn end_with_scores<'a>(
+        event: Event<'a, User<'a>>,
+        ids: &[Uuid],
+        scores: &[i64],
+        db_pool: &SqlitePool,
+    ) {
+        let log = ScoreLog {
+            admin: None,
+            reason: None,
+        };
+        // shortened to the local score log
+        let event: Event<'_, User<'_>, ActiveEvent> = event.start_event();
+        for (id, score) in ids.iter().zip(scores) {
+            event
+                .update_score_by_id(id, *score, ScoreMode::Set, &log, db_pool)
+                .await
+                .unwrap();
+        }
+        event.end_event().save_end(db_pool).await.unwrap();
+    }
+
+    #[test]
+    fn points_by_placement() {
+        let season = season(vec![10, 5, 2]);
+        assert_eq!(season.placement_points(1, 1.0), 10.0);
+        assert_eq!(season.placement_points(3, 2.0), 4.0);
+        assert_eq!(season.placement_points(4, 1.0), 0.0);
+        assert_eq!(season.placement_points(0, 1.0), 0.0);
+        assert!(Season::from_req(SeasonReq {
+            name: "empty".into(),
+            points: Vec::new(),
+        })
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn season_board_adds_placements() {
+        let db_pool = test_pool().await;
+        let season = season(vec![10, 5, 2]);
+        season.add_season(&db_pool).await.unwrap();
+        let (first, ids) = user_event(EventConfig::default(), 3, &db_pool).await;
+        let second: Event<User> = Event::new("second".into(), None, None);
+        second.add_event(&db_pool).await.unwrap();
+        for id in ids.iter() {
+            second.add_participant_from_id(*id, &db_pool).await.unwrap();
+        }
+        for (event_id, multiplier) in [(first.id, 1.0), (second.id, 2.0)] {
+            let req = SeasonEventReq {
+                event_id,
+                multiplier,
+            };
+            Season::add_event(season.id, &req, &db_pool).await.unwrap();
+        }
+        end_with_scores(first, &ids, &[30, 20, 10], &db_pool).await;
+        end_with_scores(second, &ids, &[10, 20, 30], &db_pool).await;
+        let board = SeasonEntry::get_season_board(season.id, &db_pool)
+            .await
+            .unwrap();
+        let board: Vec<_> = board.iter().map(|e| (e.rank, e.id, e.points)).collect();
+        assert_eq!(
+            board,
+            [(1, ids[2], 22.0), (2, ids[1], 15.0), (3, ids[0], 14.0)]
+        );
+    }
+}
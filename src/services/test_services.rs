@@ -0,0 +1,45 @@
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::v_models::{Event, EventConfig, Player, User, VaderEvent};
+
+// Migrated in-memory database, one connection so every query sees the same database
+pub async fn test_pool() -> SqlitePool {
+    let db_pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .expect("Unable to open test database");
+    sqlx::migrate!()
+        .run(&db_pool)
+        .await
+        .expect("Unable to run Db migrations");
+    db_pool
+}
+
+// User event saved with the given number of participants, in the order they joined
+pub async fn user_event<'a>(
+    config: EventConfig,
+    participants: usize,
+    db_pool: &SqlitePool,
+) -> (Event<'a, User<'a>>, Vec<Uuid>) {
+    let event: Event<'a, User<'a>> =
+        Event::new(format!("event {}", Uuid::new_v4()).into(), None, None).with_config(config);
+    // borrowed for less than 'a so the event can be returned
+    let added: &Event<'_, User<'_>> = &event;
+    added.add_event(db_pool).await.expect("Unable to add event");
+    let mut ids = Vec::with_capacity(participants);
+    for i in 0..participants {
+        let user = User::new(format!("user {}", i).into(), None);
+        user.add_player(db_pool).await.expect("Unable to add user");
+        added
+            .add_participant(&user, db_pool)
+            .await
+            .expect("Unable to add participant");
+        ids.push(user.id);
+    }
+    (event, ids)
+}
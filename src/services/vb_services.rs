@@ -1,18 +1,299 @@
-use actix::{AsyncContext, ContextFutureSpawner, Handler, StreamHandler, WrapFuture};
+use std::collections::HashMap;
+
+use actix::{
+    fut, ActorContext, ActorFutureExt, AsyncContext, Context, ContextFutureSpawner, Handler,
+    ResponseActFuture, StreamHandler, WrapFuture,
+};
+use actix_web::web::{self, Bytes};
 use actix_web_actors::ws;
+use chrono::{Duration, Utc};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, Row, SqlitePool};
+use uuid::Uuid;
 
+use crate::models::error_models::VaderError;
 use crate::models::query_models::{
-    TransferType, VbConnect, VbDisconnect, VboardClient, VboardGet, VboardRes, VboardSrv,
+    Announcement, EventQueryState, Severity, TransferType, VbAnnounce, VbAnnouncements, VbClock,
+    VbConnect, VbDisconnect, VbEventState, VbPoll, VbResync, VbRetract, VbSubscribe, VboardChange,
+    VboardClient, VboardEntry, VboardGet, VboardLeft, VboardMsg, VboardPoll, VboardRecipient,
+    VboardReq, VboardRes, VboardSrv, VboardSse, VboardSub, VboardWindow, CLOCK_TICK,
 };
+use crate::models::v_models::{AppState, AsyncDbRes, EventClock};
+use crate::models::ws_models::{WsConn, WsHeartbeat};
+
+impl Severity {
+    // value stored in announcements.severity column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        }
+    }
+    pub fn from_column(value: &str) -> Option<Self> {
+        match value {
+            "info" => Some(Self::Info),
+            "warning" => Some(Self::Warning),
+            "critical" => Some(Self::Critical),
+            _ => None,
+        }
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Announcement {
+    fn from_row(row: &'_ SqliteRow) -> Result<Self, sqlx::Error> {
+        let id: Uuid = Uuid::parse_str(row.get("id")).map_err(|_e| sqlx::Error::ColumnDecode {
+            index: "0".to_string(),
+            source: Box::new(VaderError::SqlxFieldError("Error decoding Announcement Id")),
+        })?;
+        let event_id: Uuid =
+            Uuid::parse_str(row.get("event_id")).map_err(|_e| sqlx::Error::ColumnDecode {
+                index: "0".to_string(),
+                source: Box::new(VaderError::SqlxFieldError("Error decoding Event Id")),
+            })?;
+        let severity: String = row.get("severity");
+        let severity =
+            Severity::from_column(&severity).ok_or_else(|| sqlx::Error::ColumnDecode {
+                index: "0".to_string(),
+                source: Box::new(VaderError::SqlxFieldError(
+                    "Error decoding Announcement Severity",
+                )),
+            })?;
+        Ok(Announcement {
+            id,
+            event_id,
+            text: row.get("text"),
+            severity,
+            created_at: row.get("created_at"),
+            expires_at: row.get("expires_at"),
+        })
+    }
+}
+
+impl Announcement {
+    pub fn add<'a>(&'a self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()> {
+        let event_id = self.event_id.to_string();
+        Box::pin(async move {
+            let event = sqlx::query_scalar::<_, String>("SELECT id FROM events WHERE id = ?")
+                .bind(&event_id)
+                .fetch_optional(db_pool)
+                .await?;
+            if event.is_none() {
+                return Err(VaderError::EventNotFound("No event found to announce in"));
+            }
+            sqlx::query(
+                "INSERT INTO announcements (id,event_id,text,severity,created_at,expires_at) VALUES (?,?,?,?,?,?)",
+            )
+            .bind(self.id.to_string())
+            .bind(&event_id)
+            .bind(&self.text)
+            .bind(self.severity.as_str())
+            .bind(self.created_at)
+            .bind(self.expires_at)
+            .execute(db_pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    // false when the event has no such active announcement
+    pub fn remove(event_id: Uuid, id: Uuid, db_pool: &SqlitePool) -> AsyncDbRes<'_, bool> {
+        Box::pin(async move {
+            let res = sqlx::query(
+                "DELETE FROM announcements WHERE id = ? AND event_id = ? AND (expires_at IS NULL OR expires_at > ?)",
+            )
+            .bind(id.to_string())
+            .bind(event_id.to_string())
+            .bind(Utc::now())
+            .execute(db_pool)
+            .await?;
+            Ok(res.rows_affected() > 0)
+        })
+    }
+
+    pub fn get_active(db_pool: &SqlitePool) -> AsyncDbRes<'_, Vec<Announcement>> {
+        Box::pin(async move {
+            let announcements = sqlx::query_as::<_, Announcement>(
+                "SELECT id,event_id,text,severity,created_at,expires_at FROM announcements WHERE expires_at IS NULL OR expires_at > ? ORDER BY created_at",
+            )
+            .bind(Utc::now())
+            .fetch_all(db_pool)
+            .await?;
+            Ok(announcements)
+        })
+    }
+}
+
+impl VboardWindow {
+    // Rows of the ranked vaderboard inside the window
+    pub fn rows<'a>(
+        &self,
+        entries: &'a [VboardEntry<'static>],
+        vb_count: u32,
+    ) -> &'a [VboardEntry<'static>] {
+        match *self {
+            Self::Top { count } => {
+                let count = count.unwrap_or(vb_count) as usize;
+                &entries[..count.min(entries.len())]
+            }
+            Self::Ranks { from, to } => {
+                let start = entries.partition_point(|e| e.rank < from);
+                let end = entries.partition_point(|e| e.rank <= to).max(start);
+                &entries[start..end]
+            }
+            Self::Around { around, radius } => match entries.iter().position(|e| e.id == around) {
+                Some(pos) => {
+                    let radius = radius as usize;
+                    let end = (pos + radius + 1).min(entries.len());
+                    &entries[pos.saturating_sub(radius)..end]
+                }
+                None => &[],
+            },
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            Self::Ranks { from, to } => from > 0 && from <= to,
+            _ => true,
+        }
+    }
+}
+
+impl VboardSub {
+    pub fn new(event_id: Uuid, window: VboardWindow) -> Self {
+        VboardSub {
+            event_id,
+            window,
+            seq: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    // Diff from the rows last sent, None when nothing changed
+    pub fn diff<'a>(&self, entries: &'a [VboardEntry<'static>]) -> Option<VboardMsg<'a>> {
+        let old: HashMap<_, _> = self.entries.iter().map(|e| (e.id, e)).collect();
+        let mut changed = Vec::new();
+        let mut entered = Vec::new();
+        for entry in entries.iter() {
+            match old.get(&entry.id) {
+                Some(prev) if *prev != entry => changed.push(VboardChange {
+                    entry,
+                    old_rank: prev.rank,
+                    old_score: prev.score,
+                }),
+                Some(_) => {}
+                None => entered.push(entry),
+            }
+        }
+        let left: Vec<VboardLeft> = self
+            .entries
+            .iter()
+            .filter(|prev| !entries.iter().any(|e| e.id == prev.id))
+            .map(|prev| VboardLeft {
+                id: prev.id,
+                old_rank: prev.rank,
+            })
+            .collect();
+        if changed.is_empty() && entered.is_empty() && left.is_empty() {
+            return None;
+        }
+        Some(VboardMsg::Diff {
+            seq: self.seq + 1,
+            changed,
+            entered,
+            left,
+        })
+    }
+}
+
+fn clock_msg(event_id: Uuid, clock: &EventClock) -> VboardMsg<'static> {
+    let server_time = Utc::now();
+    let (paused, ends_at) = match *clock {
+        EventClock::Running { ends_at } => (false, Some(ends_at)),
+        EventClock::Paused { .. } => (true, None),
+    };
+    VboardMsg::Clock {
+        event_id,
+        server_time,
+        remaining_ms: clock.remaining_ms(server_time),
+        paused,
+        ends_at,
+    }
+}
+
+fn send_msg(addr: &VboardRecipient, msg: &VboardMsg) {
+    match serde_json::to_string(msg) {
+        Ok(msg_str) => addr.do_send(VboardRes(msg_str.into())),
+        Err(e) => log::debug!("Error sending Vaderboard : {}", e),
+    }
+}
+
+impl WsHeartbeat for VboardClient {
+    fn conn(&mut self) -> &mut WsConn {
+        &mut self.conn
+    }
+}
+
+fn send_snapshot(
+    addr: &VboardRecipient,
+    sub: &mut VboardSub,
+    entries: &[VboardEntry<'static>],
+    vb_count: u32,
+) {
+    let rows = sub.window.rows(entries, vb_count);
+    sub.seq += 1;
+    send_msg(
+        addr,
+        &VboardMsg::Snapshot {
+            event_id: sub.event_id,
+            window: sub.window,
+            seq: sub.seq,
+            entries: rows,
+        },
+    );
+    sub.entries = rows.to_vec();
+}
 
 impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for VboardClient {
     fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        if !self.beat(&msg, ctx) {
+            return;
+        }
         use ws::Message::*;
+        let addr = self.addr.clone().unwrap();
         match msg {
             Ok(Ping(msg)) => ctx.pong(&msg),
-            Ok(Text(_)) => self
-                .srv_addr
-                .do_send(VboardGet(TransferType::Unicast(self.addr.clone().unwrap()))),
+            Ok(Text(text)) => match serde_json::from_str::<VboardReq>(&text) {
+                Ok(VboardReq::Subscribe { event_id, window }) => {
+                    // invalid window keeps the current subscription
+                    if !window.is_valid() {
+                        let err = VboardMsg::Error {
+                            msg: "Invalid rank window",
+                        };
+                        if let Ok(err) = serde_json::to_string(&err) {
+                            ctx.text(err);
+                        }
+                        return;
+                    }
+                    self.event_id = event_id;
+                    self.srv_addr
+                        .do_send(VbSubscribe(addr.recipient(), event_id, window))
+                }
+                Ok(VboardReq::Sync { client_time }) => {
+                    let sync = VboardMsg::ClockSync {
+                        client_time,
+                        server_time: Utc::now(),
+                    };
+                    if let Ok(sync) = serde_json::to_string(&sync) {
+                        ctx.text(sync);
+                    }
+                }
+                // any other text asks for a fresh snapshot, used by clients to resync on a seq gap
+                Ok(VboardReq::Resync) | Err(_) => self
+                    .srv_addr
+                    .do_send(VboardGet(self.event_id, TransferType::Unicast(addr))),
+            },
             _ => {}
         }
     }
@@ -26,62 +307,564 @@ impl Handler<VboardRes<'_>> for VboardClient {
     }
 }
 
+impl Handler<VboardRes<'_>> for VboardSse {
+    type Result = ();
+    fn handle(&mut self, msg: VboardRes, ctx: &mut Self::Context) -> Self::Result {
+        let event = format!("data: {}\n\n", msg.0);
+        // receiver is dropped once the http client goes away
+        if self.tx.unbounded_send(Bytes::from(event)).is_err() {
+            ctx.stop();
+        }
+    }
+}
+
 impl Handler<VbConnect> for VboardSrv {
     type Result = ();
     fn handle(&mut self, msg: VbConnect, ctx: &mut Self::Context) -> Self::Result {
-        let addr = ctx.address();
-        self.vb_addr.insert(msg.0.clone());
+        self.vb_addr.entry(msg.0).or_default().insert(msg.1.clone());
+        self.vb_subs
+            .insert(msg.1.clone(), VboardSub::new(msg.0, msg.2));
         log::debug!(
-            "New client connection.Total connection count : {}",
-            self.vb_addr.len()
+            "New client connection to event [{}].Total connection count : {}",
+            msg.0,
+            self.vb_addr[&msg.0].len()
         );
-        addr.do_send(VboardGet(TransferType::Unicast(msg.0)));
+        self.send_announcements(msg.0, &msg.1);
+        if let Some(clock) = self.vb_clocks.get(&msg.0) {
+            send_msg(&msg.1, &clock_msg(msg.0, clock));
+        }
+        if self.vb_paused.contains(&msg.0) {
+            send_msg(&msg.1, &VboardMsg::EventPaused { event_id: msg.0 });
+        }
+        self.request_vboard(msg.0, Some(msg.1), ctx);
+    }
+}
+impl Handler<VbSubscribe> for VboardSrv {
+    type Result = ();
+    fn handle(&mut self, msg: VbSubscribe, ctx: &mut Self::Context) -> Self::Result {
+        let VbSubscribe(client, event_id, window) = msg;
+        if let Some(sub) = self.vb_subs.get(&client) {
+            self.remove_client(sub.event_id, &client);
+        }
+        self.vb_addr
+            .entry(event_id)
+            .or_default()
+            .insert(client.clone());
+        self.vb_subs
+            .insert(client.clone(), VboardSub::new(event_id, window));
+        log::debug!("Client subscribed to event [{}]", event_id);
+        self.send_announcements(event_id, &client);
+        if let Some(clock) = self.vb_clocks.get(&event_id) {
+            send_msg(&client, &clock_msg(event_id, clock));
+        }
+        if self.vb_paused.contains(&event_id) {
+            send_msg(&client, &VboardMsg::EventPaused { event_id });
+        }
+        self.request_vboard(event_id, Some(client), ctx);
     }
 }
 impl Handler<VbDisconnect> for VboardSrv {
     type Result = ();
     fn handle(&mut self, msg: VbDisconnect, _ctx: &mut Self::Context) -> Self::Result {
-        self.vb_addr.remove(&msg.0);
+        // client may have switched events, its subscription knows where it is
+        let event_id = self
+            .vb_subs
+            .remove(&msg.1)
+            .map_or(msg.0, |sub| sub.event_id);
+        self.remove_client(event_id, &msg.1);
+        log::debug!("Client Disconnected from event [{}]", event_id);
+    }
+}
+
+// Ranked vaderboard of the event, None when no event is added with the id
+async fn fetch_vboard(
+    event_lock: web::Data<AppState>,
+    db_pool: SqlitePool,
+    event_id: Uuid,
+) -> Result<Option<Vec<VboardEntry<'static>>>, String> {
+    // boards of events ended before a restart are loaded back
+    event_lock
+        .load_event(event_id, &db_pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    // registry is only locked to copy the vaderboard config
+    let source = match event_lock.as_ref().events.lock().await.get(&event_id) {
+        Some(e) => e.vboard_source().map_err(|e| e.to_string())?,
+        None => return Ok(None),
+    };
+    source
+        .get_vboard(u32::MAX, &db_pool)
+        .await
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+impl VboardSrv {
+    // Snapshot from the cache when it is current, else schedule a vaderboard fetch
+    // broadcast requests mean standings changed and drop the cache
+    fn request_vboard(
+        &mut self,
+        event_id: Uuid,
+        client: Option<VboardRecipient>,
+        ctx: &mut Context<Self>,
+    ) {
+        let refetching =
+            self.vb_pending.contains_key(&event_id) || self.vb_fetching.contains(&event_id);
+        match &client {
+            Some(client) if !refetching => {
+                if let Some(entries) = self.vb_cache.get(&event_id) {
+                    if let Some(sub) = self.vb_subs.get_mut(client) {
+                        send_snapshot(client, sub, entries, self.app_state.vb_count);
+                    }
+                    return;
+                }
+            }
+            Some(_) => {}
+            None => {
+                self.vb_cache.remove(&event_id);
+                *self.vb_versions.entry(event_id).or_default() += 1;
+                if !self.vb_addr.contains_key(&event_id) {
+                    return;
+                }
+            }
+        }
+        let pending = self.vb_pending.entry(event_id).or_insert_with(|| {
+            ctx.run_later(self.vb_window, move |act, ctx| act.flush(event_id, ctx));
+            Vec::new()
+        });
+        pending.extend(client);
+    }
+
+    fn send_announcements(&self, event_id: Uuid, client: &VboardRecipient) {
+        if let Some(announcements) = self.vb_announcements.get(&event_id) {
+            announcements
+                .iter()
+                .for_each(|announcement| send_msg(client, &VboardMsg::Announcement(announcement)));
+        }
+    }
+
+    // Active announcement is kept and sent to clients of its event until it expires
+    fn announce(&mut self, announcement: Announcement, ctx: &mut Context<Self>) {
+        let (event_id, id) = (announcement.event_id, announcement.id);
+        if let Some(expires_at) = announcement.expires_at {
+            let ttl = (expires_at - Utc::now()).to_std().unwrap_or_default();
+            ctx.run_later(ttl, move |act, _ctx| {
+                act.retract(event_id, id);
+            });
+        }
+        self.broadcast_msg(event_id, &VboardMsg::Announcement(&announcement));
+        self.vb_announcements
+            .entry(event_id)
+            .or_default()
+            .push(announcement);
+    }
+
+    fn retract(&mut self, event_id: Uuid, id: Uuid) -> bool {
+        let Some(announcements) = self.vb_announcements.get_mut(&event_id) else {
+            return false;
+        };
+        let len = announcements.len();
+        announcements.retain(|announcement| announcement.id != id);
+        if announcements.len() == len {
+            return false;
+        }
+        if announcements.is_empty() {
+            self.vb_announcements.remove(&event_id);
+        }
+        self.broadcast_msg(event_id, &VboardMsg::AnnouncementRemoved { id });
+        true
+    }
+
+    // Announcements still active when the server went down
+    pub fn load_announcements(&mut self, ctx: &mut Context<Self>) {
+        let db_pool = self.db_pool.clone();
+        async move {
+            Announcement::get_active(&db_pool)
+                .await
+                .map_err(|e| e.to_string())
+        }
+        .into_actor(self)
+        .map(|res, act, ctx| match res {
+            Ok(announcements) => announcements
+                .into_iter()
+                .for_each(|announcement| act.announce(announcement, ctx)),
+            Err(e) => log::error!("Error loading Announcements : {}", e),
+        })
+        .wait(ctx);
+    }
+
+    // Running clocks are pushed every tick, the last one reaching zero included
+    pub fn tick_clocks(&mut self) {
+        let now = Utc::now();
+        for (event_id, clock) in self.vb_clocks.iter() {
+            let running = match clock {
+                EventClock::Running { ends_at } => {
+                    now - *ends_at < Duration::milliseconds(CLOCK_TICK.as_millis() as i64)
+                }
+                EventClock::Paused { .. } => false,
+            };
+            if running {
+                self.broadcast_msg(*event_id, &clock_msg(*event_id, clock));
+            }
+        }
+    }
+
+    // Serialized once for every client of the event
+    fn broadcast_msg(&self, event_id: Uuid, msg: &VboardMsg) {
+        let Some(clients) = self.vb_addr.get(&event_id) else {
+            return;
+        };
+        match serde_json::to_string(msg) {
+            Ok(msg_str) => clients
+                .iter()
+                .for_each(|client| client.do_send(VboardRes(msg_str.clone().into()))),
+            Err(e) => log::debug!("Error sending Vaderboard : {}", e),
+        }
+    }
+
+    pub fn remove_stale(&mut self) {
+        let stale: Vec<VboardRecipient> = self
+            .vb_subs
+            .keys()
+            .filter(|client| !client.connected())
+            .cloned()
+            .collect();
+        for client in stale {
+            if let Some(sub) = self.vb_subs.remove(&client) {
+                self.remove_client(sub.event_id, &client);
+                log::debug!("Removed stale client from event [{}]", sub.event_id);
+            }
+        }
+    }
+
+    fn remove_client(&mut self, event_id: Uuid, client: &VboardRecipient) {
+        if let Some(clients) = self.vb_addr.get_mut(&event_id) {
+            clients.remove(client);
+            if clients.is_empty() {
+                self.vb_addr.remove(&event_id);
+                self.vb_cache.remove(&event_id);
+            }
+        }
+    }
+
+    // Fetch vaderboard once for all requests of the window, one fetch per event at a time
+    fn flush(&mut self, event_id: Uuid, ctx: &mut Context<Self>) {
+        if self.vb_fetching.contains(&event_id) {
+            ctx.run_later(self.vb_window, move |act, ctx| act.flush(event_id, ctx));
+            return;
+        }
+        let unicast = match self.vb_pending.remove(&event_id) {
+            Some(unicast) => unicast,
+            None => return,
+        };
+        self.vb_fetching.insert(event_id);
+        let etag = self.etag(event_id);
+        fetch_vboard(self.app_state.clone(), self.db_pool.clone(), event_id)
+            .into_actor(self)
+            .map(move |vb_res, act, _ctx| {
+                act.vb_fetching.remove(&event_id);
+                match vb_res {
+                    Ok(Some(vboard)) => {
+                        act.send_vboard(event_id, &vboard, unicast);
+                        // a board fetched before the last change would be cached under the new ETag
+                        if act.vb_addr.contains_key(&event_id) && act.etag(event_id) == etag {
+                            act.vb_cache.insert(event_id, vboard);
+                        }
+                    }
+                    Ok(None) => unicast.iter().for_each(|addr| {
+                        send_msg(
+                            addr,
+                            &VboardMsg::Error {
+                                msg: "No event found",
+                            },
+                        )
+                    }),
+                    Err(e) => {
+                        log::debug!("Error sending Vaderboard : {}", e);
+                        unicast
+                            .iter()
+                            .for_each(|addr| send_msg(addr, &VboardMsg::Error { msg: &e }))
+                    }
+                }
+            })
+            .spawn(ctx);
+    }
+
+    fn etag(&self, event_id: Uuid) -> String {
+        let version = self.vb_versions.get(&event_id).copied().unwrap_or_default();
+        format!("{:x}-{}", self.vb_epoch, version)
+    }
+
+    // Diff of its window to every client of the event, snapshot to clients which asked for it
+    fn send_vboard(
+        &mut self,
+        event_id: Uuid,
+        entries: &[VboardEntry<'static>],
+        unicast: Vec<VboardRecipient>,
+    ) {
+        let vb_count = self.app_state.vb_count;
+        if let Some(clients) = self.vb_addr.get(&event_id) {
+            for addr in clients.iter() {
+                let sub = match self.vb_subs.get_mut(addr) {
+                    Some(sub) => sub,
+                    None => continue,
+                };
+                let rows = sub.window.rows(entries, vb_count);
+                if unicast.contains(addr) {
+                    send_snapshot(addr, sub, entries, vb_count);
+                } else if let Some(diff) = sub.diff(rows) {
+                    sub.seq += 1;
+                    send_msg(addr, &diff);
+                    sub.entries = rows.to_vec();
+                }
+            }
+        }
+    }
+}
+impl Handler<VbAnnounce> for VboardSrv {
+    type Result = ();
+    fn handle(&mut self, msg: VbAnnounce, ctx: &mut Self::Context) -> Self::Result {
+        let (event_id, id) = (msg.0.event_id, msg.0.id);
+        self.announce(msg.0, ctx);
         log::debug!(
-            "Client Disconnected.Total connection count : {}",
-            self.vb_addr.len()
-        )
+            "Announcement [{}] sent to {} clients of event [{}]",
+            id,
+            self.vb_addr
+                .get(&event_id)
+                .map_or(0, |clients| clients.len()),
+            event_id
+        );
+    }
+}
+impl Handler<VbRetract> for VboardSrv {
+    type Result = bool;
+    fn handle(&mut self, msg: VbRetract, _ctx: &mut Self::Context) -> Self::Result {
+        self.retract(msg.0, msg.1)
     }
 }
-impl Handler<VboardRes<'static>> for VboardSrv {
+impl Handler<VbAnnouncements> for VboardSrv {
+    type Result = Vec<Announcement>;
+    fn handle(&mut self, msg: VbAnnouncements, _ctx: &mut Self::Context) -> Self::Result {
+        self.vb_announcements
+            .get(&msg.0)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+impl Handler<VbClock> for VboardSrv {
     type Result = ();
-    fn handle(&mut self, msg: VboardRes<'static>, _ctx: &mut Self::Context) -> Self::Result {
-        let vb_str = msg.0;
-        if let Some(transfer) = msg.1 {
-            match transfer {
-                TransferType::Unicast(addr) => addr.do_send(VboardRes(vb_str, None)),
-                TransferType::Broadcast => {
-                    self.vb_addr
-                        .iter()
-                        .for_each(|addr| addr.do_send(VboardRes(vb_str.clone(), None)));
+    fn handle(&mut self, msg: VbClock, _ctx: &mut Self::Context) -> Self::Result {
+        let VbClock(event_id, clock) = msg;
+        match clock {
+            Some(clock) => {
+                self.broadcast_msg(event_id, &clock_msg(event_id, &clock));
+                self.vb_clocks.insert(event_id, clock);
+            }
+            None => {
+                if self.vb_clocks.remove(&event_id).is_some() {
+                    self.broadcast_msg(event_id, &VboardMsg::ClockStopped { event_id });
                 }
             }
         }
     }
 }
-impl Handler<VboardGet> for VboardSrv {
+
+impl Handler<VbEventState> for VboardSrv {
     type Result = ();
-    fn handle(&mut self, msg: VboardGet, ctx: &mut Self::Context) -> Self::Result {
-        let addr = ctx.address();
-        let event_lock = self.app_state.clone();
-        let db_pool = self.db_pool.clone();
-        let vb_count = self.app_state.vb_count;
-        async move {
-            let event = event_lock.as_ref().current_event.lock().await;
-            if let Some(e) = event.as_ref() {
-                let vb_res = e.get_vboard(&db_pool, vb_count).await;
-                match vb_res {
-                    Ok(vb_str) => addr.do_send(VboardRes(vb_str, Some(msg.0))),
-                    Err(e) => log::debug!("Error sending Vaderboard : {}", e),
+    fn handle(&mut self, msg: VbEventState, _ctx: &mut Self::Context) -> Self::Result {
+        let VbEventState(event_id, state) = msg;
+        match state {
+            EventQueryState::Paused => {
+                if self.vb_paused.insert(event_id) {
+                    self.broadcast_msg(event_id, &VboardMsg::EventPaused { event_id });
                 }
             }
+            EventQueryState::Start => {
+                if self.vb_paused.remove(&event_id) {
+                    self.broadcast_msg(event_id, &VboardMsg::EventResumed { event_id });
+                }
+            }
+            // a paused event that stopped is not resumed
+            EventQueryState::Added | EventQueryState::Stop => {
+                self.vb_paused.remove(&event_id);
+            }
         }
-        .into_actor(self)
-        .wait(ctx)
+    }
+}
+
+impl Handler<VbResync> for VboardSrv {
+    type Result = ();
+    fn handle(&mut self, msg: VbResync, ctx: &mut Self::Context) -> Self::Result {
+        if let Some(event_id) = self.vb_subs.get(&msg.0).map(|sub| sub.event_id) {
+            self.request_vboard(event_id, Some(msg.0), ctx);
+        }
+    }
+}
+
+// Polls are answered from the cache, the board is only fetched after a change
+impl Handler<VbPoll> for VboardSrv {
+    type Result = ResponseActFuture<Self, Result<Option<VboardPoll>, String>>;
+    fn handle(&mut self, msg: VbPoll, _ctx: &mut Self::Context) -> Self::Result {
+        let VbPoll(event_id, window) = msg;
+        let vb_count = self.app_state.vb_count;
+        let etag = self.etag(event_id);
+        if let Some(entries) = self.vb_cache.get(&event_id) {
+            let entries = window.rows(entries, vb_count).to_vec();
+            return Box::pin(fut::ready(Ok(Some(VboardPoll { etag, entries }))));
+        }
+        Box::pin(
+            fetch_vboard(self.app_state.clone(), self.db_pool.clone(), event_id)
+                .into_actor(self)
+                .map(move |vb_res, act, _ctx| {
+                    let Some(vboard) = vb_res? else {
+                        return Ok(None);
+                    };
+                    let entries = window.rows(&vboard, vb_count).to_vec();
+                    // standings changed while fetching, the next poll fetches again
+                    if act.etag(event_id) == etag {
+                        act.vb_cache.insert(event_id, vboard);
+                    }
+                    Ok(Some(VboardPoll { etag, entries }))
+                }),
+        )
+    }
+}
+
+impl Handler<VboardGet> for VboardSrv {
+    type Result = ();
+    fn handle(&mut self, msg: VboardGet, ctx: &mut Self::Context) -> Self::Result {
+        let client = match msg.1 {
+            TransferType::Unicast(addr) => Some(addr.recipient()),
+            TransferType::Broadcast => None,
+        };
+        self.request_vboard(msg.0, client, ctx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::*;
+
+    fn entries(ranks: &[u32]) -> Vec<VboardEntry<'static>> {
+        ranks
+            .iter()
+            .map(|&rank| VboardEntry {
+                rank,
+                id: Uuid::new_v4(),
+                name: Cow::Borrowed(""),
+                score: 100 - rank as i64,
+                logo: None,
+                solves: 0,
+                breakdown: None,
+                frozen: false,
+            })
+            .collect()
+    }
+
+    fn window_ranks(window: VboardWindow, entries: &[VboardEntry<'static>]) -> Vec<u32> {
+        window.rows(entries, 3).iter().map(|e| e.rank).collect()
+    }
+
+    #[test]
+    fn window_top() {
+        let entries = entries(&[1, 2, 3, 4, 5]);
+        assert_eq!(
+            window_ranks(VboardWindow::Top { count: None }, &entries),
+            [1, 2, 3]
+        );
+        assert_eq!(
+            window_ranks(VboardWindow::Top { count: Some(2) }, &entries),
+            [1, 2]
+        );
+        assert_eq!(
+            window_ranks(VboardWindow::Top { count: Some(10) }, &entries),
+            [1, 2, 3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn window_ranks_keep_ties() {
+        let entries = entries(&[1, 2, 2, 4, 5]);
+        assert_eq!(
+            window_ranks(VboardWindow::Ranks { from: 2, to: 3 }, &entries),
+            [2, 2]
+        );
+        assert_eq!(
+            window_ranks(VboardWindow::Ranks { from: 3, to: 4 }, &entries),
+            [4]
+        );
+        assert!(window_ranks(VboardWindow::Ranks { from: 6, to: 9 }, &entries).is_empty());
+    }
+
+    #[test]
+    fn window_around() {
+        let entries = entries(&[1, 2, 3, 4, 5]);
+        let around = |pos: usize, radius| VboardWindow::Around {
+            around: entries[pos].id,
+            radius,
+        };
+        assert_eq!(window_ranks(around(2, 1), &entries), [2, 3, 4]);
+        assert_eq!(window_ranks(around(0, 2), &entries), [1, 2, 3]);
+        assert_eq!(window_ranks(around(4, 2), &entries), [3, 4, 5]);
+        let missing = VboardWindow::Around {
+            around: Uuid::new_v4(),
+            radius: 2,
+        };
+        assert!(window_ranks(missing, &entries).is_empty());
+    }
+
+    #[test]
+    fn window_valid() {
+        assert!(VboardWindow::Ranks { from: 1, to: 1 }.is_valid());
+        assert!(!VboardWindow::Ranks { from: 0, to: 5 }.is_valid());
+        assert!(!VboardWindow::Ranks { from: 5, to: 2 }.is_valid());
+        assert!(VboardWindow::Top { count: Some(0) }.is_valid());
+    }
+
+    #[test]
+    fn diff_unchanged() {
+        let entries = entries(&[1, 2, 3]);
+        let mut sub = VboardSub::new(Uuid::new_v4(), VboardWindow::Top { count: None });
+        sub.entries = entries.clone();
+        assert!(sub.diff(&entries).is_none());
+    }
+
+    #[test]
+    fn diff_changes() {
+        let old = entries(&[1, 2, 3]);
+        let mut sub = VboardSub::new(Uuid::new_v4(), VboardWindow::Top { count: None });
+        sub.entries = old.clone();
+        sub.seq = 4;
+        // second overtakes first, third leaves and a new row enters
+        let mut new = vec![old[1].clone(), old[0].clone()];
+        new[0].rank = 1;
+        new[0].score = 120;
+        new[1].rank = 2;
+        new.extend(entries(&[3]));
+        let Some(VboardMsg::Diff {
+            seq,
+            changed,
+            entered,
+            left,
+        }) = sub.diff(&new)
+        else {
+            panic!("expected a diff");
+        };
+        assert_eq!(seq, 5);
+        let changed: Vec<_> = changed
+            .iter()
+            .map(|c| (c.entry.id, c.old_rank, c.old_score))
+            .collect();
+        assert_eq!(
+            changed,
+            [(old[1].id, 2, old[1].score), (old[0].id, 1, old[0].score)]
+        );
+        assert_eq!(entered.len(), 1);
+        assert_eq!(entered[0].id, new[2].id);
+        assert_eq!(left.len(), 1);
+        assert_eq!((left[0].id, left[0].old_rank), (old[2].id, 3));
     }
 }
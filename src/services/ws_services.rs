@@ -0,0 +1,64 @@
+use std::time::Instant;
+
+use actix_web::{web, HttpRequest};
+
+use crate::models::error_models::VaderError;
+use crate::models::ws_models::{WsConn, WsConns, WsLimits, WsPermit};
+
+impl WsLimits {
+    pub fn new(max_conns: usize, max_conns_per_ip: usize) -> Self {
+        WsLimits {
+            max_conns,
+            max_conns_per_ip,
+            conns: Default::default(),
+        }
+    }
+}
+
+impl WsConn {
+    // Take a connection slot for the request's peer ip
+    pub fn acquire<'a>(
+        limits: &web::Data<WsLimits>,
+        req: &HttpRequest,
+    ) -> Result<Self, VaderError<'a>> {
+        let ip = req
+            .peer_addr()
+            .ok_or(VaderError::ConnectionLimit("Unknown client address"))?
+            .ip();
+        let mut conns = limits.conns.lock().unwrap();
+        let WsConns { total, per_ip } = &mut *conns;
+        if *total >= limits.max_conns {
+            return Err(VaderError::ConnectionLimit(
+                "Server connection limit reached",
+            ));
+        }
+        let ip_conns = per_ip.entry(ip).or_default();
+        if *ip_conns >= limits.max_conns_per_ip {
+            return Err(VaderError::ConnectionLimit(
+                "Connection limit reached for address",
+            ));
+        }
+        *ip_conns += 1;
+        *total += 1;
+        Ok(WsConn {
+            last_seen: Instant::now(),
+            permit: WsPermit {
+                limits: limits.clone(),
+                ip,
+            },
+        })
+    }
+}
+
+impl Drop for WsPermit {
+    fn drop(&mut self) {
+        let mut conns = self.limits.conns.lock().unwrap();
+        conns.total -= 1;
+        if let Some(ip_conns) = conns.per_ip.get_mut(&self.ip) {
+            *ip_conns -= 1;
+            if *ip_conns == 0 {
+                conns.per_ip.remove(&self.ip);
+            }
+        }
+    }
+}
@@ -71,6 +71,7 @@ export const TeamEvent: React.FC<TeamEventProps> = ({
                                                 };
                                             }
                                         ).TeamEvent.team_size,
+                                        eventId: eventInfo.id,
                                     },
                                 });
                             }}
@@ -78,7 +79,8 @@ export const TeamEvent: React.FC<TeamEventProps> = ({
                             Add Team
                         </Button>
                         <TeamList
-                            url={teamCurFtsUrl}
+                            url={teamCurFtsUrl(eventInfo.id)}
+                            eventId={eventInfo.id}
                             updateScore={eventState === EventState.Start}
                         />
                     </Container>
@@ -92,11 +94,11 @@ export const TeamEvent: React.FC<TeamEventProps> = ({
         let reqUrl: string = "";
         switch (newState) {
             case EventState.Start: {
-                reqUrl = `${apiUrl}/admin/event/start`;
+                reqUrl = `${apiUrl}/admin/event/${eventInfo.id}/start`;
                 break;
             }
             case EventState.Stop: {
-                reqUrl = `${apiUrl}/admin/event/stop`;
+                reqUrl = `${apiUrl}/admin/event/${eventInfo.id}/stop`;
                 break;
             }
         }
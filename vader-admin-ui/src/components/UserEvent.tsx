@@ -67,13 +67,16 @@ export const UserEvent: React.FC<UserEventProps> = ({
                     <Container>
                         <Button
                             onClick={() => {
-                                navigate("/user/add");
+                                navigate("/user/add", {
+                                    state: { eventId: eventInfo.id },
+                                });
                             }}
                         >
                             Add User
                         </Button>
                         <UserList
-                            url={userCurFtsUrl}
+                            url={userCurFtsUrl(eventInfo.id)}
+                            eventId={eventInfo.id}
                             updateScore={eventState === EventState.Start}
                         />
                     </Container>
@@ -89,11 +92,11 @@ export const UserEvent: React.FC<UserEventProps> = ({
         let reqUrl: string = "";
         switch (newState) {
             case EventState.Start: {
-                reqUrl = `${apiUrl}/admin/event/start`;
+                reqUrl = `${apiUrl}/admin/event/${eventInfo.id}/start`;
                 break;
             }
             case EventState.Stop: {
-                reqUrl = `${apiUrl}/admin/event/stop`;
+                reqUrl = `${apiUrl}/admin/event/${eventInfo.id}/stop`;
                 break;
             }
         }
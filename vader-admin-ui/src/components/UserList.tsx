@@ -18,6 +18,7 @@ import { apiUrl } from "../utils/ApiUtils";
 
 interface UserListProps {
     url: string;
+    eventId?: string;
     updateScore?: boolean;
 }
 
@@ -79,7 +80,7 @@ const UserList = (props: UserListProps): JSX.Element => {
 
         const data = JSON.stringify(updatedScore);
 
-        fetch(`${apiUrl}/admin/score/update`, {
+        fetch(`${apiUrl}/admin/event/${props.eventId}/score/update`, {
             method: "POST",
             headers: {
                 "Content-Type": "application/json",
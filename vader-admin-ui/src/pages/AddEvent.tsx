@@ -65,7 +65,8 @@ const AddEvent: React.FC = (): JSX.Element => {
             body: JSON.stringify(data),
         });
         if (res.ok) {
-            navigate("/event");
+            const { id } = await res.json();
+            navigate("/event", { state: { eventId: id } });
         }
     };
     return (
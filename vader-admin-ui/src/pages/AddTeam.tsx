@@ -21,6 +21,7 @@ export const AddTeam = () => {
     const location = useLocation();
 
     const maxTeamSize = location.state?.maxTeamSize || 0;
+    const eventId = location.state?.eventId;
 
     const handleFormSubmit = async (e: React.FormEvent<HTMLFormElement>) => {
         e.preventDefault();
@@ -31,7 +32,7 @@ export const AddTeam = () => {
             return;
         }
 
-        const url = `${apiUrl}/admin/event/team/add/with_members`;
+        const url = `${apiUrl}/admin/event/${eventId}/team/add/with_members`;
         const data = {
             team_info: {
                 name: teamName,
@@ -55,6 +56,7 @@ export const AddTeam = () => {
                 navigate("/event", {
                     state: {
                         opt: TeamEventOpts.TeamList,
+                        eventId,
                     },
                 });
             } else {
@@ -1,16 +1,18 @@
 import { useState } from "react";
 import { Button, Container, TextField, Typography } from "@mui/material";
 import { apiUrl } from "../utils/ApiUtils";
-import { useNavigate } from "react-router-dom";
+import { useLocation, useNavigate } from "react-router-dom";
 import { UserEventOpts } from "../Types";
 
 export const AddUser: React.FC = () => {
     const [userName, setUserName] = useState("");
     const navigate = useNavigate();
+    const location = useLocation();
+    const eventId = location.state?.eventId;
 
     const handleFormSubmit = async (e: React.FormEvent<HTMLFormElement>) => {
         e.preventDefault();
-        const url = `${apiUrl}/admin/event/user/add`;
+        const url = `${apiUrl}/admin/event/${eventId}/user/add`;
         const formData = {
             name: userName,
         };
@@ -25,7 +27,7 @@ export const AddUser: React.FC = () => {
             if (response.ok) {
                 console.log("User added successfully!");
                 navigate("/event", {
-                    state: { opt: UserEventOpts.User },
+                    state: { opt: UserEventOpts.User, eventId },
                 });
             } else {
                 console.log("Failed to add user");
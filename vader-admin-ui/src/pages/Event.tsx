@@ -15,8 +15,11 @@ export const VaderEvent = (): JSX.Element => {
         (async () => {
             const url = `${apiUrl}/event/info`;
             const res = await fetch(url, { method: "GET" });
-            if (res.ok) {
-                const event: EventInfo = await res.json();
+            const events: Array<EventInfo> = res.ok ? await res.json() : [];
+            const event =
+                events.find((e) => e.id === location.state?.eventId) ??
+                events[0];
+            if (event) {
                 console.log("Getting cur event: ", event);
                 setCurEvent(event);
             } else {
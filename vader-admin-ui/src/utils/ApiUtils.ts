@@ -34,6 +34,9 @@ export const teamFtsUrl = `${apiUrlWs}/team/fts/20`;
 
 export const userFtsUrl = `${apiUrlWs}/user/fts/20`;
 
-export const userCurFtsUrl = `${apiUrlWs}/event/info/user/20`;
-export const teamCurFtsUrl = `${apiUrlWs}/event/info/team/20`;
-export const remMemCurFtsUrl = `${apiUrlWs}/event/info/team/rem_members/20`;
+export const userCurFtsUrl = (eventId: string): string =>
+  `${apiUrlWs}/event/${eventId}/info/user/20`;
+export const teamCurFtsUrl = (eventId: string): string =>
+  `${apiUrlWs}/event/${eventId}/info/team/20`;
+export const remMemCurFtsUrl = (eventId: string): string =>
+  `${apiUrlWs}/event/${eventId}/info/team/rem_members/20`;